@@ -1,23 +1,62 @@
 // app_state.rs
 use email_sender::EmailSender;
 use anyhow::Result;
+use tracing::info;
+use crate::auth::AuthService;
+use crate::config::Config;
+use crate::contact_store::ContactStore;
+use crate::health::{HealthRegistry, MailDirHealthCheck, SmtpHealthCheck};
+use crate::metrics::Metrics;
+use crate::notifications::Notifier;
 use crate::rate_limiter::RateLimiter;
+use crate::shutdown::Shutdown;
+use crate::webhooks::Webhooks;
 
 pub struct AppState {
     pub email_sender: EmailSender,
     pub rate_limiter: RateLimiter,
+    pub contact_store: ContactStore,
+    pub auth: AuthService,
+    pub health: HealthRegistry,
+    pub metrics: Metrics,
+    pub webhooks: Webhooks,
+    pub notifier: Notifier,
+    pub shutdown: Shutdown,
 }
 
 impl AppState {
-    pub async fn new(email_sender: EmailSender) -> Result<Self, anyhow::Error> {
+    pub async fn new(email_sender: EmailSender, shutdown: Shutdown) -> Result<Self, anyhow::Error> {
         let rate_limiter = RateLimiter::new();
-        
-        // Iniciar la limpieza automática de IPs expiradas
-        rate_limiter.start_auto_cleanup().await;
-        
+
+        // Recuperar las solicitudes guardadas en el último apagado
+        let contact_store = match Config::get_state_file() {
+            Some(path) => {
+                info!("Loading contact requests from {}", path.display());
+                ContactStore::load(path)?
+            }
+            None => ContactStore::new(),
+        };
+
+        // Dependencias comprobadas por /health/ready
+        let mut health = HealthRegistry::new();
+        // El transporte de captura guarda en memoria y no tiene nada que comprobar
+        match email_sender.transport_name() {
+            "smtp" => health.register(SmtpHealthCheck::new(email_sender.clone())),
+            "file" => health.register(MailDirHealthCheck::new(email_sender.clone())),
+            _ => {}
+        }
+
+        let metrics = Metrics::new()?;
         Ok(AppState {
+            notifier: Notifier::new(email_sender.clone(), &metrics)?,
             email_sender,
             rate_limiter,
+            contact_store,
+            auth: AuthService::from_config()?,
+            health,
+            metrics,
+            webhooks: Webhooks::from_config()?,
+            shutdown,
         })
     }
 }
\ No newline at end of file
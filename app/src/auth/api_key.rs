@@ -0,0 +1,78 @@
+use super::Role;
+use anyhow::{anyhow, Result};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+/// Prefix added to generated keys so they are easy to spot in logs and configs
+const KEY_PREFIX: &str = "bsk_";
+
+/// A configured API key: only the SHA-256 hash of the secret is kept in memory
+#[derive(Debug, Clone)]
+pub struct ApiKeyEntry {
+    pub name: String,
+    pub role: Role,
+    hash: [u8; 32],
+}
+
+impl ApiKeyEntry {
+    /// Parses a config entry in the form `name:role:sha256hex`
+    pub fn parse(entry: &str) -> Result<Self> {
+        let mut parts = entry.splitn(3, ':');
+        let (Some(name), Some(role), Some(hash)) = (parts.next(), parts.next(), parts.next()) else {
+            return Err(anyhow!("API key inválida '{}': se esperaba 'nombre:rol:hash'", entry));
+        };
+
+        let hash = hex::decode(hash.trim())
+            .ok()
+            .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+            .ok_or_else(|| anyhow!("API key '{}': el hash debe ser SHA-256 en hexadecimal", name))?;
+
+        Ok(Self {
+            name: name.trim().to_string(),
+            role: role.trim().parse()?,
+            hash,
+        })
+    }
+
+    /// Compares a presented key against the stored hash in constant time
+    pub fn matches(&self, key: &str) -> bool {
+        let candidate = Sha256::digest(key.as_bytes());
+        candidate.as_slice().ct_eq(&self.hash).into()
+    }
+}
+
+/// Hashes a plain API key into the hex form stored in configuration
+pub fn hash_api_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+/// Generates a new random API key
+pub fn generate_api_key() -> String {
+    let mut bytes = [0u8; 32];
+    rand::rng().fill_bytes(&mut bytes);
+    format!("{}{}", KEY_PREFIX, hex::encode(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_matches_entries() {
+        let key = generate_api_key();
+        let entry = ApiKeyEntry::parse(&format!("ci:viewer:{}", hash_api_key(&key))).unwrap();
+
+        assert_eq!(entry.name, "ci");
+        assert_eq!(entry.role, Role::Viewer);
+        assert!(entry.matches(&key));
+        assert!(!entry.matches("bsk_wrong"));
+    }
+
+    #[test]
+    fn rejects_malformed_entries() {
+        assert!(ApiKeyEntry::parse("ci:viewer").is_err());
+        assert!(ApiKeyEntry::parse("ci:root:abcd").is_err());
+        assert!(ApiKeyEntry::parse("ci:admin:not-hex").is_err());
+    }
+}
@@ -0,0 +1,77 @@
+use super::{Principal, Role};
+use anyhow::Result;
+use chrono::Utc;
+use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+
+/// Claims carried by the bearer tokens issued by this server
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: String,
+    pub role: Role,
+    pub iat: i64,
+    pub exp: i64,
+}
+
+/// Issues and verifies short-lived HS256 tokens
+pub struct JwtCodec {
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+    validation: Validation,
+    ttl_secs: u64,
+}
+
+impl JwtCodec {
+    pub fn new(secret: &[u8], ttl_secs: u64) -> Self {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.leeway = 0;
+
+        Self {
+            encoding_key: EncodingKey::from_secret(secret),
+            decoding_key: DecodingKey::from_secret(secret),
+            validation,
+            ttl_secs,
+        }
+    }
+
+    pub fn ttl_secs(&self) -> u64 {
+        self.ttl_secs
+    }
+
+    pub fn issue(&self, principal: &Principal) -> Result<String> {
+        let now = Utc::now().timestamp();
+        let claims = Claims {
+            sub: principal.subject.clone(),
+            role: principal.role,
+            iat: now,
+            exp: now + self.ttl_secs as i64,
+        };
+
+        Ok(encode(&Header::new(Algorithm::HS256), &claims, &self.encoding_key)?)
+    }
+
+    pub fn verify(&self, token: &str) -> Result<Principal> {
+        let data = decode::<Claims>(token, &self.decoding_key, &self.validation)?;
+        Ok(Principal {
+            subject: data.claims.sub,
+            role: data.claims.role,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_and_rejects_foreign_tokens() {
+        let codec = JwtCodec::new(b"secret-a", 60);
+        let principal = Principal { subject: "ops".into(), role: Role::Admin };
+
+        let token = codec.issue(&principal).unwrap();
+        assert_eq!(codec.verify(&token).unwrap(), principal);
+
+        let other = JwtCodec::new(b"secret-b", 60);
+        assert!(other.verify(&token).is_err());
+    }
+}
@@ -0,0 +1,265 @@
+pub mod api_key;
+pub mod jwt;
+
+use std::fmt;
+use std::future::{ready, Ready};
+use std::str::FromStr;
+
+use actix_web::dev::Payload;
+use actix_web::http::header;
+use actix_web::{web, FromRequest, HttpRequest};
+use anyhow::{anyhow, Result};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::app_state::AppState;
+use crate::config::Config;
+use crate::error::ApiError;
+use api_key::ApiKeyEntry;
+use jwt::JwtCodec;
+
+/// Header used to present a raw API key
+pub const API_KEY_HEADER: &str = "X-Api-Key";
+
+/// Access level granted to a caller. `Admin` implies every `Viewer` permission.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Viewer,
+    Admin,
+}
+
+impl FromStr for Role {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "viewer" => Ok(Role::Viewer),
+            "admin" => Ok(Role::Admin),
+            other => Err(anyhow!("Rol desconocido '{}'", other)),
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Role::Viewer => f.write_str("viewer"),
+            Role::Admin => f.write_str("admin"),
+        }
+    }
+}
+
+/// Identity resolved from an API key or a bearer token
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Principal {
+    pub subject: String,
+    pub role: Role,
+}
+
+/// Validates API keys and issues/verifies JWT bearer tokens
+pub struct AuthService {
+    api_keys: Vec<ApiKeyEntry>,
+    jwt: JwtCodec,
+}
+
+impl AuthService {
+    pub fn new(api_keys: Vec<ApiKeyEntry>, jwt: JwtCodec) -> Self {
+        Self { api_keys, jwt }
+    }
+
+    /// Builds the service from the `api_keys`, `jwt_secret` and `jwt_ttl` settings.
+    /// Without a configured secret a random one is generated, so tokens do not survive restarts.
+    pub fn from_config() -> Result<Self> {
+        let api_keys = Config::get_api_keys()
+            .iter()
+            .map(|entry| ApiKeyEntry::parse(entry))
+            .collect::<Result<Vec<_>>>()?;
+
+        let secret = match Config::get_jwt_secret() {
+            Some(secret) => secret.expose().as_bytes().to_vec(),
+            None => {
+                warn!("jwt_secret no configurado; se usará un secreto aleatorio por proceso");
+                let mut secret = vec![0u8; 32];
+                rand::rng().fill_bytes(&mut secret);
+                secret
+            }
+        };
+
+        if api_keys.is_empty() {
+            warn!("No hay API keys configuradas; los endpoints de administración no serán accesibles");
+        }
+
+        Ok(Self::new(api_keys, JwtCodec::new(&secret, Config::get_jwt_ttl().as_secs())))
+    }
+
+    pub fn authenticate_api_key(&self, key: &str) -> Option<Principal> {
+        self.api_keys.iter().find(|entry| entry.matches(key)).map(|entry| Principal {
+            subject: entry.name.clone(),
+            role: entry.role,
+        })
+    }
+
+    pub fn issue_token(&self, principal: &Principal) -> Result<String> {
+        self.jwt.issue(principal)
+    }
+
+    pub fn token_ttl_secs(&self) -> u64 {
+        self.jwt.ttl_secs()
+    }
+
+    /// Resolves the caller from `Authorization: Bearer` or the API key header
+    pub fn authenticate_request(&self, req: &HttpRequest) -> Result<Principal, AuthError> {
+        if let Some(value) = req.headers().get(header::AUTHORIZATION) {
+            let token = value
+                .to_str()
+                .ok()
+                .and_then(|v| v.strip_prefix("Bearer "))
+                .ok_or(AuthError::InvalidCredentials)?;
+            return self.jwt.verify(token.trim()).map_err(|_| AuthError::InvalidCredentials);
+        }
+
+        if let Some(value) = req.headers().get(API_KEY_HEADER) {
+            let key = value.to_str().map_err(|_| AuthError::InvalidCredentials)?;
+            return self.authenticate_api_key(key).ok_or(AuthError::InvalidCredentials);
+        }
+
+        Err(AuthError::MissingCredentials)
+    }
+}
+
+#[derive(Debug)]
+pub enum AuthError {
+    MissingCredentials,
+    InvalidCredentials,
+    Forbidden(Role),
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthError::MissingCredentials => f.write_str("Se requiere autenticación"),
+            AuthError::InvalidCredentials => f.write_str("Credenciales inválidas o expiradas"),
+            AuthError::Forbidden(role) => write!(f, "Se requiere el rol '{}'", role),
+        }
+    }
+}
+
+fn require_role(req: &HttpRequest, role: Role) -> Result<Principal, AuthError> {
+    let state = req
+        .app_data::<web::Data<AppState>>()
+        .expect("AppState no registrado en la aplicación");
+    let principal = state.auth.authenticate_request(req)?;
+
+    if principal.role < role {
+        return Err(AuthError::Forbidden(role));
+    }
+    Ok(principal)
+}
+
+/// Extractor that only succeeds for callers with the `admin` role
+#[derive(Debug)]
+pub struct AdminUser(pub Principal);
+
+impl FromRequest for AdminUser {
+    type Error = ApiError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(require_role(req, Role::Admin).map(AdminUser).map_err(ApiError::from))
+    }
+}
+
+/// Extractor that succeeds for `viewer` and `admin` callers
+#[derive(Debug)]
+pub struct ViewerUser(pub Principal);
+
+impl FromRequest for ViewerUser {
+    type Error = ApiError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(require_role(req, Role::Viewer).map(ViewerUser).map_err(ApiError::from))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{self, call, with_key, ADMIN_KEY, JWT_SECRET, VIEWER_KEY};
+    use actix_web::http::StatusCode;
+    use actix_web::{get, test, App, HttpResponse};
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use jwt::Claims;
+
+    #[get("/admin")]
+    async fn admin_only(admin: AdminUser) -> HttpResponse {
+        HttpResponse::Ok().body(admin.0.subject)
+    }
+
+    #[get("/viewer")]
+    async fn viewer_only(viewer: ViewerUser) -> HttpResponse {
+        HttpResponse::Ok().body(viewer.0.subject)
+    }
+
+    /// Token signed with `secret` for `role`, expiring `exp` seconds from now
+    fn token(secret: &[u8], role: Role, exp: i64) -> String {
+        let now = chrono::Utc::now().timestamp();
+        let claims = Claims { sub: "ops".to_string(), role, iat: now - 120, exp: now + exp };
+        encode(&Header::default(), &claims, &EncodingKey::from_secret(secret)).unwrap()
+    }
+
+    fn bearer(uri: &str, token: &str) -> test::TestRequest {
+        test::TestRequest::get().uri(uri).insert_header((header::AUTHORIZATION, format!("Bearer {}", token)))
+    }
+
+    #[actix_web::test]
+    async fn rejects_missing_and_invalid_credentials() {
+        let app = test::init_service(App::new().app_data(testing::app_state().await).service(admin_only)).await;
+
+        let (status, body) = call(&app, test::TestRequest::get().uri("/admin").to_request()).await;
+        assert_eq!((status, body["code"].as_str()), (StatusCode::UNAUTHORIZED, Some("unauthenticated")));
+
+        let invalid = [
+            with_key(test::TestRequest::get().uri("/admin"), "bsk_desconocida"),
+            bearer("/admin", "no-es-un-jwt"),
+            bearer("/admin", &token(b"otro-secreto", Role::Admin, 60)),
+            // Caducado hace un segundo: sin margen de tolerancia
+            bearer("/admin", &token(JWT_SECRET, Role::Admin, -1)),
+            test::TestRequest::get().uri("/admin").insert_header((header::AUTHORIZATION, "Basic b3BzOmNsYXZl")),
+        ];
+        for req in invalid {
+            let (status, body) = call(&app, req.to_request()).await;
+            assert_eq!((status, body["code"].as_str()), (StatusCode::UNAUTHORIZED, Some("invalid_credentials")));
+        }
+
+        // Un token inválido no cae a la API key de la misma petición
+        let req = with_key(bearer("/admin", "no-es-un-jwt"), ADMIN_KEY);
+        assert_eq!(call(&app, req.to_request()).await.0, StatusCode::UNAUTHORIZED);
+    }
+
+    #[actix_web::test]
+    async fn requires_the_role_of_the_endpoint() {
+        let app = test::init_service(
+            App::new().app_data(testing::app_state().await).service(admin_only).service(viewer_only),
+        )
+        .await;
+
+        let (status, body) = call(&app, with_key(test::TestRequest::get().uri("/admin"), VIEWER_KEY).to_request()).await;
+        assert_eq!((status, body["code"].as_str()), (StatusCode::FORBIDDEN, Some("forbidden")));
+        let (status, _) = call(&app, bearer("/admin", &token(JWT_SECRET, Role::Viewer, 60)).to_request()).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        // Admin incluye los permisos de viewer
+        for req in [
+            with_key(test::TestRequest::get().uri("/viewer"), VIEWER_KEY),
+            with_key(test::TestRequest::get().uri("/viewer"), ADMIN_KEY),
+            with_key(test::TestRequest::get().uri("/admin"), ADMIN_KEY),
+            bearer("/admin", &token(JWT_SECRET, Role::Admin, 60)),
+        ] {
+            let res = test::call_service(&app, req.to_request()).await;
+            assert_eq!(res.status(), StatusCode::OK);
+        }
+    }
+}
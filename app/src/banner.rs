@@ -0,0 +1,28 @@
+use email_sender::EmailSender;
+use tracing::info;
+
+use crate::common::ApplicationConfig;
+use crate::config::Config;
+use crate::cors::CorsSettings;
+
+/// Logs what the effective mode means for this process, one line per concern
+pub fn log_startup(email_sender: &EmailSender, cors: &CorsSettings) {
+    let mode = Config::get_mode();
+    let origins = if cors.allows_any_origin() {
+        "cualquier origen".to_string()
+    } else {
+        format!("{} orígenes permitidos", cors.allowed_origins.len())
+    };
+    let yes_no = |enabled: bool| if enabled { "sí" } else { "no" };
+
+    info!("{} {} en modo {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"), mode);
+    info!("  dirección:          http://{}", Config::get_addrs());
+    info!("  correo:             {} ({})", email_sender.transport_name(), email_sender.destination());
+    info!("  CORS:               {}", origins);
+    info!("  errores detallados: {}", yes_no(mode.verbose_errors()));
+    info!("  cabeceras seguras:  {}", yes_no(mode.strict()));
+    info!(
+        "  trazas OTLP:        {}",
+        Config::get_otel_endpoint().unwrap_or("desactivadas")
+    );
+}
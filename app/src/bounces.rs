@@ -0,0 +1,286 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::Result;
+use email_sender::bounce::parse_report;
+use email_sender::SuppressionList;
+use serde::Serialize;
+use tokio::time::interval;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+use utoipa::ToSchema;
+
+use crate::config::Config;
+use crate::services::{BackgroundService, ServiceFuture};
+
+/// Subdirectory of the mailbox the reports are moved to once read
+const PROCESSED_DIR: &str = "processed";
+/// Subdirectory for the files that could not be read or are not reports about our mail
+const FAILED_DIR: &str = "failed";
+
+/// What a bounce or complaint report changed in the suppression list
+#[derive(Debug, Default, PartialEq, Serialize, ToSchema)]
+pub struct BounceOutcome {
+    /// Addresses added to the list
+    pub suppressed: Vec<String>,
+    /// Recipients left out: soft bounces or addresses already suppressed
+    pub ignored: usize,
+    /// Configured recipients (admins, routing rules, forms) that bounced; never suppressed automatically
+    pub protected: Vec<String>,
+}
+
+/// Why a message did not change the suppression list
+#[derive(Debug)]
+pub enum RecordError {
+    /// Not a DSN or ARF report
+    NotAReport,
+    /// The report is about a message this server did not send, or does not say who sent it
+    Foreign(Option<String>),
+    /// The suppression list could not be saved
+    Storage(anyhow::Error),
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecordError::NotAReport => f.write_str("El mensaje no es un informe de rebote ni de queja"),
+            RecordError::Foreign(Some(from)) => write!(f, "El informe se refiere a un correo de {}, no de este servidor", from),
+            RecordError::Foreign(None) => f.write_str("El informe no incluye las cabeceras del correo original"),
+            RecordError::Storage(e) => write!(f, "No se pudo guardar la lista de supresión: {}", e),
+        }
+    }
+}
+
+/// Which reports are trusted and which recipients they may suppress
+#[derive(Debug, Clone)]
+pub struct BounceFilter {
+    /// `From` of the mail this server sends; reports about other senders are forged or misdirected
+    sender: String,
+    protected: HashSet<String>,
+}
+
+impl BounceFilter {
+    pub fn new(sender: &str, protected: &[String]) -> Self {
+        Self {
+            sender: sender.trim().to_lowercase(),
+            protected: protected.iter().map(|a| a.trim().to_lowercase()).collect(),
+        }
+    }
+
+    /// `smtp_from` and the configured recipients, read on every call so reloads apply
+    pub fn from_config() -> Self {
+        Self::new(Config::get_smtp_from(), &Config::get_configured_recipients())
+    }
+}
+
+/// Suppresses the hard-bounced and complaining recipients of a DSN or ARF report about mail we sent
+pub fn record(suppressions: &SuppressionList, filter: &BounceFilter, raw: &[u8]) -> Result<BounceOutcome, RecordError> {
+    let report = parse_report(raw).ok_or(RecordError::NotAReport)?;
+    if report.original_from.as_ref().map(|from| from.to_lowercase()) != Some(filter.sender.clone()) {
+        return Err(RecordError::Foreign(report.original_from));
+    }
+
+    let mut outcome = BounceOutcome::default();
+    for bounce in report.bounces {
+        let Some(reason) = bounce.suppression_reason() else {
+            debug!(address = %bounce.address, kind = ?bounce.kind, "Rebote temporal; no se suprime");
+            outcome.ignored += 1;
+            continue;
+        };
+        if filter.protected.contains(&bounce.address.trim().to_lowercase()) {
+            warn!(address = %bounce.address, %reason, "Un destinatario configurado rebotó; no se suprime, revisa la dirección");
+            outcome.protected.push(bounce.address);
+            continue;
+        }
+        if suppressions.add(&bounce.address, reason, bounce.detail()).map_err(RecordError::Storage)? {
+            info!(address = %bounce.address, %reason, "Dirección añadida a la lista de supresión");
+            outcome.suppressed.push(bounce.address);
+        } else {
+            debug!(address = %bounce.address, "La dirección ya estaba suprimida");
+            outcome.ignored += 1;
+        }
+    }
+    Ok(outcome)
+}
+
+/// Reads the reports the MTA leaves in `bounce_mailbox_dir`. With a Maildir layout only `new/` is read,
+/// where messages appear once complete; otherwise files are left alone until they have not changed for a
+/// whole poll interval, so a report the MTA is still writing is not read half-way.
+/// Reports are moved to `processed/`, and files that can't be read or are not reports about our mail to `failed/`.
+#[derive(Clone)]
+pub struct BounceMailbox {
+    dir: PathBuf,
+    poll_interval: Duration,
+    suppressions: SuppressionList,
+}
+
+impl BounceMailbox {
+    pub fn new(dir: &Path, poll_interval: Duration, suppressions: SuppressionList) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            poll_interval,
+            suppressions,
+        }
+    }
+
+    /// Processes every complete file in the mailbox; returns how many were moved out of it.
+    /// A bad file never stops the scan; only a mailbox that can't be listed is an error.
+    fn scan(&self) -> Result<usize> {
+        let maildir = self.dir.join("new");
+        let (inbox, settled) = if maildir.is_dir() {
+            (maildir, Duration::ZERO)
+        } else {
+            (self.dir.clone(), self.poll_interval)
+        };
+
+        let filter = BounceFilter::from_config();
+        let mut moved = 0;
+        for entry in fs::read_dir(&inbox)? {
+            let Ok(entry) = entry else { continue };
+            let path = entry.path();
+            let Ok(metadata) = entry.metadata() else { continue };
+            let age = metadata.modified().ok().and_then(|modified| modified.elapsed().ok());
+            if !metadata.is_file() || age.is_none_or(|age| age < settled) {
+                continue;
+            }
+
+            let target = match fs::read(&path).map_err(|e| e.to_string()) {
+                Ok(raw) => match record(&self.suppressions, &filter, &raw) {
+                    Ok(outcome) => {
+                        debug!(file = %path.display(), suppressed = outcome.suppressed.len(), "Informe de rebote procesado");
+                        PROCESSED_DIR
+                    }
+                    // No es culpa del archivo: se deja en el buzón para el siguiente intento
+                    Err(RecordError::Storage(e)) => {
+                        warn!(file = %path.display(), error = %e, "No se pudo guardar la lista de supresión; se reintentará");
+                        continue;
+                    }
+                    Err(e) => {
+                        warn!(file = %path.display(), "{}", e);
+                        FAILED_DIR
+                    }
+                },
+                Err(e) => {
+                    warn!(file = %path.display(), error = %e, "No se pudo leer el informe de rebote");
+                    FAILED_DIR
+                }
+            };
+            match move_into(&path, &self.dir.join(target)) {
+                Ok(()) => moved += 1,
+                Err(e) => warn!(file = %path.display(), error = %e, "No se pudo mover el informe a {}/", target),
+            }
+        }
+        Ok(moved)
+    }
+}
+
+fn move_into(path: &Path, dir: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let name = path.file_name().unwrap_or_default();
+    fs::rename(path, dir.join(name))
+}
+
+impl BackgroundService for BounceMailbox {
+    fn name(&self) -> &'static str {
+        "bounce_mailbox"
+    }
+
+    fn run(&self, shutdown: CancellationToken) -> ServiceFuture<'_> {
+        Box::pin(async move {
+            let mut interval = interval(self.poll_interval);
+            while shutdown.run_until_cancelled(interval.tick()).await.is_some() {
+                let mailbox = self.clone();
+                // Un buzón inaccesible se vuelve a intentar en el siguiente ciclo sin parar el servicio
+                if let Err(e) = tokio::task::spawn_blocking(move || mailbox.scan()).await? {
+                    warn!(dir = %self.dir.display(), error = %e, "No se pudo leer el buzón de rebotes");
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use email_sender::SuppressionReason;
+
+    const DSN: &str = "Content-Type: multipart/report; report-type=delivery-status; boundary=\"b\"\r\n\r\n\
+--b\r\nContent-Type: message/delivery-status\r\n\r\n\
+Reporting-MTA: dns; mx.tusitio.com\r\n\r\n\
+Final-Recipient: rfc822; nadie@example.com\r\nAction: failed\r\nStatus: 5.1.1\r\n\r\n\
+Final-Recipient: rfc822; lleno@example.com\r\nAction: failed\r\nStatus: 4.2.2\r\n\r\n\
+Final-Recipient: rfc822; admin@example.com\r\nAction: failed\r\nStatus: 5.2.1\r\n\r\n\
+--b\r\nContent-Type: text/rfc822-headers\r\n\r\n\
+From: no-reply@tusitio.com\r\nTo: nadie@example.com, lleno@example.com, admin@example.com\r\n\r\n\
+--b--\r\n";
+
+    fn filter() -> BounceFilter {
+        BounceFilter::new("No-Reply@tusitio.com", &["Admin@Example.com".to_string()])
+    }
+
+    #[test]
+    fn suppresses_the_hard_bounces_of_the_mailbox() {
+        let dir = std::env::temp_dir().join(format!("bounces-{}", std::process::id()));
+        fs::create_dir_all(dir.join("new")).unwrap();
+        fs::write(dir.join("new").join("1.eml"), "Subject: Hola\r\n\r\nNo es un rebote").unwrap();
+        fs::write(dir.join("new").join("2.eml"), DSN).unwrap();
+        fs::write(dir.join("new").join("3.eml"), DSN.replace("no-reply@tusitio.com", "otro@example.org")).unwrap();
+
+        // En un Maildir los mensajes de new/ ya están completos y se leen sin esperar
+        let suppressions = SuppressionList::new();
+        let mailbox = BounceMailbox::new(&dir, Duration::from_secs(3600), suppressions.clone());
+        assert_eq!(mailbox.scan().unwrap(), 3);
+        assert_eq!(mailbox.scan().unwrap(), 0);
+
+        assert_eq!(suppressions.len(), 1);
+        assert_eq!(suppressions.get("nadie@example.com").unwrap().reason, SuppressionReason::HardBounce);
+        assert!(dir.join(PROCESSED_DIR).join("2.eml").exists());
+        assert!(dir.join(FAILED_DIR).join("1.eml").exists());
+        assert!(dir.join(FAILED_DIR).join("3.eml").exists());
+
+        // El mismo informe otra vez no duplica nada
+        let outcome = record(&suppressions, &filter(), DSN.as_bytes()).unwrap();
+        assert_eq!(
+            outcome,
+            BounceOutcome { suppressed: Vec::new(), ignored: 2, protected: vec!["admin@example.com".to_string()] }
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn leaves_files_that_may_still_be_written() {
+        let dir = std::env::temp_dir().join(format!("bounces-plain-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("1.eml"), DSN).unwrap();
+
+        let suppressions = SuppressionList::new();
+        assert_eq!(BounceMailbox::new(&dir, Duration::from_secs(3600), suppressions.clone()).scan().unwrap(), 0);
+        assert!(dir.join("1.eml").exists());
+
+        assert_eq!(BounceMailbox::new(&dir, Duration::ZERO, suppressions.clone()).scan().unwrap(), 1);
+        assert!(dir.join(PROCESSED_DIR).join("1.eml").exists());
+        assert_eq!(suppressions.len(), 1);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn ignores_reports_about_mail_we_did_not_send() {
+        let suppressions = SuppressionList::new();
+        let forged = DSN.replace("From: no-reply@tusitio.com", "From: alguien@example.org");
+        assert!(matches!(
+            record(&suppressions, &filter(), forged.as_bytes()),
+            Err(RecordError::Foreign(Some(from))) if from == "alguien@example.org"
+        ));
+
+        let without_headers = DSN.replace("From: no-reply@tusitio.com\r\n", "");
+        assert!(matches!(record(&suppressions, &filter(), without_headers.as_bytes()), Err(RecordError::Foreign(None))));
+        assert!(matches!(record(&suppressions, &filter(), b"Subject: Hola\r\n\r\nHola"), Err(RecordError::NotAReport)));
+        assert!(suppressions.is_empty());
+    }
+}
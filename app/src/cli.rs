@@ -0,0 +1,164 @@
+use std::path::PathBuf;
+
+use crate::auth::api_key::{generate_api_key, hash_api_key};
+use crate::auth::Role;
+use crate::common::ApplicationConfig;
+use crate::config::{secrets_file, Config, ConfigSources, DEFAULT_ENV_PREFIX};
+use email_sender::DkimSigner;
+use anyhow::{anyhow, Result};
+use zeroize::Zeroizing;
+use clap::{Parser, Subcommand};
+
+/// Command line of the server. Without a subcommand the server starts.
+/// Flags are the last configuration layer, above the file and the environment.
+#[derive(Debug, Parser)]
+#[command(name = "app", version, about = "Servidor de formularios de contacto")]
+pub struct Cli {
+    /// Archivo de configuración TOML o YAML (por defecto `<prefijo>CONFIG_FILE`)
+    #[arg(short, long, value_name = "ARCHIVO")]
+    pub config: Option<PathBuf>,
+
+    /// Prefijo de las variables de entorno de configuración
+    #[arg(long, default_value = DEFAULT_ENV_PREFIX, value_name = "PREFIJO")]
+    pub env_prefix: String,
+
+    /// Modo de ejecución (dev, prod, ...)
+    #[arg(long)]
+    pub mode: Option<String>,
+
+    #[arg(long)]
+    pub host: Option<String>,
+
+    #[arg(long)]
+    pub port: Option<String>,
+
+    /// Directivas de log al estilo `RUST_LOG`
+    #[arg(long, value_name = "FILTRO")]
+    pub log_filter: Option<String>,
+
+    /// Asigna cualquier clave de configuración, p. ej. `--set smtp_server=smtp.tusitio.com`
+    #[arg(long = "set", value_name = "CLAVE=VALOR", value_parser = parse_override)]
+    pub overrides: Vec<(String, String)>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Genera una API key nueva
+    GenerateApiKey {
+        name: String,
+        /// admin | viewer
+        role: Role,
+    },
+    /// Calcula la entrada de `api_keys` para una key existente
+    HashApiKey {
+        name: String,
+        /// admin | viewer
+        role: Role,
+        key: String,
+    },
+    /// Carga y valida la configuración sin arrancar el servidor
+    CheckConfig,
+    /// Genera una clave para el archivo de secretos cifrado (`secrets_key`)
+    SecretsKeygen,
+    /// Cifra un TOML de secretos con la clave de `<prefijo>SECRETS_KEY` o `<prefijo>SECRETS_KEY_FILE`
+    SecretsEncrypt {
+        input: PathBuf,
+        output: PathBuf,
+    },
+    /// Muestra el contenido de un archivo de secretos cifrado
+    SecretsDecrypt {
+        file: PathBuf,
+    },
+}
+
+fn parse_override(arg: &str) -> Result<(String, String), String> {
+    arg.split_once('=')
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .ok_or_else(|| format!("se esperaba CLAVE=VALOR, no '{}'", arg))
+}
+
+/// Key for the encrypted secrets file, from the environment like the server would read it
+fn secrets_key(prefix: &str) -> Result<Zeroizing<String>> {
+    dotenv::dotenv().ok();
+    let var = format!("{}SECRETS_KEY", prefix);
+    if let Ok(path) = std::env::var(format!("{}_FILE", var)) {
+        return Ok(Zeroizing::new(std::fs::read_to_string(path)?.trim().to_string()));
+    }
+    std::env::var(&var)
+        .map(Zeroizing::new)
+        .map_err(|_| anyhow!("Define {} o {}_FILE con la clave de secretos", var, var))
+}
+
+impl Cli {
+    /// Configuration layers selected by these arguments, with flags applied last
+    pub fn config_sources(&self) -> ConfigSources {
+        let mut sources = ConfigSources::from_env(&self.env_prefix);
+        if let Some(path) = &self.config {
+            sources.file = Some(path.clone());
+        }
+
+        let flags = [
+            ("mode", &self.mode),
+            ("host", &self.host),
+            ("port", &self.port),
+            ("log_filter", &self.log_filter),
+        ];
+        sources.overrides = flags
+            .into_iter()
+            .filter_map(|(key, value)| value.clone().map(|v| (key.to_string(), v)))
+            .chain(self.overrides.iter().cloned())
+            .collect();
+        sources
+    }
+}
+
+/// Runs a management subcommand
+pub fn run(command: &Command, cli: &Cli) -> Result<()> {
+    match command {
+        Command::GenerateApiKey { name, role } => {
+            let key = generate_api_key();
+            println!("API key (guárdala, no se volverá a mostrar): {}", key);
+            println!("Entrada para api_keys: {}:{}:{}", name, role, hash_api_key(&key));
+        }
+        Command::HashApiKey { name, role, key } => {
+            println!("{}:{}:{}", name, role, hash_api_key(key));
+        }
+        Command::CheckConfig => {
+            dotenv::dotenv().ok();
+            Config::init(&cli.config_sources())?;
+            println!(
+                "Configuración válida: modo '{}', escuchando en {}, correo por {}",
+                Config::get_mode(),
+                Config::get_addrs(),
+                Config::get_mail_transport()
+            );
+            for deprecation in Config::get_deprecations() {
+                println!("Aviso: {}", deprecation);
+            }
+            if let Some((domain, selector, key_file)) = Config::get_dkim() {
+                let signer = DkimSigner::from_file(domain, selector, key_file, Config::get_dkim_headers())?;
+                println!("Firma DKIM: {} con el selector '{}' ({})", domain, selector, signer.algorithm());
+            }
+        }
+        Command::SecretsKeygen => {
+            println!("{}", secrets_file::generate_key());
+        }
+        Command::SecretsEncrypt { input, output } => {
+            let key = secrets_key(&cli.env_prefix)?;
+            let plaintext = Zeroizing::new(std::fs::read_to_string(input)?);
+            toml::from_str::<toml::Table>(&plaintext).map_err(|e| anyhow!("TOML inválido: {}", e))?;
+            let sealed = secrets_file::encrypt(&key, &plaintext).map_err(|e| anyhow!(e))?;
+            std::fs::write(output, sealed)?;
+            println!("Secretos cifrados en {}", output.display());
+        }
+        Command::SecretsDecrypt { file } => {
+            let key = secrets_key(&cli.env_prefix)?;
+            let plaintext = secrets_file::decrypt(&key, &std::fs::read(file)?).map_err(|e| anyhow!(e))?;
+            print!("{}", plaintext.as_str());
+        }
+    }
+    Ok(())
+}
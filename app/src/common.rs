@@ -1,6 +1,20 @@
-use crate::config::Config;
-use tracing::Level;
-use tracing_subscriber;
+use std::net::TcpListener;
+
+use actix_web::dev::Server;
+use actix_web::web::{self, ServiceConfig};
+use actix_web::Scope;
+use crate::config::{Config, ConfigSources, LogFormat, Mode, DEFAULT_ENV_PREFIX};
+use crate::services::Services;
+use crate::shutdown::{self, Shutdown};
+use crate::telemetry;
+use crate::logging;
+use crate::openapi;
+use tracing::{info, warn};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::Layer;
+
+/// Prefix of the versioned API
+pub const API_PREFIX: &str = "/api/v1";
 
 /// Configuration trait that defines the contract for application settings
 /// Implementations should provide concrete values for server configuration
@@ -8,11 +22,11 @@ use tracing_subscriber;
 pub trait ApplicationConfig {
     fn get_addrs() -> String;
 
-    fn get_max_level_log() -> Level;
+    fn get_log_filter() -> &'static str;
 
-    fn get_mode() -> &'static str;
+    fn get_mode() -> Mode;
 
-    fn get_port() -> &'static str;
+    fn get_port() -> u16;
 
     fn get_host() -> &'static str;
 
@@ -21,20 +35,84 @@ pub trait ApplicationConfig {
     fn get_with_migrations() -> bool;
 }
 
+/// A group of handlers served under `prefix`, together with the OpenAPI document that describes them.
+/// Declared with the `controller!` macro so both come from the same list of handlers.
+#[derive(Clone, Copy)]
+pub struct Controller {
+    pub prefix: &'static str,
+    pub scope: fn() -> Scope,
+    pub openapi: fn() -> utoipa::openapi::OpenApi,
+}
+
+/// Controllers served by the application, collected through [`Application::routes`]
+/// and mounted on every worker by [`Routes::configure`]
+#[derive(Clone, Default)]
+pub struct Routes {
+    root: Vec<Controller>,
+    api: Vec<Controller>,
+}
+
+impl Routes {
+    /// A controller mounted at the root, outside the versioned API (health, metrics...)
+    pub fn root(&mut self, controller: Controller) -> &mut Self {
+        self.root.push(controller);
+        self
+    }
+
+    /// A controller mounted under `/api/v1`
+    pub fn api(&mut self, controller: Controller) -> &mut Self {
+        self.api.push(controller);
+        self
+    }
+
+    /// OpenAPI document of every registered controller, with the paths as they are served
+    pub fn openapi(&self) -> utoipa::openapi::OpenApi {
+        let root = self.root.iter().map(|c| (c.prefix.to_string(), c));
+        let api = self.api.iter().map(|c| (format!("{}{}", API_PREFIX, c.prefix), c));
+        root.chain(api)
+            .fold(openapi::base(), |doc, (prefix, c)| doc.nest(prefix, (c.openapi)()))
+    }
+
+    pub fn configure(&self, cfg: &mut ServiceConfig) {
+        for controller in &self.root {
+            cfg.service((controller.scope)());
+        }
+        let api = self.api.iter().fold(web::scope(API_PREFIX), |api, c| api.service((c.scope)()));
+        cfg.service(openapi::configure(api, self.openapi()));
+    }
+}
+
 /// Main application trait that defines the lifecycle and behavior of the server
 /// Provides default implementations for common initialization tasks
 pub trait Application {
-    /// Sets up logging infrastructure with tracing subscriber
-    /// Configures log level and span events for debugging
+    /// Loads and validates the layered configuration before anything reads it.
+    /// By default uses the `APP_*` environment variables and the file named by `APP_CONFIG_FILE`.
+    fn load_config(&self) -> anyhow::Result<()> {
+        Config::init(&ConfigSources::from_env(DEFAULT_ENV_PREFIX))
+    }
+
+    /// Sets up the single tracing pipeline used by the server
+    /// `log` records from dependencies are bridged into it, the output format comes from `log_format`
+    /// and the `log_filter` directives can be changed at runtime through [`logging::set_filter`].
+    /// Spans are also exported over OTLP when `otel_endpoint` is configured.
     fn initialize_logging(&self) -> anyhow::Result<()> {
-        env_logger::init();
+        let fmt_layer = match Config::get_log_format() {
+            LogFormat::Json => tracing_subscriber::fmt::layer()
+                .json()
+                .with_current_span(true)
+                .with_span_list(false)
+                .boxed(),
+            LogFormat::Pretty => tracing_subscriber::fmt::layer().pretty().boxed(),
+        };
 
-        let subscriber = tracing_subscriber::fmt()
-            .with_max_level(Config::get_max_level_log())
-            .with_span_events(tracing_subscriber::fmt::format::FmtSpan::CLOSE)
-            .finish();
+        let subscriber = tracing_subscriber::registry()
+            .with(logging::filter_layer(Config::get_log_filter())?)
+            .with(fmt_layer)
+            .with(telemetry::layer()?);
 
         tracing::subscriber::set_global_default(subscriber)?;
+        // Todos los registros de `log` pasan a tracing; el filtro recargable decide qué se emite
+        tracing_log::LogTracer::init()?;
         Ok(())
     }
 
@@ -44,18 +122,85 @@ pub trait Application {
         self.initialize_logging()
     }
 
+    /// Shutdown coordinator shared by the request handlers, the background services and the hooks
+    fn lifecycle(&self) -> &Shutdown;
+
     /// Application-specific setup logic (must be implemented by concrete types)
+    /// `on_shutdown` hooks are usually registered here through [`Application::lifecycle`]
     async fn setup(&self) -> anyhow::Result<()>;
 
-    /// Server creation logic (must be implemented by concrete types)
-    async fn create_server(&self) -> anyhow::Result<()>;
+    /// Registers the controllers to serve; called once after setup
+    fn routes(&self, _routes: &mut Routes) {}
+
+    /// Registers the background services to supervise; called once after setup
+    fn services(&self, _services: &mut Services) {}
+
+    /// Builds the HTTP server on `listener` with the registered routes (must be implemented by concrete types)
+    /// The returned server is not running yet.
+    fn create_server(&self, listener: TcpListener, routes: Routes) -> anyhow::Result<Server>;
+
+    /// Sets the application up, starts its services and builds the server on `listener`
+    async fn launch(&self, listener: TcpListener) -> anyhow::Result<Server> {
+        self.setup().await?;
 
-    /// Main entry point that orchestrates the complete application startup
-    /// Loads environment variables, initializes logging, sets up the app, and starts the server
+        let mut services = Services::new();
+        self.services(&mut services);
+        services.start(self.lifecycle());
+
+        let mut routes = Routes::default();
+        self.routes(&mut routes);
+        self.create_server(listener, routes)
+    }
+
+    /// Runs once the server has stopped accepting connections and drained its requests
+    /// Stops the background services, waits for queued tasks and then runs the `on_shutdown` hooks.
+    /// The wait only gets what is left of `shutdown_timeout` since the stop signal.
+    async fn shutdown(&self) -> anyhow::Result<()> {
+        let lifecycle = self.lifecycle();
+        let timeout = Config::get_shutdown_timeout();
+        if !lifecycle.drain(lifecycle.begin(timeout)).await {
+            warn!("Shutdown timeout of {:?} expired with {} tasks still running", timeout, lifecycle.pending());
+        }
+        lifecycle.run_hooks().await?;
+        info!("Shutdown complete");
+        Ok(())
+    }
+
+    /// Main entry point that orchestrates the complete application lifecycle
+    /// Loads environment variables and configuration, initializes logging, launches the app on the configured
+    /// address and, on SIGTERM or Ctrl-C, stops accepting connections and shuts down in order
     async fn start(&self) -> anyhow::Result<()> {
         dotenv::dotenv().ok();
+        self.load_config()?;
         self.initialize()?;
-        self.setup().await?;
-        self.create_server().await
+        for deprecation in Config::get_deprecations() {
+            warn!("{}", deprecation);
+        }
+
+        let result = async {
+            let server = self.launch(TcpListener::bind(Config::get_addrs())?).await?;
+
+            let handle = server.handle();
+            let lifecycle = self.lifecycle().clone();
+            tokio::spawn(async move {
+                shutdown::wait_for_signal().await;
+                // El servidor tiene `shutdown_timeout` para sus peticiones; el drenaje posterior, lo que quede
+                lifecycle.begin(Config::get_shutdown_timeout());
+                info!("Stopping the server: no new connections, waiting for in-flight requests");
+                handle.stop(true).await;
+            });
+
+            server.await?;
+            anyhow::Ok(())
+        }
+        .await;
+        let result = match self.shutdown().await {
+            Ok(()) => result,
+            Err(e) => result.and(Err(e)),
+        };
+
+        // Exportar los spans pendientes antes de salir
+        tokio::task::spawn_blocking(telemetry::shutdown).await?;
+        result
     }
-}
\ No newline at end of file
+}
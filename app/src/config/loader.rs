@@ -0,0 +1,895 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use actix_web::http::header::HeaderName;
+use actix_web::http::Method;
+use serde_json::Value;
+
+use super::{secrets_file, Config, LogFormat, MailTransport, Mode, Secret};
+use crate::auth::api_key::ApiKeyEntry;
+use crate::forms::{self, looks_like_email, Forms};
+use crate::i18n::Locale;
+use crate::logging;
+use crate::routing::{self, RoutingRules};
+
+/// Prefix of the environment variables read by default (`APP_PORT`, `APP_SMTP_SERVER`, ...)
+pub const DEFAULT_ENV_PREFIX: &str = "APP_";
+
+/// Every key the loader understands. Nested tables in config files are flattened with `_`,
+/// so `[smtp] server = "..."` is the key `smtp_server`, set from the environment as `APP_SMTP_SERVER`.
+/// Any key can also be read from a file with the `_file` suffix (`APP_SMTP_PASSWORD_FILE=/run/secrets/smtp`).
+const KEYS: &[&str] = &[
+    "mode",
+    "host",
+    "port",
+    "log_filter",
+    "log_format",
+    "otel_endpoint",
+    "otel_service_name",
+    "smtp_server",
+    "smtp_user",
+    "smtp_password",
+    "smtp_from",
+    "smtp_check_on_startup",
+    "mail_transport",
+    "mail_dir",
+    "dkim_domain",
+    "dkim_selector",
+    "dkim_key_file",
+    "dkim_headers",
+    "suppression_file",
+    "bounce_mailbox_dir",
+    "bounce_poll_interval",
+    "admin_emails",
+    "rate_limit_per_minute",
+    "rate_limit_per_12h",
+    "api_keys",
+    "jwt_secret",
+    "jwt_ttl",
+    "cors_allowed_origins",
+    "cors_allowed_methods",
+    "cors_allowed_headers",
+    "cors_allow_credentials",
+    "cors_max_age",
+    "shutdown_timeout",
+    "state_file",
+    "default_locale",
+    "contact_acknowledgement",
+    "contact_channels",
+    "routing_file",
+    "forms_file",
+    "webhook_secret",
+    "webhook_contact_received",
+    "webhook_form_submitted",
+    "webhook_max_attempts",
+    "webhook_timeout",
+    "secrets_file",
+    "secrets_key",
+];
+
+/// Standard variables honoured without the prefix, below the prefixed ones
+const ENV_ALIASES: &[(&str, &str)] = &[
+    ("log_filter", "RUST_LOG"),
+    ("otel_endpoint", "OTEL_EXPORTER_OTLP_ENDPOINT"),
+    ("otel_service_name", "OTEL_SERVICE_NAME"),
+];
+
+/// Unprefixed names read before the `APP_*` variables existed. Still honoured, below the standard aliases,
+/// so old deployments keep their mode and credentials; [`deprecated_variables`] reports them.
+const LEGACY_ENV: &[(&str, &str)] = &[
+    ("mode", "MODE"),
+    ("host", "HOST"),
+    ("port", "PORT"),
+    ("smtp_server", "SMTP_SERVER"),
+    ("smtp_user", "SMTP_USER"),
+    ("smtp_password", "SMTP_PASSWORD"),
+    ("smtp_from", "FROM_EMAIL"),
+    ("admin_emails", "ADMIN_EMAILS"),
+];
+
+/// Values shipped in `env.example`; a prod deployment still using them was never configured
+const EXAMPLE_VALUES: &[(&str, &str)] = &[
+    ("smtp_user", "pepe@gmail.com"),
+    ("smtp_password", "asd asd asd asd"),
+    ("smtp_from", "no-reply@gmail.com"),
+    ("admin_emails", "uwu@gmail.com"),
+    ("admin_emails", "owo@gmail.com"),
+    ("jwt_secret", "cambia-este-secreto"),
+];
+
+/// HS256 keys shorter than the hash output weaken the signature
+const MIN_JWT_SECRET_LEN: usize = 32;
+
+/// Where each configuration layer comes from
+#[derive(Debug, Clone, Default)]
+pub struct ConfigSources {
+    /// TOML or YAML file, chosen by extension
+    pub file: Option<PathBuf>,
+    pub env_prefix: String,
+    /// Snapshot of the environment, so loading does not depend on global state
+    pub env: HashMap<String, String>,
+    /// `key=value` pairs given on the command line, applied last
+    pub overrides: Vec<(String, String)>,
+}
+
+impl ConfigSources {
+    /// Process environment with the given prefix; the file comes from `<prefix>CONFIG_FILE` if set
+    pub fn from_env(env_prefix: &str) -> Self {
+        let env: HashMap<String, String> = std::env::vars().collect();
+        let file = env.get(&format!("{}CONFIG_FILE", env_prefix)).map(PathBuf::from);
+
+        Self {
+            file,
+            env_prefix: env_prefix.to_string(),
+            env,
+            overrides: Vec::new(),
+        }
+    }
+}
+
+/// One invalid key, with the layer that provided the offending value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    pub key: String,
+    pub origin: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.origin {
+            Some(origin) => write!(f, "{} ({}): {}", self.key, origin, self.message),
+            None => write!(f, "{}: {}", self.key, self.message),
+        }
+    }
+}
+
+/// Every problem found while loading the configuration
+#[derive(Debug)]
+pub struct ConfigError {
+    pub issues: Vec<ConfigIssue>,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Configuración inválida ({} errores):", self.issues.len())?;
+        for issue in &self.issues {
+            write!(f, "\n  - {}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Raw value before typed parsing: env vars and flags are always scalars, files may hold lists
+#[derive(Debug, Clone, PartialEq)]
+enum RawValue {
+    Scalar(String),
+    List(Vec<String>),
+}
+
+impl RawValue {
+    fn scalar(&self) -> Result<&str, String> {
+        match self {
+            RawValue::Scalar(s) => Ok(s.trim()),
+            RawValue::List(_) => Err("se esperaba un valor simple, no una lista".to_string()),
+        }
+    }
+
+    /// Lists can also be written as comma separated strings; empty items are ignored
+    fn list(&self) -> Vec<String> {
+        let items: Vec<&str> = match self {
+            RawValue::Scalar(s) => s.split(',').collect(),
+            RawValue::List(items) => items.iter().map(String::as_str).collect(),
+        };
+        items
+            .into_iter()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Empty strings unset optional keys
+    fn optional(&self) -> Result<Option<&str>, String> {
+        self.scalar().map(|s| Some(s).filter(|s| !s.is_empty()))
+    }
+}
+
+struct Setting {
+    key: String,
+    origin: String,
+    value: RawValue,
+}
+
+/// Applies defaults, file, environment and CLI layers in that order and validates the result
+pub fn load(sources: &ConfigSources) -> Result<Config, ConfigError> {
+    let mut issues = Vec::new();
+    let mut settings = Vec::new();
+
+    if let Some(path) = &sources.file {
+        match read_file(path) {
+            Ok(file_settings) => settings.extend(file_settings),
+            Err(message) => issues.push(ConfigIssue {
+                key: "config_file".to_string(),
+                origin: Some(path.display().to_string()),
+                message,
+            }),
+        }
+    }
+    let file_layer_len = settings.len();
+    settings.extend(env_settings(&sources.env, &sources.env_prefix));
+    settings.extend(sources.overrides.iter().map(|(key, value)| Setting {
+        key: key.trim().to_ascii_lowercase().replace(['-', '.'], "_"),
+        origin: format!("--set {}", key),
+        value: RawValue::Scalar(value.clone()),
+    }));
+
+    resolve_file_references(&mut settings, &mut issues);
+
+    // El archivo de secretos cifrado va justo después del archivo de configuración
+    match secrets_settings(&settings) {
+        Ok(secrets) => {
+            let mut secrets = secrets;
+            resolve_file_references(&mut secrets, &mut issues);
+            settings.splice(file_layer_len..file_layer_len, secrets);
+        }
+        Err(issue) => issues.push(issue),
+    }
+
+    let mut config = Config::default();
+    for setting in settings {
+        if let Err(message) = apply(&mut config, &setting.key, &setting.value) {
+            issues.push(ConfigIssue {
+                key: setting.key,
+                origin: Some(setting.origin),
+                message,
+            });
+        }
+    }
+
+    issues.extend(validate(&config));
+
+    if issues.is_empty() {
+        Ok(config)
+    } else {
+        Err(ConfigError { issues })
+    }
+}
+
+/// Warnings for the legacy unprefixed variables set in `sources`
+pub fn deprecated_variables(sources: &ConfigSources) -> Vec<String> {
+    LEGACY_ENV
+        .iter()
+        .filter(|(_, name)| sources.env.contains_key(*name))
+        .map(|(key, name)| {
+            format!(
+                "La variable {} está obsoleta; usa {}{}",
+                name,
+                sources.env_prefix,
+                key.to_ascii_uppercase()
+            )
+        })
+        .collect()
+}
+
+/// Replaces `<key>_file` settings with the contents of the named file, minus the trailing newline
+fn resolve_file_references(settings: &mut [Setting], issues: &mut Vec<ConfigIssue>) {
+    for setting in settings.iter_mut() {
+        let Some(key) = setting.key.strip_suffix("_file").filter(|key| KEYS.contains(key)) else {
+            continue;
+        };
+        let key = key.to_string();
+
+        let contents = setting
+            .value
+            .scalar()
+            .and_then(|path| fs::read_to_string(path).map_err(|e| format!("no se pudo leer '{}': {}", path, e)));
+        match contents {
+            Ok(contents) => {
+                setting.origin = format!("{} ({})", setting.origin, setting.value.scalar().unwrap_or_default());
+                setting.key = key;
+                setting.value = RawValue::Scalar(contents.trim_end_matches(['\r', '\n']).to_string());
+            }
+            Err(message) => issues.push(ConfigIssue {
+                key: setting.key.clone(),
+                origin: Some(setting.origin.clone()),
+                message,
+            }),
+        }
+    }
+}
+
+/// Settings stored in the encrypted `secrets_file`, decrypted with `secrets_key`
+fn secrets_settings(settings: &[Setting]) -> Result<Vec<Setting>, ConfigIssue> {
+    let last = |key: &str| settings.iter().rev().find(|s| s.key == key);
+    let Some(file) = last("secrets_file") else {
+        return Ok(Vec::new());
+    };
+    let issue = |message: String| ConfigIssue {
+        key: "secrets_file".to_string(),
+        origin: Some(file.origin.clone()),
+        message,
+    };
+
+    let path = file.value.scalar().map_err(issue)?;
+    let key = last("secrets_key")
+        .ok_or_else(|| issue("falta secrets_key para descifrarlo".to_string()))
+        .and_then(|key| key.value.scalar().map_err(issue))?;
+    let data = fs::read(path).map_err(|e| issue(format!("no se pudo leer '{}': {}", path, e)))?;
+    let plaintext = secrets_file::decrypt(key, &data).map_err(issue)?;
+
+    let mut secrets = parse_document(path, "toml", &plaintext).map_err(issue)?;
+    if let Some(nested) = secrets.iter().find(|s| s.key.starts_with("secrets_")) {
+        return Err(issue(format!("'{}' no puede definirse dentro del archivo de secretos", nested.key)));
+    }
+    for setting in &mut secrets {
+        setting.origin = format!("secretos {}", path);
+    }
+    Ok(secrets)
+}
+
+fn read_file(path: &Path) -> Result<Vec<Setting>, String> {
+    let contents = fs::read_to_string(path).map_err(|e| format!("no se pudo leer: {}", e))?;
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default();
+    parse_document(&path.display().to_string(), extension, &contents)
+}
+
+fn parse_document(origin: &str, extension: &str, contents: &str) -> Result<Vec<Setting>, String> {
+    let document: Value = match extension.to_ascii_lowercase().as_str() {
+        "toml" => toml::from_str(contents).map_err(|e| format!("TOML inválido: {}", e))?,
+        "yaml" | "yml" => serde_yaml::from_str(contents).map_err(|e| format!("YAML inválido: {}", e))?,
+        other => return Err(format!("formato no soportado '{}' (usa .toml, .yaml o .yml)", other)),
+    };
+
+    let Value::Object(table) = document else {
+        return Err("el documento debe ser una tabla de claves".to_string());
+    };
+
+    let mut settings = Vec::new();
+    flatten(origin, "", table, &mut settings);
+    Ok(settings)
+}
+
+/// Turns nested tables into `parent_child` keys
+fn flatten(origin: &str, prefix: &str, table: serde_json::Map<String, Value>, out: &mut Vec<Setting>) {
+    for (name, value) in table {
+        let key = if prefix.is_empty() {
+            name.to_ascii_lowercase()
+        } else {
+            format!("{}_{}", prefix, name.to_ascii_lowercase())
+        };
+
+        let value = match value {
+            Value::Null => continue,
+            Value::Object(nested) => {
+                flatten(origin, &key, nested, out);
+                continue;
+            }
+            Value::Array(items) => RawValue::List(items.into_iter().map(scalar_to_string).collect()),
+            other => RawValue::Scalar(scalar_to_string(other)),
+        };
+
+        out.push(Setting {
+            key,
+            origin: origin.to_string(),
+            value,
+        });
+    }
+}
+
+fn scalar_to_string(value: Value) -> String {
+    match value {
+        Value::String(s) => s,
+        other => other.to_string(),
+    }
+}
+
+fn env_settings(env: &HashMap<String, String>, prefix: &str) -> Vec<Setting> {
+    let mut settings = Vec::new();
+
+    for (key, alias) in LEGACY_ENV.iter().chain(ENV_ALIASES) {
+        if let Some(value) = env.get(*alias) {
+            settings.push(Setting {
+                key: key.to_string(),
+                origin: format!("variable {}", alias),
+                value: RawValue::Scalar(value.clone()),
+            });
+        }
+    }
+
+    for key in KEYS {
+        // Si se definen ambas, gana la variante `_FILE`
+        for (key, name) in [
+            (key.to_string(), format!("{}{}", prefix, key.to_ascii_uppercase())),
+            (format!("{}_file", key), format!("{}{}_FILE", prefix, key.to_ascii_uppercase())),
+        ] {
+            if let Some(value) = env.get(&name) {
+                settings.push(Setting {
+                    key,
+                    origin: format!("variable {}", name),
+                    value: RawValue::Scalar(value.clone()),
+                });
+            }
+        }
+    }
+
+    settings
+}
+
+fn parse<T>(value: &RawValue) -> Result<T, String>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    let raw = value.scalar()?;
+    raw.parse().map_err(|e| format!("valor inválido '{}': {}", raw, e))
+}
+
+fn parse_bool(value: &RawValue) -> Result<bool, String> {
+    match value.scalar()?.to_ascii_lowercase().as_str() {
+        "true" | "1" | "yes" | "on" => Ok(true),
+        "false" | "0" | "no" | "off" => Ok(false),
+        other => Err(format!("valor booleano inválido '{}'", other)),
+    }
+}
+
+fn parse_port(value: &RawValue) -> Result<u16, String> {
+    match parse::<u16>(value)? {
+        0 => Err("el puerto debe estar entre 1 y 65535".to_string()),
+        port => Ok(port),
+    }
+}
+
+/// Plain numbers are seconds; otherwise humantime syntax such as `15m` or `1h 30m`
+fn parse_duration(value: &RawValue) -> Result<Duration, String> {
+    let raw = value.scalar()?;
+    if let Ok(secs) = raw.parse::<u64>() {
+        return Ok(Duration::from_secs(secs));
+    }
+    humantime::parse_duration(raw).map_err(|e| format!("duración inválida '{}': {}", raw, e))
+}
+
+fn apply(config: &mut Config, key: &str, value: &RawValue) -> Result<(), String> {
+    match key {
+        "mode" => config.mode = parse::<Mode>(value)?,
+        "host" => config.host = value.scalar()?.to_string(),
+        "port" => config.port = parse_port(value)?,
+        "log_filter" => {
+            let directives = value.scalar()?;
+            logging::parse_filter(directives).map_err(|e| e.to_string())?;
+            config.log_filter = directives.to_string();
+        }
+        "log_format" => config.log_format = parse::<LogFormat>(value)?,
+        "otel_endpoint" => config.otel_endpoint = value.optional()?.map(str::to_string),
+        "otel_service_name" => config.otel_service_name = value.scalar()?.to_string(),
+        "smtp_server" => config.smtp_server = value.scalar()?.to_string(),
+        "smtp_user" => config.smtp_user = value.scalar()?.to_string(),
+        "smtp_password" => config.smtp_password = Secret::from(value.scalar()?),
+        "smtp_from" => config.smtp_from = value.scalar()?.to_string(),
+        "smtp_check_on_startup" => config.smtp_check_on_startup = parse_bool(value)?,
+        "mail_transport" => config.mail_transport = Some(parse::<MailTransport>(value)?),
+        "mail_dir" => config.mail_dir = PathBuf::from(value.scalar()?),
+        "dkim_domain" => config.dkim_domain = value.optional()?.map(str::to_string),
+        "dkim_selector" => config.dkim_selector = value.optional()?.map(str::to_string),
+        "dkim_key_file" => config.dkim_key_file = value.optional()?.map(PathBuf::from),
+        "dkim_headers" => config.dkim_headers = value.list(),
+        "suppression_file" => config.suppression_file = value.optional()?.map(PathBuf::from),
+        "bounce_mailbox_dir" => config.bounce_mailbox_dir = value.optional()?.map(PathBuf::from),
+        "bounce_poll_interval" => config.bounce_poll_interval = parse_duration(value)?,
+        "admin_emails" => config.admin_emails = value.list(),
+        "rate_limit_per_minute" => config.rate_limit_per_minute = parse(value)?,
+        "rate_limit_per_12h" => config.rate_limit_per_12h = parse(value)?,
+        "api_keys" => config.api_keys = value.list(),
+        "jwt_secret" => config.jwt_secret = value.optional()?.map(Secret::from),
+        "jwt_ttl" => config.jwt_ttl = parse_duration(value)?,
+        "cors_allowed_origins" => config.cors_allowed_origins = Some(value.list()),
+        "cors_allowed_methods" => config.cors_allowed_methods = Some(value.list()),
+        "cors_allowed_headers" => config.cors_allowed_headers = Some(value.list()),
+        "cors_allow_credentials" => config.cors_allow_credentials = Some(parse_bool(value)?),
+        "cors_max_age" => config.cors_max_age = Some(parse(value)?),
+        "shutdown_timeout" => config.shutdown_timeout = parse_duration(value)?,
+        "state_file" => config.state_file = value.optional()?.map(PathBuf::from),
+        "default_locale" => config.default_locale = parse::<Locale>(value)?,
+        "contact_acknowledgement" => config.contact_acknowledgement = parse_bool(value)?,
+        "contact_channels" => {
+            config.contact_channels = value.list().iter().map(|s| s.parse()).collect::<Result<_, _>>()?
+        }
+        "routing_file" => {
+            let path = value.optional()?.map(PathBuf::from);
+            config.routing = match &path {
+                Some(path) => routing::load(path)?,
+                None => RoutingRules::default(),
+            };
+            config.routing_file = path;
+        }
+        "forms_file" => {
+            let path = value.optional()?.map(PathBuf::from);
+            config.forms = match &path {
+                Some(path) => forms::load(path)?,
+                None => Forms::default(),
+            };
+            config.forms_file = path;
+        }
+        "webhook_secret" => config.webhook_secret = value.optional()?.map(Secret::from),
+        "webhook_contact_received" => config.webhook_contact_received = value.list(),
+        "webhook_form_submitted" => config.webhook_form_submitted = value.list(),
+        "webhook_max_attempts" => config.webhook_max_attempts = parse(value)?,
+        "webhook_timeout" => config.webhook_timeout = parse_duration(value)?,
+        // Las consume el propio loader para descifrar el archivo de secretos
+        "secrets_file" | "secrets_key" => {}
+        _ => return Err("clave desconocida".to_string()),
+    }
+    Ok(())
+}
+
+/// Checks that need the final, merged configuration
+fn validate(config: &Config) -> Vec<ConfigIssue> {
+    let mut issues = Vec::new();
+    let mut invalid = |key: &str, message: String| {
+        issues.push(ConfigIssue {
+            key: key.to_string(),
+            origin: None,
+            message,
+        })
+    };
+
+    if config.admin_emails.is_empty() {
+        invalid("admin_emails", "se necesita al menos un destinatario".to_string());
+    }
+    for address in config.admin_emails.iter().filter(|a| !looks_like_email(a)) {
+        invalid("admin_emails", format!("dirección inválida '{}'", address));
+    }
+    if config.rate_limit_per_minute == 0 || config.rate_limit_per_12h < config.rate_limit_per_minute {
+        invalid(
+            "rate_limit_per_12h",
+            format!(
+                "los límites deben cumplir 0 < por minuto ({}) <= por 12 horas ({})",
+                config.rate_limit_per_minute, config.rate_limit_per_12h
+            ),
+        );
+    }
+    if !looks_like_email(&config.smtp_from) {
+        invalid("smtp_from", format!("dirección inválida '{}'", config.smtp_from));
+    }
+    for entry in &config.api_keys {
+        if let Err(e) = ApiKeyEntry::parse(entry) {
+            invalid("api_keys", e.to_string());
+        }
+    }
+    let webhook_targets = [
+        ("webhook_contact_received", &config.webhook_contact_received),
+        ("webhook_form_submitted", &config.webhook_form_submitted),
+    ];
+    for (key, urls) in webhook_targets {
+        for url in urls {
+            if !matches!(reqwest::Url::parse(url), Ok(parsed) if matches!(parsed.scheme(), "http" | "https")) {
+                invalid(key, format!("URL inválida '{}'", url));
+            }
+        }
+        if !urls.is_empty() && config.webhook_secret.is_none() {
+            invalid("webhook_secret", format!("se necesita para firmar los webhooks de {}", key));
+        }
+    }
+    let dkim = [
+        ("dkim_domain", config.dkim_domain.is_some()),
+        ("dkim_selector", config.dkim_selector.is_some()),
+        ("dkim_key_file", config.dkim_key_file.is_some()),
+    ];
+    if dkim.iter().any(|(_, set)| *set) {
+        for (key, _) in dkim.iter().filter(|(_, set)| !set) {
+            invalid(key, "se necesita para firmar con DKIM junto con dkim_domain, dkim_selector y dkim_key_file".to_string());
+        }
+    }
+    if config.bounce_poll_interval.is_zero() {
+        invalid("bounce_poll_interval", "debe ser mayor que cero".to_string());
+    }
+    if config.webhook_max_attempts == 0 {
+        invalid("webhook_max_attempts", "debe ser al menos 1".to_string());
+    }
+    for method in config.cors_allowed_methods.iter().flatten() {
+        if Method::from_bytes(method.to_ascii_uppercase().as_bytes()).is_err() {
+            invalid("cors_allowed_methods", format!("método inválido '{}'", method));
+        }
+    }
+    for header in config.cors_allowed_headers.iter().flatten().filter(|h| h.as_str() != "*") {
+        if HeaderName::from_bytes(header.as_bytes()).is_err() {
+            invalid("cors_allowed_headers", format!("cabecera inválida '{}'", header));
+        }
+    }
+
+    // En producción los valores de ejemplo nunca son intencionados
+    if config.mode.strict() {
+        if config.cors_allowed_origins.iter().flatten().any(|o| o == "*") {
+            invalid("cors_allowed_origins", "'*' no está permitido en prod; enumera los orígenes".to_string());
+        }
+        if config.mail_transport.is_some_and(|t| t != MailTransport::Smtp) {
+            invalid("mail_transport", "en prod el correo debe salir por smtp".to_string());
+        }
+
+        let defaults = Config::default();
+        let placeholder = "valor de ejemplo; configúralo antes de arrancar en prod".to_string();
+        let example = |key: &str, value: &str| EXAMPLE_VALUES.contains(&(key, value));
+
+        if config.smtp_user == defaults.smtp_user || example("smtp_user", &config.smtp_user) {
+            invalid("smtp_user", placeholder.clone());
+        }
+        if config.smtp_password.is_empty()
+            || config.smtp_password == defaults.smtp_password
+            || example("smtp_password", config.smtp_password.expose())
+        {
+            invalid("smtp_password", placeholder.clone());
+        }
+        if config.smtp_from == defaults.smtp_from || example("smtp_from", &config.smtp_from) {
+            invalid("smtp_from", placeholder.clone());
+        }
+        if config.admin_emails == defaults.admin_emails || config.admin_emails.iter().any(|a| example("admin_emails", a)) {
+            invalid("admin_emails", placeholder.clone());
+        }
+        // Sin jwt_secret se genera uno aleatorio por proceso, que sí es seguro
+        if let Some(secret) = &config.jwt_secret {
+            if example("jwt_secret", secret.expose()) {
+                invalid("jwt_secret", placeholder);
+            } else if secret.expose().len() < MIN_JWT_SECRET_LEN {
+                invalid("jwt_secret", format!("debe tener al menos {} bytes en prod", MIN_JWT_SECRET_LEN));
+            }
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use crate::notifications::ChannelSpec;
+
+    fn sources(env: &[(&str, &str)]) -> ConfigSources {
+        ConfigSources {
+            env_prefix: DEFAULT_ENV_PREFIX.to_string(),
+            env: env.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn temp_file(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("{}-{}", std::process::id(), name));
+        fs::File::create(&path).unwrap().write_all(contents.as_bytes()).unwrap();
+        path
+    }
+
+    #[test]
+    fn later_layers_override_earlier_ones() {
+        let file = temp_file(
+            "layers.toml",
+            r#"
+port = 9000
+host = "127.0.0.1"
+admin_emails = ["ops@tusitio.com", "ventas@tusitio.com"]
+
+[log]
+format = "json"
+
+[jwt]
+ttl = "30m"
+"#,
+        );
+        let mut sources = sources(&[("APP_PORT", "9100"), ("RUST_LOG", "warn"), ("PORT", "1")]);
+        sources.file = Some(file.clone());
+        sources.overrides = vec![("port".to_string(), "9200".to_string())];
+
+        let config = load(&sources).unwrap();
+        fs::remove_file(file).unwrap();
+
+        assert_eq!(config.port, 9200);
+        assert_eq!(config.host, "127.0.0.1");
+        assert_eq!(config.log_format, LogFormat::Json);
+        assert_eq!(config.log_filter, "warn");
+        assert_eq!(config.jwt_ttl, Duration::from_secs(30 * 60));
+        assert_eq!(config.admin_emails, ["ops@tusitio.com", "ventas@tusitio.com"]);
+    }
+
+    #[test]
+    fn reads_yaml_files() {
+        let file = temp_file("layers.yaml", "smtp:\n  server: smtp.tusitio.com\n  check_on_startup: true\n");
+        let mut sources = sources(&[]);
+        sources.file = Some(file.clone());
+
+        let config = load(&sources).unwrap();
+        fs::remove_file(file).unwrap();
+
+        assert_eq!(config.smtp_server, "smtp.tusitio.com");
+        assert!(config.smtp_check_on_startup);
+    }
+
+    #[test]
+    fn reports_every_invalid_key() {
+        let mut sources = sources(&[
+            ("APP_PORT", "80808"),
+            ("APP_LOG_FORMAT", "xml"),
+            ("APP_JWT_TTL", "pronto"),
+        ]);
+        sources.overrides = vec![("smtp_servidor".to_string(), "x".to_string())];
+
+        let error = load(&sources).unwrap_err();
+        let keys: Vec<_> = error.issues.iter().map(|i| i.key.as_str()).collect();
+        assert_eq!(keys, ["port", "log_format", "jwt_ttl", "smtp_servidor"]);
+        assert_eq!(error.issues[0].origin.as_deref(), Some("variable APP_PORT"));
+        assert!(error.to_string().starts_with("Configuración inválida (4 errores):"));
+    }
+
+    #[test]
+    fn refuses_placeholder_credentials_in_prod() {
+        let error = load(&sources(&[("APP_MODE", "prod")])).unwrap_err();
+        let keys: Vec<_> = error.issues.iter().map(|i| i.key.as_str()).collect();
+        assert_eq!(keys, ["smtp_user", "smtp_password", "smtp_from", "admin_emails"]);
+
+        let config = load(&sources(&[
+            ("APP_MODE", "prod"),
+            ("APP_SMTP_USER", "mailer@tusitio.com"),
+            ("APP_SMTP_PASSWORD", "s3creto"),
+            ("APP_SMTP_FROM", "no-reply@tusitio.es"),
+            ("APP_ADMIN_EMAILS", "ops@tusitio.com"),
+        ]))
+        .unwrap();
+        assert_eq!(config.mode, Mode::Prod);
+    }
+
+    #[test]
+    fn refuses_the_example_values_in_prod() {
+        let error = load(&sources(&[
+            ("APP_MODE", "prod"),
+            ("APP_SMTP_USER", "pepe@gmail.com"),
+            ("APP_SMTP_PASSWORD", "asd asd asd asd"),
+            ("APP_SMTP_FROM", "no-reply@gmail.com"),
+            ("APP_ADMIN_EMAILS", "ops@tusitio.com,uwu@gmail.com"),
+            ("APP_JWT_SECRET", "cambia-este-secreto"),
+        ]))
+        .unwrap_err();
+        let keys: Vec<_> = error.issues.iter().map(|i| i.key.as_str()).collect();
+        assert_eq!(keys, ["smtp_user", "smtp_password", "smtp_from", "admin_emails", "jwt_secret"]);
+
+        let prod = [
+            ("APP_MODE", "prod"),
+            ("APP_SMTP_USER", "mailer@tusitio.com"),
+            ("APP_SMTP_PASSWORD", "s3creto"),
+            ("APP_SMTP_FROM", "no-reply@tusitio.es"),
+            ("APP_ADMIN_EMAILS", "ops@tusitio.com"),
+        ];
+        let error = load(&sources(&[prod.as_slice(), &[("APP_JWT_SECRET", "corto")]].concat())).unwrap_err();
+        assert_eq!(error.issues[0].key, "jwt_secret");
+        assert!(error.issues[0].message.contains("al menos 32 bytes"));
+
+        let secret = "k".repeat(MIN_JWT_SECRET_LEN);
+        assert!(load(&sources(&[prod.as_slice(), &[("APP_JWT_SECRET", secret.as_str())]].concat())).is_ok());
+        // Fuera de prod los valores de ejemplo sirven para desarrollar
+        assert!(load(&sources(&[("APP_JWT_SECRET", "cambia-este-secreto")])).is_ok());
+    }
+
+    #[test]
+    fn honours_the_legacy_unprefixed_variables() {
+        let legacy = sources(&[
+            ("MODE", "prod"),
+            ("SMTP_USER", "mailer@tusitio.com"),
+            ("SMTP_PASSWORD", "s3creto"),
+            ("FROM_EMAIL", "no-reply@tusitio.es"),
+            ("ADMIN_EMAILS", "ops@tusitio.com, ventas@tusitio.com"),
+            ("PORT", "9000"),
+            ("APP_PORT", "9100"),
+        ]);
+        let config = load(&legacy).unwrap();
+        assert_eq!(config.mode, Mode::Prod);
+        assert_eq!(config.smtp_from, "no-reply@tusitio.es");
+        assert_eq!(config.admin_emails, ["ops@tusitio.com", "ventas@tusitio.com"]);
+        // La variable con prefijo gana a la antigua
+        assert_eq!(config.port, 9100);
+
+        let deprecations = deprecated_variables(&legacy);
+        assert_eq!(deprecations.len(), 6);
+        assert!(deprecations.contains(&"La variable FROM_EMAIL está obsoleta; usa APP_SMTP_FROM".to_string()));
+        assert!(deprecated_variables(&sources(&[("APP_MODE", "prod")])).is_empty());
+    }
+
+    #[test]
+    fn reads_secrets_from_files_and_encrypted_store() {
+        let password = temp_file("smtp_password", "desde-archivo\n");
+        let key = secrets_file::generate_key();
+        let sealed = secrets_file::encrypt(&key, "jwt_secret = \"cifrado\"\n[smtp]\npassword = \"pisado\"\n").unwrap();
+        let store = std::env::temp_dir().join(format!("{}-secrets.enc", std::process::id()));
+        fs::write(&store, sealed).unwrap();
+
+        let config = load(&sources(&[
+            ("APP_SMTP_PASSWORD_FILE", password.to_str().unwrap()),
+            ("APP_SECRETS_FILE", store.to_str().unwrap()),
+            ("APP_SECRETS_KEY", key.as_str()),
+        ]))
+        .unwrap();
+
+        // Las variables de entorno tienen prioridad sobre el archivo de secretos
+        assert_eq!(config.smtp_password.expose(), "desde-archivo");
+        assert_eq!(config.jwt_secret.as_ref().map(Secret::expose), Some("cifrado"));
+        assert!(!format!("{:?}", config).contains("desde-archivo"));
+
+        let error = load(&sources(&[
+            ("APP_SECRETS_FILE", store.to_str().unwrap()),
+            ("APP_SECRETS_KEY", secrets_file::generate_key().as_str()),
+        ]))
+        .unwrap_err();
+        assert_eq!(error.issues[0].key, "secrets_file");
+
+        fs::remove_file(password).unwrap();
+        fs::remove_file(store).unwrap();
+    }
+
+    #[test]
+    fn loads_forms_and_their_templates() {
+        let template = temp_file("quote.html", "<p>{{form}}: {{email}}</p>");
+        let forms = temp_file(
+            "forms.toml",
+            &format!(
+                r#"
+[quote]
+title = "Presupuesto"
+template = "{}"
+channels = ["email", "teams:https://example.webhook.office.com/webhookb2/x"]
+rate_limit = {{ per_minute = 1, per_12h = 3 }}
+
+[[quote.fields]]
+name = "email"
+type = "email"
+required = true
+"#,
+                template.file_name().unwrap().to_str().unwrap()
+            ),
+        );
+
+        let config = load(&sources(&[("APP_FORMS_FILE", forms.to_str().unwrap())])).unwrap();
+        let quote = config.forms.get("quote").unwrap();
+        assert_eq!(quote.template.as_deref(), Some("<p>{{form}}: {{email}}</p>"));
+        assert_eq!(quote.rate_limit.map(|l| l.per_minute), Some(1));
+        assert_eq!(quote.channels.len(), 2);
+        assert_eq!(config.contact_channels, [ChannelSpec::Email]);
+        assert!(config.forms.get("contact").is_none());
+
+        fs::write(&forms, "[quote]\nrecipients = [\"nadie\"]\n[[quote.fields]]\nname = \"x\"\n").unwrap();
+        let error = load(&sources(&[("APP_FORMS_FILE", forms.to_str().unwrap())])).unwrap_err();
+        assert_eq!(error.issues[0].key, "forms_file");
+        assert!(error.issues[0].message.contains("destinatario inválido 'nadie'"));
+
+        fs::remove_file(forms).unwrap();
+        fs::remove_file(template).unwrap();
+    }
+
+    #[test]
+    fn loads_routing_rules() {
+        let routing = temp_file(
+            "routing.toml",
+            "[[rules]]\nservice = [\"design\"]\nrecipients = [\"diseno@tusitio.com\"]\n",
+        );
+        let config = load(&sources(&[("APP_ROUTING_FILE", routing.to_str().unwrap())])).unwrap();
+        assert_eq!(config.routing.rules[0].recipients, ["diseno@tusitio.com"]);
+
+        fs::write(&routing, "[[rules]]\nrecipients = [\"ops@tusitio.com\"]\n").unwrap();
+        let error = load(&sources(&[("APP_ROUTING_FILE", routing.to_str().unwrap())])).unwrap_err();
+        assert_eq!(error.issues[0].key, "routing_file");
+        assert!(error.issues[0].message.contains("regla 1"));
+
+        fs::remove_file(routing).unwrap();
+    }
+
+    #[test]
+    fn dkim_needs_domain_selector_and_key() {
+        let config = load(&sources(&[])).unwrap();
+        assert_eq!(config.dkim_key_file, None);
+
+        let error = load(&sources(&[("APP_DKIM_DOMAIN", "tusitio.com"), ("APP_DKIM_HEADERS", "From,Subject")])).unwrap_err();
+        let keys: Vec<&str> = error.issues.iter().map(|i| i.key.as_str()).collect();
+        assert_eq!(keys, ["dkim_selector", "dkim_key_file"]);
+    }
+}
@@ -0,0 +1,429 @@
+use crate::common::ApplicationConfig;
+use crate::forms::{FormDefinition, Forms};
+use crate::i18n::Locale;
+use crate::models::email::ContactRequest;
+use crate::notifications::ChannelSpec;
+use crate::routing::RoutingRules;
+use anyhow::anyhow;
+use arc_swap::ArcSwap;
+use derive_builder::Builder;
+use once_cell::sync::{Lazy, OnceCell};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+mod loader;
+mod mode;
+mod reload;
+mod secret;
+pub mod secrets_file;
+
+pub use loader::{ConfigSources, DEFAULT_ENV_PREFIX};
+pub use mode::{MailTransport, Mode};
+pub use secret::Secret;
+pub use reload::register_watchers;
+
+/// Configuration the process was started with, backing the `&'static` getters
+static CONFIG: OnceCell<Config> = OnceCell::new();
+
+/// Latest valid configuration; only the keys in [`reload::RELOADABLE_KEYS`] ever differ from [`CONFIG`]
+static LIVE: Lazy<ArcSwap<Config>> = Lazy::new(|| ArcSwap::from_pointee(current().clone()));
+
+/// Sources used at startup, read again on every reload
+static SOURCES: OnceCell<ConfigSources> = OnceCell::new();
+
+/// Legacy settings found at startup, logged once the tracing pipeline is up
+static DEPRECATIONS: OnceCell<Vec<String>> = OnceCell::new();
+
+/// Startup configuration. Falls back to the defaults when [`Config::init`] was never called,
+/// plus the test forms of [`crate::testing::config`] in unit tests.
+fn current() -> &'static Config {
+    #[cfg(test)]
+    let defaults = crate::testing::config;
+    #[cfg(not(test))]
+    let defaults = Config::default;
+    CONFIG.get_or_init(defaults)
+}
+
+fn live() -> Arc<Config> {
+    LIVE.load_full()
+}
+
+/// Output format of the tracing subscriber
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human readable, multi-line output for local development
+    Pretty,
+    /// One JSON object per event, including the fields of the enclosing spans
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "pretty" => Ok(LogFormat::Pretty),
+            "json" => Ok(LogFormat::Json),
+            other => Err(format!("Formato de log desconocido '{}'", other)),
+        }
+    }
+}
+
+/// Main application configuration structure with default values.
+/// Built by layering a config file, `APP_*` environment variables and CLI flags on top of these defaults.
+#[derive(Builder, Debug, Clone, PartialEq)]
+pub struct Config {
+    /// `RUST_LOG`-style filter directives, e.g. `info,app=debug`
+    #[builder(default = "String::from(\"debug\")")]
+    pub log_filter: String,
+    #[builder(default = "LogFormat::Pretty")]
+    pub log_format: LogFormat,
+    /// OTLP/HTTP collector base URL; trace export is disabled when unset
+    #[builder(default = "None")]
+    pub otel_endpoint: Option<String>,
+    #[builder(default = "String::from(\"base-server\")")]
+    pub otel_service_name: String,
+    #[builder(default = "Mode::Dev")]
+    pub mode: Mode,
+    #[builder(default = "8080")]
+    pub port: u16,
+    #[builder(default = "String::from(\"0.0.0.0\")")]
+    pub host: String,
+    #[builder(default = "String::from(\"smtp.gmail.com\")")]
+    pub smtp_server: String,
+    #[builder(default = "String::from(\"usuario@gmail.com\")")]
+    pub smtp_user: String,
+    #[builder(default = "Secret::from(\"password\")")]
+    pub smtp_password: Secret,
+    #[builder(default = "String::from(\"no-reply@tusitio.com\")")]
+    pub smtp_from: String,
+    /// Delivery of outgoing mail; defaults to the mode's transport (file in dev, capture in test)
+    #[builder(default = "None")]
+    pub mail_transport: Option<MailTransport>,
+    /// Directory for the `file` mail transport
+    #[builder(default = "PathBuf::from(\"mail\")")]
+    pub mail_dir: PathBuf,
+    /// Probe the SMTP relay during setup and refuse to start if it is unusable
+    #[builder(default = "false")]
+    pub smtp_check_on_startup: bool,
+    /// Signing domain (`d=`) of the DKIM signature; DKIM is off unless the domain, selector and key are set
+    #[builder(default = "None")]
+    pub dkim_domain: Option<String>,
+    /// Selector (`s=`) under which the public key is published in DNS
+    #[builder(default = "None")]
+    pub dkim_selector: Option<String>,
+    /// PEM private key, RSA or Ed25519
+    #[builder(default = "None")]
+    pub dkim_key_file: Option<PathBuf>,
+    /// Headers to sign; the email-sender defaults if empty
+    #[builder(default = "Vec::new()")]
+    pub dkim_headers: Vec<String>,
+    /// Where the addresses that hard-bounced or complained are kept; in memory only when unset
+    #[builder(default = "None")]
+    pub suppression_file: Option<PathBuf>,
+    /// Directory the MTA drops bounce and complaint reports into, one message per file
+    #[builder(default = "None")]
+    pub bounce_mailbox_dir: Option<PathBuf>,
+    #[builder(default = "Duration::from_secs(60)")]
+    pub bounce_poll_interval: Duration,
+    #[builder(default = "vec![\"admin@example.com\".to_string()]")]
+    pub admin_emails: Vec<String>,
+    /// Contact requests allowed per client IP and minute
+    #[builder(default = "2")]
+    pub rate_limit_per_minute: usize,
+    /// Contact requests allowed per client IP every 12 hours
+    #[builder(default = "4")]
+    pub rate_limit_per_12h: usize,
+    /// API key entries in the form `name:role:sha256hex`
+    #[builder(default = "Vec::new()")]
+    pub api_keys: Vec<String>,
+    #[builder(default = "None")]
+    pub jwt_secret: Option<Secret>,
+    /// Lifetime of issued access tokens
+    #[builder(default = "Duration::from_secs(900)")]
+    pub jwt_ttl: Duration,
+    #[builder(default = "None")]
+    pub cors_allowed_origins: Option<Vec<String>>,
+    #[builder(default = "None")]
+    pub cors_allowed_methods: Option<Vec<String>>,
+    #[builder(default = "None")]
+    pub cors_allowed_headers: Option<Vec<String>>,
+    #[builder(default = "None")]
+    pub cors_allow_credentials: Option<bool>,
+    #[builder(default = "None")]
+    pub cors_max_age: Option<usize>,
+    /// Total time given to in-flight requests and then queued mail to finish once shutdown starts
+    #[builder(default = "Duration::from_secs(30)")]
+    pub shutdown_timeout: Duration,
+    /// Where the contact store is saved on shutdown and loaded from on startup; in memory only when unset
+    #[builder(default = "None")]
+    pub state_file: Option<PathBuf>,
+    /// Language used when the client does not ask for a supported one, and for the admin notifications
+    #[builder(default = "Locale::Es")]
+    pub default_locale: Locale,
+    /// Send the customer a localised acknowledgement of their contact request.
+    /// Off by default: the address comes from an unauthenticated request, so anyone can pick the recipient.
+    #[builder(default = "false")]
+    pub contact_acknowledgement: bool,
+    /// Where requests to `/api/v1/contact` are announced: `email` (to `admin_emails`) and chat incoming webhooks
+    #[builder(default = "vec![ChannelSpec::Email]")]
+    pub contact_channels: Vec<ChannelSpec>,
+    /// TOML or YAML file with the rules choosing the recipients of each contact request
+    #[builder(default = "None")]
+    pub routing_file: Option<PathBuf>,
+    /// Rules read from `routing_file`; everything goes to `admin_emails` if empty
+    #[builder(default = "RoutingRules::default()")]
+    pub routing: RoutingRules,
+    /// TOML or YAML file with the forms served at `/api/v1/forms/{form_id}`
+    #[builder(default = "None")]
+    pub forms_file: Option<PathBuf>,
+    /// Definitions read from `forms_file`
+    #[builder(default = "Forms::default()")]
+    pub forms: Forms,
+    /// Key of the `X-Webhook-Signature` HMAC; required when any webhook target is set
+    #[builder(default = "None")]
+    pub webhook_secret: Option<Secret>,
+    /// URLs notified of every request to `/api/v1/contact`
+    #[builder(default = "Vec::new()")]
+    pub webhook_contact_received: Vec<String>,
+    /// URLs notified of every configured form submission
+    #[builder(default = "Vec::new()")]
+    pub webhook_form_submitted: Vec<String>,
+    /// Attempts per delivery, with exponential backoff between them, before it is marked failed
+    #[builder(default = "5")]
+    pub webhook_max_attempts: u32,
+    #[builder(default = "Duration::from_secs(10)")]
+    pub webhook_timeout: Duration,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        ConfigBuilder::default().build().expect("todos los campos tienen valor por defecto")
+    }
+}
+
+/// Implementation of common application configuration interface
+impl ApplicationConfig for Config {
+    fn get_addrs() -> String {
+        format!("{}:{}", current().host, current().port)
+    }
+
+    fn get_log_filter() -> &'static str {
+        &current().log_filter
+    }
+
+    fn get_mode() -> Mode {
+        current().mode
+    }
+
+    fn get_port() -> u16 {
+        current().port
+    }
+
+    fn get_host() -> &'static str {
+        &current().host
+    }
+
+    fn get_max_pool_size() -> u32 {
+        8 // Valor por defecto
+    }
+
+    fn get_with_migrations() -> bool {
+        true // Valor por defecto
+    }
+    
+}
+
+/// Additional configuration methods specific to this application
+impl Config {
+    /// Loads and validates the layered configuration and makes it the process-wide one.
+    /// Must run before anything reads the configuration; every invalid key is reported at once.
+    pub fn init(sources: &ConfigSources) -> anyhow::Result<()> {
+        let config = loader::load(sources)?;
+        CONFIG
+            .set(config.clone())
+            .map_err(|_| anyhow!("La configuración ya fue inicializada"))?;
+        LIVE.store(Arc::new(config));
+        let _ = SOURCES.set(sources.clone());
+        let _ = DEPRECATIONS.set(loader::deprecated_variables(sources));
+        Ok(())
+    }
+
+    /// Warnings about legacy variables the configuration was loaded with
+    pub fn get_deprecations() -> &'static [String] {
+        DEPRECATIONS.get().map(Vec::as_slice).unwrap_or_default()
+    }
+
+    pub fn get_log_format() -> LogFormat {
+        current().log_format
+    }
+
+    pub fn get_otel_endpoint() -> Option<&'static str> {
+        current().otel_endpoint.as_deref()
+    }
+
+    pub fn get_otel_service_name() -> &'static str {
+        &current().otel_service_name
+    }
+
+    pub fn get_smtp_server() -> &'static str {
+        &current().smtp_server
+    }
+
+    pub fn get_smtp_user() -> &'static str {
+        &current().smtp_user
+    }
+
+    pub fn get_smtp_password() -> &'static Secret {
+        &current().smtp_password
+    }
+
+    pub fn get_smtp_from() -> &'static str {
+        &current().smtp_from
+    }
+
+    pub fn get_mail_transport() -> MailTransport {
+        let config = current();
+        config.mail_transport.unwrap_or_else(|| config.mode.default_mail_transport())
+    }
+
+    pub fn get_mail_dir() -> &'static Path {
+        &current().mail_dir
+    }
+
+    pub fn get_smtp_check_on_startup() -> bool {
+        current().smtp_check_on_startup
+    }
+
+    /// `(dominio, selector, clave)` si la firma DKIM está configurada
+    pub fn get_dkim() -> Option<(&'static str, &'static str, &'static Path)> {
+        let config = current();
+        match (&config.dkim_domain, &config.dkim_selector, &config.dkim_key_file) {
+            (Some(domain), Some(selector), Some(key_file)) => Some((domain, selector, key_file)),
+            _ => None,
+        }
+    }
+
+    pub fn get_dkim_headers() -> &'static [String] {
+        &current().dkim_headers
+    }
+
+    pub fn get_suppression_file() -> Option<&'static Path> {
+        current().suppression_file.as_deref()
+    }
+
+    pub fn get_bounce_mailbox_dir() -> Option<&'static Path> {
+        current().bounce_mailbox_dir.as_deref()
+    }
+
+    pub fn get_bounce_poll_interval() -> Duration {
+        current().bounce_poll_interval
+    }
+
+    /// Obtiene la lista de emails de admin como un vector. Recargable.
+    pub fn get_admin_emails_list() -> Vec<String> {
+        live().admin_emails.clone()
+    }
+
+    /// Límites por IP `(por minuto, por 12 horas)`. Recargable.
+    pub fn get_rate_limits() -> (usize, usize) {
+        let config = live();
+        (config.rate_limit_per_minute, config.rate_limit_per_12h)
+    }
+
+    pub fn get_api_keys() -> &'static [String] {
+        &current().api_keys
+    }
+
+    pub fn get_jwt_secret() -> Option<&'static Secret> {
+        current().jwt_secret.as_ref()
+    }
+
+    pub fn get_jwt_ttl() -> Duration {
+        current().jwt_ttl
+    }
+
+    /// Recargable, por eso devuelve una copia
+    pub fn get_cors_allowed_origins() -> Option<Vec<String>> {
+        live().cors_allowed_origins.clone()
+    }
+
+    pub fn get_cors_allowed_methods() -> Option<&'static [String]> {
+        current().cors_allowed_methods.as_deref()
+    }
+
+    pub fn get_cors_allowed_headers() -> Option<&'static [String]> {
+        current().cors_allowed_headers.as_deref()
+    }
+
+    pub fn get_cors_allow_credentials() -> Option<bool> {
+        current().cors_allow_credentials
+    }
+
+    pub fn get_cors_max_age() -> Option<usize> {
+        current().cors_max_age
+    }
+
+    pub fn get_shutdown_timeout() -> Duration {
+        current().shutdown_timeout
+    }
+
+    pub fn get_state_file() -> Option<&'static Path> {
+        current().state_file.as_deref()
+    }
+
+    pub fn get_default_locale() -> Locale {
+        current().default_locale
+    }
+
+    pub fn get_contact_acknowledgement() -> bool {
+        current().contact_acknowledgement
+    }
+
+    /// Destinatarios de una solicitud de contacto según las reglas de `routing_file`. Recargable.
+    pub fn get_contact_recipients(request: &ContactRequest) -> Vec<String> {
+        let config = live();
+        match config.routing.recipients(request) {
+            Some(recipients) => recipients.to_vec(),
+            None => config.admin_emails.clone(),
+        }
+    }
+
+    pub fn get_contact_channels() -> &'static [ChannelSpec] {
+        &current().contact_channels
+    }
+
+    /// Direcciones que reciben los avisos: admins, reglas de enrutado y formularios. Recargable salvo los formularios.
+    pub fn get_configured_recipients() -> Vec<String> {
+        let config = live();
+        let routing = config.routing.default.iter().chain(config.routing.rules.iter().flat_map(|r| &r.recipients));
+        let forms = current().forms.values().flat_map(|form| &form.recipients);
+        config.admin_emails.iter().chain(routing).chain(forms).cloned().collect()
+    }
+
+    pub fn get_form(id: &str) -> Option<&'static FormDefinition> {
+        current().forms.get(id)
+    }
+
+    pub fn get_webhook_secret() -> Option<&'static Secret> {
+        current().webhook_secret.as_ref()
+    }
+
+    pub fn get_webhook_contact_received() -> &'static [String] {
+        &current().webhook_contact_received
+    }
+
+    pub fn get_webhook_form_submitted() -> &'static [String] {
+        &current().webhook_form_submitted
+    }
+
+    pub fn get_webhook_max_attempts() -> u32 {
+        current().webhook_max_attempts
+    }
+
+    pub fn get_webhook_timeout() -> Duration {
+        current().webhook_timeout
+    }
+}
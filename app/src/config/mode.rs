@@ -0,0 +1,105 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// Deployment environment; decides the defaults that differ between development and production
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Dev,
+    Test,
+    Staging,
+    Prod,
+}
+
+impl Mode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Mode::Dev => "dev",
+            Mode::Test => "test",
+            Mode::Staging => "staging",
+            Mode::Prod => "prod",
+        }
+    }
+
+    /// Error responses include the underlying error, not just a generic message
+    pub fn verbose_errors(self) -> bool {
+        self == Mode::Dev
+    }
+
+    /// CORS defaults allow any origin instead of none
+    pub fn permissive_cors(self) -> bool {
+        self == Mode::Dev
+    }
+
+    /// Responses carry HSTS, CSP and the other hardening headers, and CORS may not use `*`
+    pub fn strict(self) -> bool {
+        self == Mode::Prod
+    }
+
+    /// The Swagger UI is served at `/api/v1/docs`; the JSON document is always available
+    pub fn api_docs(self) -> bool {
+        self == Mode::Dev
+    }
+
+    /// Where mail goes unless `mail_transport` says otherwise
+    pub fn default_mail_transport(self) -> MailTransport {
+        match self {
+            Mode::Dev => MailTransport::File,
+            Mode::Test => MailTransport::Capture,
+            Mode::Staging | Mode::Prod => MailTransport::Smtp,
+        }
+    }
+}
+
+impl FromStr for Mode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "dev" | "development" => Ok(Mode::Dev),
+            "test" => Ok(Mode::Test),
+            "staging" => Ok(Mode::Staging),
+            "prod" | "production" => Ok(Mode::Prod),
+            other => Err(format!("Modo desconocido '{}' (dev, test, staging o prod)", other)),
+        }
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// How outgoing mail is delivered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MailTransport {
+    /// Real delivery through the configured relay
+    Smtp,
+    /// One `.eml` file per message in `mail_dir`
+    File,
+    /// Kept in memory, for tests
+    Capture,
+}
+
+impl FromStr for MailTransport {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "smtp" => Ok(MailTransport::Smtp),
+            "file" => Ok(MailTransport::File),
+            "capture" => Ok(MailTransport::Capture),
+            other => Err(format!("Transporte de correo desconocido '{}' (smtp, file o capture)", other)),
+        }
+    }
+}
+
+impl fmt::Display for MailTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            MailTransport::Smtp => "smtp",
+            MailTransport::File => "file",
+            MailTransport::Capture => "capture",
+        })
+    }
+}
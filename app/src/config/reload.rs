@@ -0,0 +1,258 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{anyhow, Result};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+
+use super::{loader, Config, LIVE, SOURCES};
+use crate::logging;
+use crate::services::{BackgroundService, ServiceFuture, Services};
+
+/// Keys that take effect without a restart; changes to any other key are only logged
+pub const RELOADABLE_KEYS: &[&str] = &[
+    "admin_emails",
+    "routing_file",
+    "routing",
+    "rate_limit_per_minute",
+    "rate_limit_per_12h",
+    "cors_allowed_origins",
+    "log_filter",
+];
+
+/// How often the config file is checked for changes
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Outcome of a reload: keys applied live and keys that changed but need a restart
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ReloadReport {
+    pub applied: Vec<&'static str>,
+    pub ignored: Vec<&'static str>,
+}
+
+macro_rules! changed_keys {
+    ($old:expr, $new:expr, $($field:ident),+ $(,)?) => {{
+        let mut keys = Vec::new();
+        $(if $old.$field != $new.$field {
+            keys.push(stringify!($field));
+        })+
+        keys
+    }};
+}
+
+fn diff(old: &Config, new: &Config) -> Vec<&'static str> {
+    changed_keys!(
+        old, new,
+        log_filter, log_format, otel_endpoint, otel_service_name, mode, port, host,
+        smtp_server, smtp_user, smtp_password, smtp_from, mail_transport, mail_dir, smtp_check_on_startup,
+        dkim_domain, dkim_selector, dkim_key_file, dkim_headers,
+        suppression_file, bounce_mailbox_dir, bounce_poll_interval,
+        admin_emails, rate_limit_per_minute, rate_limit_per_12h, api_keys, jwt_secret, jwt_ttl,
+        cors_allowed_origins, cors_allowed_methods, cors_allowed_headers,
+        cors_allow_credentials, cors_max_age, shutdown_timeout, state_file,
+        default_locale, contact_acknowledgement, contact_channels, routing_file, routing, forms_file, forms,
+        webhook_secret, webhook_contact_received, webhook_form_submitted, webhook_max_attempts, webhook_timeout,
+    )
+}
+
+/// Copies the reloadable settings of `loaded` onto `current`, leaving everything else untouched
+fn merge(current: &Config, loaded: &Config) -> (Config, ReloadReport) {
+    let mut next = current.clone();
+    next.admin_emails = loaded.admin_emails.clone();
+    next.routing_file = loaded.routing_file.clone();
+    next.routing = loaded.routing.clone();
+    next.rate_limit_per_minute = loaded.rate_limit_per_minute;
+    next.rate_limit_per_12h = loaded.rate_limit_per_12h;
+    next.cors_allowed_origins = loaded.cors_allowed_origins.clone();
+    next.log_filter = loaded.log_filter.clone();
+
+    let (applied, ignored) = diff(current, loaded)
+        .into_iter()
+        .partition(|key| RELOADABLE_KEYS.contains(key));
+    (next, ReloadReport { applied, ignored })
+}
+
+/// Reads the configuration sources again and swaps in the reloadable settings.
+/// An invalid configuration is rejected as a whole and the running one is kept.
+pub fn reload() -> Result<ReloadReport> {
+    let sources = SOURCES
+        .get()
+        .ok_or_else(|| anyhow!("La configuración no fue inicializada"))?;
+    let loaded = loader::load(sources)?;
+
+    let current = LIVE.load_full();
+    let (next, report) = merge(&current, &loaded);
+
+    if report.applied.contains(&"log_filter") {
+        logging::set_filter(&next.log_filter)?;
+    }
+    LIVE.store(Arc::new(next));
+
+    for key in &report.applied {
+        info!("Configuración recargada: '{}' actualizado", key);
+    }
+    for key in &report.ignored {
+        warn!("'{}' cambió pero no es recargable; se aplicará al reiniciar", key);
+    }
+    Ok(report)
+}
+
+fn reload_and_log(trigger: &str) {
+    info!("Recargando configuración ({})", trigger);
+    if let Err(e) = reload() {
+        error!("Recarga de configuración rechazada, se mantiene la actual: {}", e);
+    }
+}
+
+async fn modified_at(path: &Path) -> Option<SystemTime> {
+    tokio::fs::metadata(path).await.ok()?.modified().ok()
+}
+
+/// Reloads the configuration on `SIGHUP`
+pub struct ReloadOnHangup;
+
+impl BackgroundService for ReloadOnHangup {
+    fn name(&self) -> &'static str {
+        "config_reload_sighup"
+    }
+
+    fn run(&self, shutdown: CancellationToken) -> ServiceFuture<'_> {
+        Box::pin(async move {
+            #[cfg(unix)]
+            {
+                use tokio::signal::unix::{signal, SignalKind};
+
+                let mut hangup = signal(SignalKind::hangup())?;
+                while let Some(Some(())) = shutdown.run_until_cancelled(hangup.recv()).await {
+                    reload_and_log("SIGHUP");
+                }
+            }
+            #[cfg(not(unix))]
+            shutdown.cancelled().await;
+            Ok(())
+        })
+    }
+}
+
+/// Reloads the configuration when the config file changes.
+/// The file is polled instead of using inotify so editors that replace the file and
+/// symlink swaps (e.g. Kubernetes ConfigMaps) are also noticed.
+pub struct WatchConfigFile {
+    path: PathBuf,
+    interval: Duration,
+}
+
+impl BackgroundService for WatchConfigFile {
+    fn name(&self) -> &'static str {
+        "config_file_watcher"
+    }
+
+    fn run(&self, shutdown: CancellationToken) -> ServiceFuture<'_> {
+        Box::pin(async move {
+            let mut last_modified = modified_at(&self.path).await;
+            let mut ticker = tokio::time::interval(self.interval);
+            while shutdown.run_until_cancelled(ticker.tick()).await.is_some() {
+                let modified = modified_at(&self.path).await;
+                if modified.is_some() && modified != last_modified {
+                    last_modified = modified;
+                    reload_and_log(&format!("cambios en {}", self.path.display()));
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Registers the reload triggers: `SIGHUP` and, when a config file is in use, a watcher on it
+pub fn register_watchers(services: &mut Services) {
+    services.register(ReloadOnHangup);
+    if let Some(path) = SOURCES.get().and_then(|s| s.file.clone()) {
+        services.register(WatchConfigFile { path, interval: WATCH_INTERVAL });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConfigSources;
+    use crate::testing;
+    use std::collections::HashMap;
+
+    #[test]
+    fn merge_applies_only_reloadable_keys() {
+        let current = Config::default();
+        let loaded = Config {
+            admin_emails: vec!["ventas@tusitio.com".to_string()],
+            rate_limit_per_minute: 5,
+            cors_allowed_origins: Some(vec!["https://www.tusitio.com".to_string()]),
+            port: 9090,
+            smtp_server: "smtp.tusitio.com".to_string(),
+            ..Default::default()
+        };
+
+        let (next, report) = merge(&current, &loaded);
+
+        assert_eq!(next.admin_emails, loaded.admin_emails);
+        assert_eq!(next.rate_limit_per_minute, 5);
+        assert_eq!(next.cors_allowed_origins, loaded.cors_allowed_origins);
+        assert_eq!(next.port, current.port);
+        assert_eq!(next.smtp_server, current.smtp_server);
+        assert_eq!(
+            report,
+            ReloadReport {
+                applied: vec!["admin_emails", "rate_limit_per_minute", "cors_allowed_origins"],
+                ignored: vec!["port", "smtp_server"],
+            }
+        );
+    }
+
+    #[actix_web::test]
+    async fn reloads_the_live_config_when_the_file_changes() {
+        let _config = testing::LIVE_CONFIG.lock().await;
+        let previous = LIVE.load_full();
+        let path = std::env::temp_dir().join(format!("reload-{}.toml", std::process::id()));
+        std::fs::write(&path, "admin_emails = [\"ventas@tusitio.com\"]\nport = 9090\n").unwrap();
+        SOURCES
+            .set(ConfigSources {
+                file: Some(path.clone()),
+                env_prefix: "RELOAD_TEST_".to_string(),
+                env: HashMap::new(),
+                overrides: Vec::new(),
+            })
+            .unwrap();
+
+        let report = reload().unwrap();
+        assert_eq!(report.applied, ["admin_emails"]);
+        assert!(report.ignored.contains(&"port"));
+        assert_eq!(Config::get_admin_emails_list(), ["ventas@tusitio.com"]);
+
+        // Un archivo inválido se rechaza entero, también sus claves válidas
+        std::fs::write(&path, "admin_emails = [\"ops@tusitio.com\"]\nrate_limit_per_minute = \"muchos\"\n").unwrap();
+        assert!(reload().is_err());
+        assert_eq!(Config::get_admin_emails_list(), ["ventas@tusitio.com"]);
+
+
+        // El vigilante recarga en cuanto cambia la fecha de modificación
+        let shutdown = CancellationToken::new();
+        let watcher = WatchConfigFile { path: path.clone(), interval: Duration::from_millis(20) };
+        let watching = tokio::spawn({
+            let shutdown = shutdown.clone();
+            async move { watcher.run(shutdown).await.unwrap() }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        std::fs::write(&path, "admin_emails = [\"soporte@tusitio.com\"]\n").unwrap();
+        for _ in 0..100 {
+            if Config::get_admin_emails_list() != ["ventas@tusitio.com"] {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(Config::get_admin_emails_list(), ["soporte@tusitio.com"]);
+        shutdown.cancel();
+        watching.await.unwrap();
+
+        LIVE.store(previous);
+        std::fs::remove_file(path).unwrap();
+    }
+}
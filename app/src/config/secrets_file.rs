@@ -0,0 +1,80 @@
+use rand::RngCore;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use zeroize::Zeroizing;
+
+/// Header of encrypted secrets files, also authenticated as associated data
+const MAGIC: &[u8] = b"BSS1";
+
+const KEY_LEN: usize = 32;
+
+/// New random key, hex encoded, for `secrets_key`
+pub fn generate_key() -> String {
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+    rand::rng().fill_bytes(key.as_mut());
+    hex::encode(key.as_ref())
+}
+
+fn cipher(key_hex: &str) -> Result<LessSafeKey, String> {
+    let key = Zeroizing::new(
+        hex::decode(key_hex.trim()).map_err(|_| "la clave debe estar en hexadecimal".to_string())?,
+    );
+    if key.len() != KEY_LEN {
+        return Err(format!("la clave debe tener {} bytes ({} caracteres hex)", KEY_LEN, KEY_LEN * 2));
+    }
+    let key = UnboundKey::new(&CHACHA20_POLY1305, &key).map_err(|_| "clave inválida".to_string())?;
+    Ok(LessSafeKey::new(key))
+}
+
+/// Encrypts a TOML document of secrets with ChaCha20-Poly1305: `MAGIC || nonce || ciphertext+tag`
+pub fn encrypt(key_hex: &str, plaintext: &str) -> Result<Vec<u8>, String> {
+    let cipher = cipher(key_hex)?;
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::rng().fill_bytes(&mut nonce);
+
+    let mut sealed = plaintext.as_bytes().to_vec();
+    cipher
+        .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::from(MAGIC), &mut sealed)
+        .map_err(|_| "no se pudo cifrar".to_string())?;
+
+    Ok([MAGIC, &nonce, &sealed].concat())
+}
+
+/// Decrypts a file produced by [`encrypt`]; fails if the key is wrong or the data was altered
+pub fn decrypt(key_hex: &str, data: &[u8]) -> Result<Zeroizing<String>, String> {
+    let cipher = cipher(key_hex)?;
+    let rest = data
+        .strip_prefix(MAGIC)
+        .ok_or_else(|| "no es un archivo de secretos cifrado".to_string())?;
+    if rest.len() < NONCE_LEN {
+        return Err("archivo de secretos truncado".to_string());
+    }
+    let (nonce, sealed) = rest.split_at(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| "nonce inválido".to_string())?;
+
+    let mut buffer = Zeroizing::new(sealed.to_vec());
+    let plaintext = cipher
+        .open_in_place(nonce, Aad::from(MAGIC), buffer.as_mut())
+        .map_err(|_| "clave incorrecta o archivo alterado".to_string())?;
+
+    std::str::from_utf8(plaintext)
+        .map(|s| Zeroizing::new(s.to_string()))
+        .map_err(|_| "el contenido descifrado no es UTF-8".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_and_detects_tampering() {
+        let key = generate_key();
+        let mut sealed = encrypt(&key, "[smtp]\npassword = \"s3creto\"\n").unwrap();
+
+        assert_eq!(decrypt(&key, &sealed).unwrap().as_str(), "[smtp]\npassword = \"s3creto\"\n");
+        assert!(decrypt(&generate_key(), &sealed).is_err());
+
+        let last = sealed.len() - 1;
+        sealed[last] ^= 1;
+        assert!(decrypt(&key, &sealed).is_err());
+    }
+}
@@ -0,0 +1,287 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::models::contact::{ContactFilter, ContactNote, ContactStatus, ContactSubmission};
+use crate::models::email::ContactRequest;
+
+pub const DEFAULT_PER_PAGE: usize = 20;
+pub const MAX_PER_PAGE: usize = 100;
+/// Form id of the submissions received at `/api/v1/contact`
+pub const CONTACT_FORM: &str = "contact";
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct StoreInner {
+    next_id: u64,
+    submissions: BTreeMap<u64, ContactSubmission>,
+}
+
+/// Almacén en memoria de las solicitudes de contacto recibidas
+#[derive(Clone, Default)]
+pub struct ContactStore {
+    inner: Arc<Mutex<StoreInner>>,
+}
+
+impl ContactStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Recupera las solicitudes guardadas con [`ContactStore::save`]; vacío si el archivo no existe
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let inner = match std::fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => StoreInner::default(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self { inner: Arc::new(Mutex::new(inner)) })
+    }
+
+    /// Escribe todas las solicitudes en `path` como JSON.
+    /// Se escribe a un archivo temporal y se renombra para no dejar un archivo a medias.
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let json = serde_json::to_vec_pretty(&*self.inner.lock().unwrap())?;
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// Guarda una nueva solicitud y devuelve la copia almacenada
+    pub fn insert(&self, ip: &str, request: &ContactRequest) -> ContactSubmission {
+        let mut inner = self.inner.lock().unwrap();
+        inner.next_id += 1;
+
+        let now = Utc::now();
+        let submission = ContactSubmission {
+            id: inner.next_id,
+            created_at: now,
+            updated_at: now,
+            ip: ip.to_string(),
+            name: request.name.clone(),
+            company: request.company.clone(),
+            email: request.email.clone(),
+            service: request.service.clone(),
+            message: request.message.clone(),
+            status: ContactStatus::New,
+            notes: Vec::new(),
+            form: None,
+            fields: BTreeMap::new(),
+        };
+
+        inner.submissions.insert(submission.id, submission.clone());
+        submission
+    }
+
+    /// Guarda el envío de un formulario configurado. Los campos con el mismo nombre que los del
+    /// formulario de contacto (`name`, `email`...) se copian para poder filtrar y buscar igual.
+    pub fn insert_form(&self, ip: &str, form_id: &str, fields: BTreeMap<String, Value>) -> ContactSubmission {
+        let text = |name: &str| match fields.get(name) {
+            Some(Value::String(s)) => s.clone(),
+            Some(other) => other.to_string(),
+            None => String::new(),
+        };
+
+        let mut inner = self.inner.lock().unwrap();
+        inner.next_id += 1;
+
+        let now = Utc::now();
+        let submission = ContactSubmission {
+            id: inner.next_id,
+            created_at: now,
+            updated_at: now,
+            ip: ip.to_string(),
+            name: text("name"),
+            company: text("company"),
+            email: text("email"),
+            service: text("service"),
+            message: text("message"),
+            status: ContactStatus::New,
+            notes: Vec::new(),
+            form: Some(form_id.to_string()),
+            fields,
+        };
+
+        inner.submissions.insert(submission.id, submission.clone());
+        submission
+    }
+
+    /// Lista las solicitudes que cumplen el filtro, de la más reciente a la más antigua.
+    /// Devuelve la página pedida y el total de coincidencias.
+    pub fn list(&self, filter: &ContactFilter) -> (Vec<ContactSubmission>, usize) {
+        let inner = self.inner.lock().unwrap();
+        let query = filter.q.as_ref().map(|q| q.to_lowercase());
+
+        let matching: Vec<&ContactSubmission> = inner
+            .submissions
+            .values()
+            .rev()
+            .filter(|s| filter.from.is_none_or(|from| s.created_at >= from))
+            .filter(|s| filter.to.is_none_or(|to| s.created_at <= to))
+            .filter(|s| {
+                filter
+                    .service
+                    .as_ref()
+                    .is_none_or(|service| s.service.eq_ignore_ascii_case(service))
+            })
+            .filter(|s| filter.status.is_none_or(|status| s.status == status))
+            .filter(|s| {
+                filter
+                    .form
+                    .as_ref()
+                    .is_none_or(|form| s.form.as_deref().unwrap_or(CONTACT_FORM) == form)
+            })
+            .filter(|s| query.as_ref().is_none_or(|q| matches_text(s, q)))
+            .collect();
+
+        let (page, per_page) = page_bounds(filter);
+        let total = matching.len();
+        let items = matching
+            .into_iter()
+            .skip((page - 1) * per_page)
+            .take(per_page)
+            .cloned()
+            .collect();
+
+        (items, total)
+    }
+
+    pub fn get(&self, id: u64) -> Option<ContactSubmission> {
+        self.inner.lock().unwrap().submissions.get(&id).cloned()
+    }
+
+    pub fn set_status(&self, id: u64, status: ContactStatus) -> Option<ContactSubmission> {
+        self.update(id, |s| s.status = status)
+    }
+
+    pub fn add_note(&self, id: u64, body: &str) -> Option<ContactSubmission> {
+        self.update(id, |s| {
+            s.notes.push(ContactNote {
+                body: body.to_string(),
+                created_at: Utc::now(),
+            })
+        })
+    }
+
+    pub fn delete(&self, id: u64) -> bool {
+        self.inner.lock().unwrap().submissions.remove(&id).is_some()
+    }
+
+    fn update(&self, id: u64, apply: impl FnOnce(&mut ContactSubmission)) -> Option<ContactSubmission> {
+        let mut inner = self.inner.lock().unwrap();
+        let submission = inner.submissions.get_mut(&id)?;
+        apply(submission);
+        submission.updated_at = Utc::now();
+        Some(submission.clone())
+    }
+}
+
+/// Normaliza los parámetros de paginación (página base 1)
+pub fn page_bounds(filter: &ContactFilter) -> (usize, usize) {
+    let page = filter.page.unwrap_or(1).max(1);
+    let per_page = filter
+        .per_page
+        .unwrap_or(DEFAULT_PER_PAGE)
+        .clamp(1, MAX_PER_PAGE);
+    (page, per_page)
+}
+
+fn matches_text(submission: &ContactSubmission, query: &str) -> bool {
+    [
+        &submission.name,
+        &submission.company,
+        &submission.email,
+        &submission.service,
+        &submission.message,
+    ]
+    .iter()
+    .any(|field| field.to_lowercase().contains(query))
+        || submission.fields.values().any(|value| match value {
+            Value::String(s) => s.to_lowercase().contains(query),
+            _ => false,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(name: &str, service: &str, message: &str) -> ContactRequest {
+        ContactRequest {
+            name: name.to_string(),
+            company: "ACME".to_string(),
+            email: format!("{}@example.com", name.to_lowercase()),
+            service: service.to_string(),
+            message: message.to_string(),
+            locale: None,
+        }
+    }
+
+    #[test]
+    fn filters_by_service_status_and_text() {
+        let store = ContactStore::new();
+        let first = store.insert("1.1.1.1", &request("Ana", "design", "Need a logo"));
+        store.insert("2.2.2.2", &request("Luis", "support", "Server is down"));
+        store.insert("3.3.3.3", &request("Eva", "design", "Website redesign"));
+        store.set_status(first.id, ContactStatus::Spam);
+
+        let by_service = ContactFilter { service: Some("DESIGN".into()), ..Default::default() };
+        assert_eq!(store.list(&by_service).1, 2);
+
+        let by_status = ContactFilter { status: Some(ContactStatus::New), ..Default::default() };
+        assert_eq!(store.list(&by_status).1, 2);
+
+        let by_text = ContactFilter { q: Some("server".into()), ..Default::default() };
+        let (items, total) = store.list(&by_text);
+        assert_eq!(total, 1);
+        assert_eq!(items[0].name, "Luis");
+    }
+
+    #[test]
+    fn paginates_newest_first() {
+        let store = ContactStore::new();
+        for i in 0..5 {
+            store.insert("ip", &request(&format!("N{i}"), "web", "hola"));
+        }
+
+        let filter = ContactFilter { page: Some(2), per_page: Some(2), ..Default::default() };
+        let (items, total) = store.list(&filter);
+        assert_eq!(total, 5);
+        assert_eq!(items.iter().map(|s| s.id).collect::<Vec<_>>(), vec![3, 2]);
+    }
+
+    #[test]
+    fn notes_and_delete() {
+        let store = ContactStore::new();
+        let submission = store.insert("ip", &request("Ana", "web", "hola"));
+
+        let updated = store.add_note(submission.id, "Llamar el lunes").unwrap();
+        assert_eq!(updated.notes.len(), 1);
+        assert!(store.add_note(999, "nope").is_none());
+
+        assert!(store.delete(submission.id));
+        assert!(store.get(submission.id).is_none());
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let path = std::env::temp_dir().join(format!("contacts-{}.json", uuid::Uuid::new_v4()));
+        assert!(ContactStore::load(&path).unwrap().get(1).is_none());
+
+        let store = ContactStore::new();
+        let first = store.insert("ip", &request("Ana", "web", "hola"));
+        store.add_note(first.id, "Llamar el lunes");
+        store.save(&path).unwrap();
+
+        let restored = ContactStore::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(restored.get(first.id).unwrap().notes.len(), 1);
+        // Los ids siguen después de los recuperados
+        assert_eq!(restored.insert("ip", &request("Luis", "web", "hola")).id, first.id + 1);
+    }
+}
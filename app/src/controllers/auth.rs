@@ -0,0 +1,54 @@
+use actix_web::{web, HttpRequest, HttpResponse, post};
+use crate::app_state::AppState;
+use crate::auth::{AuthError, API_KEY_HEADER};
+use crate::error::{ApiError, ErrorBody};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TokenResponse {
+    pub access_token: String,
+    #[schema(example = "Bearer")]
+    pub token_type: &'static str,
+    /// Segundos de validez del token
+    pub expires_in: u64,
+}
+
+/// Intercambia una API key (cabecera `X-Api-Key`) por un token JWT de corta duración
+#[utoipa::path(
+    tag = "auth",
+    security(("api_key" = [])),
+    responses(
+        (status = 200, description = "Token emitido", body = TokenResponse),
+        (status = 401, description = "Falta la API key o no es válida", body = ErrorBody),
+    )
+)]
+#[post("/token")]
+pub async fn issue_token(
+    req: HttpRequest,
+    app_state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let key = req
+        .headers()
+        .get(API_KEY_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .ok_or(AuthError::MissingCredentials)?;
+
+    let principal = app_state
+        .auth
+        .authenticate_api_key(key)
+        .ok_or(AuthError::InvalidCredentials)?;
+
+    let access_token = app_state
+        .auth
+        .issue_token(&principal)
+        .map_err(|e| ApiError::Internal(e.context("Error al firmar el token JWT")))?;
+
+    Ok(HttpResponse::Ok().json(TokenResponse {
+        access_token,
+        token_type: "Bearer",
+        expires_in: app_state.auth.token_ttl_secs(),
+    }))
+}
+
+controller!("/auth" => [issue_token]);
@@ -0,0 +1,175 @@
+use actix_web::{web, HttpResponse, Responder, get, patch, post, delete};
+use crate::app_state::AppState;
+use crate::auth::{AdminUser, ViewerUser};
+use crate::contact_store::page_bounds;
+use crate::error::{ApiError, ErrorBody};
+use crate::i18n::Message;
+use crate::models::contact::{AddNoteRequest, ContactFilter, ContactSubmission, UpdateStatusRequest};
+use serde::Serialize;
+use utoipa::ToSchema;
+use tracing::{debug, info};
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ContactPage {
+    pub items: Vec<ContactSubmission>,
+    pub total: usize,
+    pub page: usize,
+    pub per_page: usize,
+}
+
+fn not_found(id: u64) -> ApiError {
+    ApiError::NotFound(Message::new("error.contact_not_found").arg("id", id))
+}
+
+/// Lista las solicitudes de contacto, filtradas y paginadas
+#[utoipa::path(
+    tag = "contacts",
+    params(ContactFilter),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Página de solicitudes", body = ContactPage),
+        (status = 400, description = "Filtro no válido", body = ErrorBody),
+        (status = 401, description = "Falta el token o no es válido", body = ErrorBody),
+        (status = 403, description = "El rol del token no basta", body = ErrorBody),
+    )
+)]
+#[get("")]
+pub async fn list_contacts(
+    user: ViewerUser,
+    filter: web::Query<ContactFilter>,
+    app_state: web::Data<AppState>,
+) -> impl Responder {
+    debug!("{} lista solicitudes de contacto", user.0.subject);
+    let (items, total) = app_state.contact_store.list(&filter);
+    let (page, per_page) = page_bounds(&filter);
+
+    HttpResponse::Ok().json(ContactPage {
+        items,
+        total,
+        page,
+        per_page,
+    })
+}
+
+#[utoipa::path(
+    tag = "contacts",
+    params(("id" = u64, Path, description = "Identificador de la solicitud")),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Solicitud", body = ContactSubmission),
+        (status = 401, description = "Falta el token o no es válido", body = ErrorBody),
+        (status = 403, description = "El rol del token no basta", body = ErrorBody),
+        (status = 404, description = "No existe", body = ErrorBody),
+    )
+)]
+#[get("/{id}")]
+pub async fn get_contact(
+    user: ViewerUser,
+    id: web::Path<u64>,
+    app_state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let id = id.into_inner();
+    debug!("{} consulta la solicitud {}", user.0.subject, id);
+    match app_state.contact_store.get(id) {
+        Some(submission) => Ok(HttpResponse::Ok().json(submission)),
+        None => Err(not_found(id)),
+    }
+}
+
+#[utoipa::path(
+    tag = "contacts",
+    params(("id" = u64, Path, description = "Identificador de la solicitud")),
+    request_body = UpdateStatusRequest,
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Solicitud actualizada", body = ContactSubmission),
+        (status = 400, description = "Cuerpo no válido", body = ErrorBody),
+        (status = 401, description = "Falta el token o no es válido", body = ErrorBody),
+        (status = 403, description = "El rol del token no basta", body = ErrorBody),
+        (status = 404, description = "No existe", body = ErrorBody),
+    )
+)]
+#[patch("/{id}/status")]
+pub async fn update_contact_status(
+    admin: AdminUser,
+    id: web::Path<u64>,
+    body: web::Json<UpdateStatusRequest>,
+    app_state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let id = id.into_inner();
+    info!("{} cambia el estado de la solicitud {} a {:?}", admin.0.subject, id, body.status);
+    match app_state.contact_store.set_status(id, body.status) {
+        Some(submission) => Ok(HttpResponse::Ok().json(submission)),
+        None => Err(not_found(id)),
+    }
+}
+
+#[utoipa::path(
+    tag = "contacts",
+    params(("id" = u64, Path, description = "Identificador de la solicitud")),
+    request_body = AddNoteRequest,
+    security(("bearer" = [])),
+    responses(
+        (status = 201, description = "Nota añadida", body = ContactSubmission),
+        (status = 400, description = "Nota vacía o cuerpo no válido", body = ErrorBody),
+        (status = 401, description = "Falta el token o no es válido", body = ErrorBody),
+        (status = 403, description = "El rol del token no basta", body = ErrorBody),
+        (status = 404, description = "No existe", body = ErrorBody),
+    )
+)]
+#[post("/{id}/notes")]
+pub async fn add_contact_note(
+    admin: AdminUser,
+    id: web::Path<u64>,
+    body: web::Json<AddNoteRequest>,
+    app_state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let id = id.into_inner();
+
+    if body.body.trim().is_empty() {
+        return Err(ApiError::Validation {
+            field: "body",
+            message: Message::new("error.empty_note"),
+        });
+    }
+
+    info!("{} añade una nota a la solicitud {}", admin.0.subject, id);
+    match app_state.contact_store.add_note(id, body.body.trim()) {
+        Some(submission) => Ok(HttpResponse::Created().json(submission)),
+        None => Err(not_found(id)),
+    }
+}
+
+#[utoipa::path(
+    tag = "contacts",
+    params(("id" = u64, Path, description = "Identificador de la solicitud")),
+    security(("bearer" = [])),
+    responses(
+        (status = 204, description = "Solicitud eliminada"),
+        (status = 401, description = "Falta el token o no es válido", body = ErrorBody),
+        (status = 403, description = "El rol del token no basta", body = ErrorBody),
+        (status = 404, description = "No existe", body = ErrorBody),
+    )
+)]
+#[delete("/{id}")]
+pub async fn delete_contact(
+    admin: AdminUser,
+    id: web::Path<u64>,
+    app_state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let id = id.into_inner();
+    info!("{} elimina la solicitud {}", admin.0.subject, id);
+    if app_state.contact_store.delete(id) {
+        Ok(HttpResponse::NoContent().finish())
+    } else {
+        Err(not_found(id))
+    }
+}
+
+controller!("/admin/contacts" => [
+    list_contacts,
+    get_contact,
+    update_contact_status,
+    add_contact_note,
+    delete_contact,
+]);
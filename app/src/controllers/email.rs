@@ -1,29 +1,64 @@
-use actix_web::{web, HttpResponse, Responder, post, HttpRequest};
+use actix_web::{web, HttpResponse, post, HttpRequest};
+use crate::app_state::AppState;
 use crate::models::email::ContactRequest;
 use crate::config::Config;
+use crate::error::{ApiError, ErrorBody};
+use crate::forms::looks_like_email;
+use crate::i18n::{self, Locale, Message};
+use crate::notifications::{self, Notification};
+use crate::templates;
+use crate::webhooks::WebhookEvent;
+use email_sender::is_valid_address;
 use serde::Serialize;
+use tracing::warn;
+use utoipa::ToSchema;
 
-#[derive(Debug, Serialize)]
-pub struct ContactResponse {
-    pub success: bool,
-    pub message: String,
-    pub remaining_requests: Option<(usize, usize)>, // (por minuto, por 12 horas)
+/// Rechazo por rate limiting, contado en las métricas según la ventana agotada.
+/// `retry_after_secs` es lo que falta para que caduque la petición más antigua de esa ventana.
+pub fn rate_limited(app_state: &AppState, remaining: (usize, usize), retry_after_secs: u64) -> ApiError {
+    let window = match remaining {
+        (0, _) => "1m",
+        (_, 0) => "12h",
+        _ => "unknown",
+    };
+    app_state.metrics.rate_limit_rejections_total.with_label_values(&[window]).inc();
+
+    ApiError::RateLimited {
+        window,
+        remaining,
+        retry_after_secs: retry_after_secs.max(1),
+    }
 }
 
-#[derive(Debug, Serialize)]
-pub struct RateLimitResponse {
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ContactResponse {
     pub success: bool,
     pub message: String,
+    /// Peticiones que quedan en la ventana de un minuto y en la de 12 horas
+    #[schema(value_type = [usize], min_items = 2, max_items = 2, example = json!([2, 9]))]
     pub remaining_requests: (usize, usize), // (por minuto, por 12 horas)
-    pub retry_after: Option<String>,
 }
 
+/// Envía el formulario de contacto a los administradores y, si está activado, un acuse al cliente
+#[utoipa::path(
+    tag = "contact",
+    request_body = ContactRequest,
+    responses(
+        (status = 200, description = "Solicitud enviada", body = ContactResponse),
+        (status = 400, description = "Cuerpo no válido o email del cliente inválido", body = ErrorBody),
+        (status = 429, description = "Límite de peticiones superado; ver `Retry-After`", body = ErrorBody,
+            headers(("Retry-After" = u64, description = "Segundos hasta poder reintentar"))),
+        (status = 502, description = "El servidor de correo rechazó el mensaje", body = ErrorBody),
+        (status = 503, description = "Ningún canal de notificación respondió; `Retry-After` si falló el correo", body = ErrorBody,
+            headers(("Retry-After" = u64, description = "Segundos hasta poder reintentar"))),
+    )
+)]
 #[post("")]
 pub async fn contact_request(
     req: HttpRequest,
     data: web::Json<ContactRequest>,
-    app_state: web::Data<crate::app_state::AppState>,
-) -> impl Responder {
+    app_state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
     // Obtener la IP del cliente
     let client_ip = req
         .connection_info()
@@ -31,187 +66,111 @@ pub async fn contact_request(
         .unwrap_or("unknown")
         .to_string();
 
+    // El campo `locale` del cuerpo tiene prioridad sobre `Accept-Language`
+    let locale = match data.locale.as_deref().map(str::parse::<Locale>) {
+        Some(Ok(locale)) => {
+            i18n::set_current(locale);
+            locale
+        }
+        _ => i18n::current(),
+    };
+
+    // El email lo elige el cliente y se usa en el acuse y en las reglas de enrutado: se valida antes de enviar nada
+    if !looks_like_email(&data.email) || !is_valid_address(&data.email) {
+        return Err(ApiError::Validation {
+            field: "email",
+            message: Message::new("error.field_invalid_email"),
+        });
+    }
+
     // Verificar rate limiting
     let (can_request, remaining_requests) = app_state.rate_limiter.check_rate_limit(&client_ip);
     
     if !can_request {
-        let (remaining_minute, remaining_12h) = remaining_requests;
-        
-        let message = if remaining_minute == 0 {
-            "Demasiadas solicitudes por minuto. Intenta de nuevo en 1 minuto."
-        } else if remaining_12h == 0 {
-            "Has alcanzado el límite máximo de solicitudes por 12 horas. Intenta de nuevo más tarde."
-        } else {
-            "Demasiadas solicitudes. Intenta de nuevo más tarde."
-        };
-
-        return HttpResponse::TooManyRequests().json(RateLimitResponse {
-            success: false,
-            message: message.to_string(),
-            remaining_requests,
-            retry_after: Some("60s".to_string()), // 1 minuto
+        let retry_after = app_state.rate_limiter.retry_after(&client_ip, Config::get_rate_limits());
+        return Err(rate_limited(&app_state, remaining_requests, retry_after));
+    }
+
+    // Guardar la solicitud para que pueda revisarse desde la API de administración
+    // y avisar a los webhooks, que se entregan en segundo plano
+    let submission = app_state.contact_store.insert(&client_ip, &data);
+    app_state.webhooks.dispatch(&app_state.shutdown, WebhookEvent::ContactReceived, &submission);
+
+    // Destinatarios según las reglas de enrutado (servicio, palabras clave, dominio), o los admins
+    let admin_emails = Config::get_contact_recipients(&data);
+
+    // Notificación para los admins en el idioma por defecto, acuse para el cliente en el suyo
+    let now = chrono::Utc::now();
+    let admin_locale = Config::get_default_locale();
+    let html_body = templates::contact_notification(admin_locale, &client_ip, &data, now);
+    let acknowledgement = Config::get_contact_acknowledgement()
+        .then(|| (data.email.clone(), templates::contact_acknowledgement(locale, &data, now)));
+
+    // Avisar a los admins por cada canal configurado (correo y chats).
+    // Se lanza como tarea del apagado ordenado: si el cliente corta o el servidor se detiene
+    // la notificación se sigue enviando y el apagado la espera. Solo falla si fallan todos los canales.
+    let notification = Notification {
+        title: i18n::text(admin_locale, "notification.subject"),
+        html: html_body,
+        fields: templates::contact_fields(admin_locale, &client_ip, &data),
+        recipients: admin_emails,
+    };
+    notifications::deliver(&app_state, Config::get_contact_channels(), notification).await?;
+
+    // El acuse no bloquea la respuesta; si falla (p. ej. dirección del cliente inválida) solo se registra
+    if let Some((customer, body)) = acknowledgement {
+        let email_sender = app_state.email_sender.clone();
+        app_state.shutdown.spawn(async move {
+            let subject = i18n::text(locale, "acknowledgement.subject");
+            if let Err(e) = email_sender.send_html_email(&[customer], &subject, &body).await {
+                warn!(error = %e, "No se pudo enviar el acuse de recibo");
+            }
         });
     }
 
-    // Obtener la lista de emails de admin desde la configuración
-    let admin_emails = Config::get_admin_emails_list();
-
-    // Crear el contenido del email de notificación con la IP
-    let html_body = format!(
-        r#"
-        <!DOCTYPE html>
-        <html lang="es">
-        <head>
-            <meta charset="UTF-8">
-            <meta name="viewport" content="width=device-width, initial-scale=1.0">
-            <title>Nueva Solicitud de Contacto</title>
-            <style>
-                body {{
-                    font-family: 'Segoe UI', Tahoma, Geneva, Verdana, sans-serif;
-                    line-height: 1.6;
-                    color: #333;
-                    max-width: 600px;
-                    margin: 0 auto;
-                    padding: 20px;
-                    background-color: #f4f4f4;
-                }}
-                .container {{
-                    background-color: #ffffff;
-                    border-radius: 10px;
-                    padding: 30px;
-                    box-shadow: 0 4px 6px rgba(0, 0, 0, 0.1);
-                }}
-                .header {{
-                    background: linear-gradient(135deg, #667eea 0%, #764ba2 100%);
-                    color: white;
-                    padding: 20px;
-                    border-radius: 8px;
-                    text-align: center;
-                    margin-bottom: 25px;
-                }}
-                .header h1 {{
-                    margin: 0;
-                    font-size: 24px;
-                    font-weight: 300;
-                }}
-                .info-section {{
-                    background-color: #f8f9fa;
-                    border-left: 4px solid #667eea;
-                    padding: 15px;
-                    margin: 15px 0;
-                    border-radius: 0 5px 5px 0;
-                }}
-                .info-section h3 {{
-                    margin: 0 0 10px 0;
-                    color: #667eea;
-                    font-size: 16px;
-                }}
-                .field {{
-                    margin: 10px 0;
-                }}
-                .field strong {{
-                    color: #555;
-                    display: inline-block;
-                    width: 80px;
-                }}
-                .message-box {{
-                    background-color: #fff3cd;
-                    border: 1px solid #ffeaa7;
-                    border-radius: 5px;
-                    padding: 15px;
-                    margin: 15px 0;
-                }}
-                .footer {{
-                    text-align: center;
-                    margin-top: 30px;
-                    padding-top: 20px;
-                    border-top: 1px solid #eee;
-                    color: #666;
-                    font-size: 14px;
-                }}
-                .ip-badge {{
-                    background-color: #e3f2fd;
-                    color: #1976d2;
-                    padding: 5px 10px;
-                    border-radius: 15px;
-                    font-size: 12px;
-                    font-family: monospace;
-                }}
-            </style>
-        </head>
-        <body>
-            <div class="container">
-                <div class="header">
-                    <h1>📧 Nueva Solicitud de Contacto</h1>
-                </div>
-                
-                <div class="info-section">
-                    <h3>📍 Información del Cliente</h3>
-                    <div class="field">
-                        <strong>IP:</strong> 
-                        <span class="ip-badge">{}</span>
-                    </div>
-                    <div class="field">
-                        <strong>Nombre:</strong> {}
-                    </div>
-                    <div class="field">
-                        <strong>Empresa:</strong> {}
-                    </div>
-                    <div class="field">
-                        <strong>Email:</strong> {}
-                    </div>
-                    <div class="field">
-                        <strong>Servicio:</strong> {}
-                    </div>
-                </div>
-                
-                <div class="message-box">
-                    <h3>💬 Mensaje del Cliente</h3>
-                    <p style="white-space: pre-wrap; margin: 0;">{}</p>
-                </div>
-                
-                <div class="footer">
-                    <p>Este email fue generado automáticamente por el sistema de contactos.</p>
-                    <p>Fecha y hora: {}</p>
-                </div>
-            </div>
-        </body>
-        </html>
-        "#,
-        client_ip, 
-        data.name, 
-        data.company, 
-        data.email, 
-        data.service, 
-        data.message,
-        chrono::Utc::now().format("%d/%m/%Y %H:%M:%S").to_string()
-    );
-
-    // Enviar el email de notificación HTML a los admins
-    match app_state.email_sender.send_html_email(
-        &admin_emails,
-        "Nueva solicitud de contacto",
-        &html_body,
-    ).await {
-        Ok(()) => {
-            HttpResponse::Ok().json(ContactResponse {
-                success: true,
-                message: "Solicitud de contacto enviada exitosamente".to_string(),
-                remaining_requests: Some(remaining_requests),
-            })
-        }
-        Err(e) => {
-            eprintln!("Error al enviar email de notificación: {}", e);
-            HttpResponse::InternalServerError().json(ContactResponse {
-                success: false,
-                message: "Error al procesar la solicitud de contacto".to_string(),
-                remaining_requests: None,
-            })
+    Ok(HttpResponse::Ok().json(ContactResponse {
+        success: true,
+        message: i18n::text(locale, "contact.sent"),
+        remaining_requests,
+    }))
+}
+
+controller!("/contact" => [contact_request]);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shutdown::Shutdown;
+    use crate::testing;
+    use actix_web::http::StatusCode;
+    use actix_web::{test, App};
+    use email_sender::EmailSender;
+    use serde_json::{json, Value};
+
+    fn request(email: &str) -> Value {
+        json!({ "name": "Ana", "company": "Acme", "email": email, "service": "web", "message": "Hola" })
+    }
+
+    #[actix_web::test]
+    async fn rejects_invalid_customer_addresses_before_sending() {
+        let _config = testing::LIVE_CONFIG.lock().await;
+        let state = web::Data::new(AppState::new(EmailSender::capture("no-reply@tusitio.com"), Shutdown::new()).await.unwrap());
+        let app = test::init_service(App::new().app_data(state.clone()).service((controller().scope)())).await;
+
+        for email in ["nadie", "ana@example.com, victima@example.com", "Ana <ana@example.com>", "ana@example.com\r\nBcc: x@example.com"] {
+            let req = test::TestRequest::post().uri("/contact").set_json(request(email)).to_request();
+            let res = test::call_service(&app, req).await;
+            assert_eq!(res.status(), StatusCode::BAD_REQUEST, "{}", email);
+            let body: Value = test::read_body_json(res).await;
+            assert_eq!(body["code"], "validation_failed");
+            assert_eq!(body["details"]["field"], "email");
         }
+        assert!(state.email_sender.captured().unwrap().emails().is_empty());
+
+        let req = test::TestRequest::post().uri("/contact").set_json(request("ana@example.com")).to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+        // Sin contact_acknowledgement solo se avisa a los admins
+        let emails = state.email_sender.captured().unwrap().emails();
+        assert_eq!(emails.iter().map(|e| e.to.as_str()).collect::<Vec<_>>(), ["admin@example.com"]);
     }
 }
-
-pub fn routes() -> actix_web::Scope {
-    web::scope("/contact")
-        .service(contact_request)
-} 
\ No newline at end of file
@@ -0,0 +1,137 @@
+use actix_web::{web, HttpRequest, HttpResponse, post};
+use crate::app_state::AppState;
+use crate::config::Config;
+use crate::controllers::email::{rate_limited, ContactResponse};
+use crate::error::{ApiError, ErrorBody};
+use crate::i18n::{self, Message};
+use crate::notifications::{self, Notification};
+use crate::templates;
+use crate::webhooks::WebhookEvent;
+use serde_json::{Map, Value};
+use tracing::info;
+
+/// Valida, guarda y notifica el envío de uno de los formularios definidos en `forms_file`
+#[utoipa::path(
+    tag = "forms",
+    params(("form_id" = String, Path, description = "Id del formulario en `forms_file`")),
+    request_body(content = Object, description = "Valores de los campos, por nombre"),
+    responses(
+        (status = 200, description = "Formulario enviado", body = ContactResponse),
+        (status = 400, description = "Campos no válidos; `details.fields` indica el motivo de cada uno", body = ErrorBody),
+        (status = 404, description = "Formulario no definido", body = ErrorBody),
+        (status = 429, description = "Límite de peticiones superado; ver `Retry-After`", body = ErrorBody,
+            headers(("Retry-After" = u64, description = "Segundos hasta poder reintentar"))),
+        (status = 502, description = "El servidor de correo rechazó el mensaje", body = ErrorBody),
+        (status = 503, description = "Ningún canal de notificación respondió; `Retry-After` si falló el correo", body = ErrorBody,
+            headers(("Retry-After" = u64, description = "Segundos hasta poder reintentar"))),
+    )
+)]
+#[post("/{form_id}")]
+pub async fn submit_form(
+    req: HttpRequest,
+    form_id: web::Path<String>,
+    data: web::Json<Map<String, Value>>,
+    app_state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let form_id = form_id.into_inner();
+    let form = Config::get_form(&form_id)
+        .ok_or_else(|| ApiError::NotFound(Message::new("error.form_not_found").arg("id", &form_id)))?;
+
+    let client_ip = req
+        .connection_info()
+        .realip_remote_addr()
+        .unwrap_or("unknown")
+        .to_string();
+
+    // Un envío inválido no consume cupo del rate limiting
+    let fields = form.validate(&data).map_err(ApiError::InvalidFields)?;
+
+    // Con límites propios cada formulario lleva su cuenta; si no, comparte la del formulario de contacto
+    let (key, limits) = match form.rate_limit {
+        Some(policy) => (format!("{}:{}", form_id, client_ip), (policy.per_minute, policy.per_12h)),
+        None => (client_ip.clone(), Config::get_rate_limits()),
+    };
+    let (can_request, remaining_requests) = app_state.rate_limiter.check(&key, limits);
+    if !can_request {
+        let retry_after = app_state.rate_limiter.retry_after(&key, limits);
+        return Err(rate_limited(&app_state, remaining_requests, retry_after));
+    }
+
+    let submission = app_state.contact_store.insert_form(&client_ip, &form_id, fields);
+    info!(form = %form_id, id = submission.id, "Formulario recibido");
+    app_state.webhooks.dispatch(&app_state.shutdown, WebhookEvent::FormSubmitted, &submission);
+
+    let recipients = if form.recipients.is_empty() {
+        Config::get_admin_emails_list()
+    } else {
+        form.recipients.clone()
+    };
+    let admin_locale = Config::get_default_locale();
+    let subject = form.subject.clone().unwrap_or_else(|| {
+        Message::new("form.subject").arg("form", form.title(&form_id)).render(admin_locale)
+    });
+    let html_body = templates::form_notification(
+        admin_locale,
+        &form_id,
+        form,
+        &client_ip,
+        &submission.fields,
+        submission.created_at,
+    );
+
+    // Igual que en el formulario de contacto, la notificación sobrevive al cliente y el apagado la espera
+    let notification = Notification {
+        title: subject,
+        html: html_body,
+        fields: templates::form_fields(admin_locale, form, &client_ip, &submission.fields),
+        recipients,
+    };
+    notifications::deliver(&app_state, &form.channels, notification).await?;
+
+    Ok(HttpResponse::Ok().json(ContactResponse {
+        success: true,
+        message: i18n::text(i18n::current(), "form.sent"),
+        remaining_requests,
+    }))
+}
+
+controller!("/forms" => [submit_form]);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i18n::Locale;
+    use crate::testing::{self, call};
+    use actix_web::http::StatusCode;
+    use actix_web::{test, App};
+    use serde_json::json;
+
+    #[actix_web::test]
+    async fn submits_the_defined_forms() {
+        let _config = testing::LIVE_CONFIG.lock().await;
+        let state = testing::app_state().await;
+        let app = test::init_service(App::new().app_data(state.clone()).service((controller().scope)())).await;
+        let post = |uri: &str, body: Value| test::TestRequest::post().uri(uri).set_json(body).to_request();
+
+        let (status, body) = call(&app, post("/forms/nope", json!({ "name": "Ana" }))).await;
+        assert_eq!((status, body["code"].as_str()), (StatusCode::NOT_FOUND, Some("not_found")));
+
+        let (status, body) = call(&app, post("/forms/quote", json!({ "email": "no-es-un-email", "extra": 1 }))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "validation_failed");
+        let fields = body["details"]["fields"].as_object().unwrap();
+        assert_eq!(fields["name"], i18n::text(Locale::Es, "error.field_required"));
+        assert!(fields.contains_key("email"));
+        assert!(fields.contains_key("extra"));
+        assert!(state.email_sender.captured().unwrap().emails().is_empty());
+
+        let (status, body) = call(&app, post("/forms/quote", json!({ "name": "Ana", "email": "ana@example.com" }))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["success"], true);
+        assert_eq!(body["remaining_requests"], json!([1, 9]));
+        let emails = state.email_sender.captured().unwrap().emails();
+        assert_eq!(emails.len(), 1);
+        assert_eq!(emails[0].to, "admin@example.com");
+        assert!(emails[0].body.contains("Ana"));
+    }
+}
@@ -0,0 +1,42 @@
+use actix_web::{web, HttpResponse, Responder, get};
+use crate::app_state::AppState;
+use crate::health::{HealthReport, HealthStatus};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LivenessResponse {
+    pub status: HealthStatus,
+}
+
+/// El proceso está vivo y atendiendo peticiones; no comprueba dependencias
+#[utoipa::path(
+    tag = "health",
+    responses((status = 200, description = "Proceso vivo", body = LivenessResponse))
+)]
+#[get("/live")]
+pub async fn live() -> impl Responder {
+    HttpResponse::Ok().json(LivenessResponse {
+        status: HealthStatus::Up,
+    })
+}
+
+/// Comprueba todas las dependencias registradas; 503 si alguna no está disponible
+#[utoipa::path(
+    tag = "health",
+    responses(
+        (status = 200, description = "Todas las dependencias disponibles", body = HealthReport),
+        (status = 503, description = "Alguna dependencia no está disponible", body = HealthReport),
+    )
+)]
+#[get("/ready")]
+pub async fn ready(app_state: web::Data<AppState>) -> impl Responder {
+    let report = app_state.health.run().await;
+
+    match report.status {
+        HealthStatus::Up => HttpResponse::Ok().json(report),
+        HealthStatus::Down => HttpResponse::ServiceUnavailable().json(report),
+    }
+}
+
+controller!("/health" => [live, ready]);
@@ -0,0 +1,108 @@
+use actix_web::{web, HttpResponse, get, put};
+use crate::auth::{AdminUser, ViewerUser};
+use crate::error::{ApiError, ErrorBody};
+use crate::i18n::Message;
+use crate::logging::{self, FilterError};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use utoipa::ToSchema;
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct LogFilterBody {
+    /// Directivas al estilo `RUST_LOG`, p. ej. `info,app=debug`
+    pub filter: String,
+}
+
+/// Filtro de logs activo
+#[utoipa::path(
+    tag = "admin",
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Filtro actual", body = LogFilterBody),
+        (status = 401, description = "Falta el token o no es válido", body = ErrorBody),
+        (status = 403, description = "El rol del token no basta", body = ErrorBody),
+        (status = 503, description = "El filtro no se puede cambiar en caliente", body = ErrorBody),
+    )
+)]
+#[get("")]
+pub async fn get_log_level(_user: ViewerUser) -> Result<HttpResponse, ApiError> {
+    match logging::current_filter() {
+        Some(filter) => Ok(HttpResponse::Ok().json(LogFilterBody { filter })),
+        None => Err(ApiError::ServiceUnavailable(Message::new("error.logging_unavailable"))),
+    }
+}
+
+/// Cambia el filtro de logs en caliente
+#[utoipa::path(
+    tag = "admin",
+    request_body = LogFilterBody,
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Filtro aplicado", body = LogFilterBody),
+        (status = 400, description = "Directivas no válidas", body = ErrorBody),
+        (status = 401, description = "Falta el token o no es válido", body = ErrorBody),
+        (status = 403, description = "El rol del token no basta", body = ErrorBody),
+        (status = 503, description = "El filtro no se puede cambiar en caliente", body = ErrorBody),
+    )
+)]
+#[put("")]
+pub async fn set_log_level(
+    admin: AdminUser,
+    body: web::Json<LogFilterBody>,
+) -> Result<HttpResponse, ApiError> {
+    match logging::set_filter(&body.filter) {
+        Ok(()) => {
+            info!("{} cambia el filtro de logs a '{}'", admin.0.subject, body.filter);
+            Ok(HttpResponse::Ok().json(LogFilterBody {
+                filter: logging::current_filter().unwrap_or_else(|| body.filter.clone()),
+            }))
+        }
+        Err(FilterError::Invalid(e)) => {
+            warn!("Filtro de logs rechazado: {}", e);
+            Err(ApiError::Validation {
+                field: "filter",
+                message: Message::new("error.invalid_log_filter").arg("cause", e),
+            })
+        }
+        Err(FilterError::Unavailable) => Err(ApiError::ServiceUnavailable(Message::new("error.logging_unavailable"))),
+    }
+}
+
+controller!("/admin/log-level" => [get_log_level, set_log_level]);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{self, call, with_key, ADMIN_KEY, VIEWER_KEY};
+    use actix_web::http::StatusCode;
+    use actix_web::{test, App};
+    use serde_json::json;
+
+    #[actix_web::test]
+    async fn reads_and_changes_the_filter() {
+        testing::init_logging();
+        let app = test::init_service(
+            App::new().app_data(testing::app_state().await).service((controller().scope)()),
+        )
+        .await;
+        let get = || test::TestRequest::get().uri("/admin/log-level");
+        let put = |filter: &str| test::TestRequest::put().uri("/admin/log-level").set_json(json!({ "filter": filter }));
+
+        let (status, body) = call(&app, with_key(get(), VIEWER_KEY).to_request()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["filter"].as_str(), logging::current_filter().as_deref());
+
+        let (status, body) = call(&app, with_key(put("debug"), VIEWER_KEY).to_request()).await;
+        assert_eq!((status, body["code"].as_str()), (StatusCode::FORBIDDEN, Some("forbidden")));
+
+        let (status, body) = call(&app, with_key(put("app=verbose"), ADMIN_KEY).to_request()).await;
+        assert_eq!((status, body["code"].as_str()), (StatusCode::BAD_REQUEST, Some("validation_failed")));
+        assert_eq!(body["details"]["field"], "filter");
+
+        let (status, body) = call(&app, with_key(put("warn,app=debug"), ADMIN_KEY).to_request()).await;
+        assert_eq!(status, StatusCode::OK);
+        // El filtro aplicado se devuelve normalizado, como lo muestra `tracing`
+        assert_eq!(body["filter"], "app=debug,warn");
+        assert_eq!(logging::current_filter().as_deref(), Some("app=debug,warn"));
+    }
+}
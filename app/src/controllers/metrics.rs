@@ -0,0 +1,29 @@
+use actix_web::{web, HttpResponse, Responder, get};
+use crate::app_state::AppState;
+use tracing::error;
+
+/// Exporta las métricas en formato de texto de Prometheus
+#[utoipa::path(
+    tag = "metrics",
+    responses((status = 200, description = "Métricas de Prometheus", content_type = "text/plain", body = String))
+)]
+#[get("")]
+pub async fn metrics(app_state: web::Data<AppState>) -> impl Responder {
+    // Gauges que se leen en el momento del scrape
+    app_state
+        .metrics
+        .rate_limiter_tracked_ips
+        .set(app_state.rate_limiter.tracked_ips() as i64);
+
+    match app_state.metrics.render() {
+        Ok(body) => HttpResponse::Ok()
+            .content_type("text/plain; version=0.0.4; charset=utf-8")
+            .body(body),
+        Err(e) => {
+            error!("Error al codificar las métricas: {}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+controller!("/metrics" => [metrics]);
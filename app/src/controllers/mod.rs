@@ -1 +1,43 @@
-pub mod email;
\ No newline at end of file
+use crate::common::Routes;
+
+/// Declares the controller of the module: the scope served under `$prefix` and its OpenAPI
+/// document are built from the same list of handlers, so a route can't be served undocumented
+macro_rules! controller {
+    ($prefix:literal => [$($handler:ident),+ $(,)?]) => {
+        #[derive(utoipa::OpenApi)]
+        #[openapi(paths($($handler),+))]
+        pub struct ApiDoc;
+
+        pub fn controller() -> $crate::common::Controller {
+            $crate::common::Controller {
+                prefix: $prefix,
+                scope: || actix_web::web::scope($prefix)$(.service($handler))+,
+                openapi: <ApiDoc as utoipa::OpenApi>::openapi,
+            }
+        }
+    };
+}
+
+pub mod auth;
+pub mod contacts;
+pub mod email;
+pub mod forms;
+pub mod health;
+pub mod log_level;
+pub mod metrics;
+pub mod suppressions;
+pub mod webhooks;
+
+/// Registers every controller; new controllers only need a line here
+pub fn register(routes: &mut Routes) {
+    routes
+        .root(health::controller())
+        .root(metrics::controller())
+        .api(auth::controller())
+        .api(email::controller())
+        .api(forms::controller())
+        .api(contacts::controller())
+        .api(log_level::controller())
+        .api(webhooks::controller())
+        .api(suppressions::controller());
+}
@@ -0,0 +1,242 @@
+use actix_web::{web, HttpResponse, get, post, delete};
+use crate::app_state::AppState;
+use crate::auth::{AdminUser, ViewerUser};
+use crate::bounces::{self, BounceFilter, BounceOutcome, RecordError};
+use crate::config::Config;
+use crate::error::{ApiError, ErrorBody};
+use crate::i18n::Message;
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use email_sender::Suppression;
+use serde::Serialize;
+use serde_json::json;
+use utoipa::ToSchema;
+use tracing::{debug, info};
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SuppressionEntry {
+    pub address: String,
+    /// `hard_bounce` o `complaint`
+    #[schema(example = "hard_bounce")]
+    pub reason: &'static str,
+    /// Estado DSN y respuesta del servidor remoto, o tipo de queja
+    pub detail: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<Suppression> for SuppressionEntry {
+    fn from(suppression: Suppression) -> Self {
+        Self {
+            address: suppression.address,
+            reason: suppression.reason.as_str(),
+            detail: suppression.detail,
+            created_at: suppression.created_at,
+        }
+    }
+}
+
+fn storage_error(e: anyhow::Error) -> ApiError {
+    ApiError::Internal(e.context("No se pudo guardar la lista de supresión"))
+}
+
+/// Los cambios de la lista escriben su archivo: se hacen fuera de los hilos que atienden peticiones
+async fn blocking<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> Result<T, ApiError> {
+    web::block(f).await.map_err(|e| ApiError::Internal(anyhow!("{}", e)))
+}
+
+/// Direcciones a las que no se envía correo, de la más reciente a la más antigua
+#[utoipa::path(
+    tag = "admin",
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Direcciones suprimidas", body = [SuppressionEntry]),
+        (status = 401, description = "Falta el token o no es válido", body = ErrorBody),
+        (status = 403, description = "El rol del token no basta", body = ErrorBody),
+    )
+)]
+#[get("")]
+pub async fn list_suppressions(user: ViewerUser, app_state: web::Data<AppState>) -> HttpResponse {
+    debug!("{} consulta la lista de supresión", user.0.subject);
+    let entries: Vec<SuppressionEntry> = app_state
+        .email_sender
+        .suppressions()
+        .list()
+        .into_iter()
+        .map(SuppressionEntry::from)
+        .collect();
+    HttpResponse::Ok().json(entries)
+}
+
+/// Vuelve a permitir el envío a una dirección
+#[utoipa::path(
+    tag = "admin",
+    params(("address" = String, Path, description = "Dirección suprimida")),
+    security(("bearer" = [])),
+    responses(
+        (status = 204, description = "Dirección quitada de la lista"),
+        (status = 401, description = "Falta el token o no es válido", body = ErrorBody),
+        (status = 403, description = "El rol del token no basta", body = ErrorBody),
+        (status = 404, description = "La dirección no está suprimida", body = ErrorBody),
+    )
+)]
+#[delete("/{address}")]
+pub async fn delete_suppression(
+    admin: AdminUser,
+    address: web::Path<String>,
+    app_state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let address = address.into_inner();
+    info!("{} quita {} de la lista de supresión", admin.0.subject, address);
+    let suppressions = app_state.email_sender.suppressions().clone();
+    let target = address.clone();
+    if blocking(move || suppressions.remove(&target)).await?.map_err(storage_error)? {
+        Ok(HttpResponse::NoContent().finish())
+    } else {
+        Err(ApiError::NotFound(Message::new("error.suppression_not_found").arg("address", &address)))
+    }
+}
+
+/// Vacía la lista de supresión
+#[utoipa::path(
+    tag = "admin",
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Número de direcciones quitadas", body = Object, example = json!({ "removed": 3 })),
+        (status = 401, description = "Falta el token o no es válido", body = ErrorBody),
+        (status = 403, description = "El rol del token no basta", body = ErrorBody),
+    )
+)]
+#[delete("")]
+pub async fn clear_suppressions(admin: AdminUser, app_state: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let suppressions = app_state.email_sender.suppressions().clone();
+    let removed = blocking(move || suppressions.clear()).await?.map_err(storage_error)?;
+    info!("{} vacía la lista de supresión ({} direcciones)", admin.0.subject, removed);
+    Ok(HttpResponse::Ok().json(json!({ "removed": removed })))
+}
+
+/// Recibe un informe de rebote (DSN) o de queja (ARF) en bruto, p. ej. reenviado por el MTA,
+/// y suprime los destinatarios con rebote permanente o queja. Solo se aceptan informes sobre correos
+/// enviados desde `smtp_from`, y los destinatarios configurados (admins, enrutado, formularios) nunca se suprimen.
+#[utoipa::path(
+    tag = "admin",
+    request_body(content = String, content_type = "message/rfc822", description = "Mensaje `multipart/report` completo"),
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "Informe procesado", body = BounceOutcome),
+        (status = 400, description = "El mensaje no es un informe de rebote ni de queja, o no es sobre un correo nuestro", body = ErrorBody),
+        (status = 401, description = "Falta el token o no es válido", body = ErrorBody),
+        (status = 403, description = "El rol del token no basta", body = ErrorBody),
+    )
+)]
+#[post("/bounces")]
+pub async fn report_bounce(
+    admin: AdminUser,
+    body: web::Bytes,
+    app_state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    debug!("{} envía un informe de rebote ({} bytes)", admin.0.subject, body.len());
+    let suppressions = app_state.email_sender.suppressions().clone();
+    let recorded = blocking(move || bounces::record(&suppressions, &BounceFilter::from_config(), &body)).await?;
+    match recorded {
+        Ok(outcome) => Ok(HttpResponse::Ok().json(outcome)),
+        Err(RecordError::NotAReport) => Err(ApiError::Validation {
+            field: "body",
+            message: Message::new("error.not_a_bounce_report"),
+        }),
+        Err(RecordError::Foreign(_)) => Err(ApiError::Validation {
+            field: "body",
+            message: Message::new("error.foreign_bounce_report").arg("sender", Config::get_smtp_from()),
+        }),
+        Err(RecordError::Storage(e)) => Err(storage_error(e)),
+    }
+}
+
+controller!("/admin/suppressions" => [
+    list_suppressions,
+    clear_suppressions,
+    report_bounce,
+    delete_suppression,
+]);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i18n::{self, Locale};
+    use crate::testing::{self, call, with_key, ADMIN_KEY, VIEWER_KEY};
+    use actix_web::http::StatusCode;
+    use actix_web::{test, App};
+    use email_sender::SuppressionReason;
+
+    const DSN: &str = "Content-Type: multipart/report; report-type=delivery-status; boundary=\"b\"\r\n\r\n\
+--b\r\nContent-Type: message/delivery-status\r\n\r\n\
+Reporting-MTA: dns; mx.tusitio.com\r\n\r\n\
+Final-Recipient: rfc822; nadie@example.com\r\nAction: failed\r\nStatus: 5.1.1\r\n\r\n\
+--b\r\nContent-Type: text/rfc822-headers\r\n\r\n\
+From: no-reply@tusitio.com\r\nTo: nadie@example.com\r\n\r\n\
+--b--\r\n";
+
+    #[actix_web::test]
+    async fn lists_and_deletes_suppressions() {
+        let state = testing::app_state().await;
+        let suppressions = state.email_sender.suppressions();
+        suppressions.add("ana@example.com", SuppressionReason::HardBounce, Some("5.1.1".to_string())).unwrap();
+        let app = test::init_service(App::new().app_data(state.clone()).service((controller().scope)())).await;
+
+        let (status, _) = call(&app, test::TestRequest::get().uri("/admin/suppressions").to_request()).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, body) = call(&app, with_key(test::TestRequest::get().uri("/admin/suppressions"), VIEWER_KEY).to_request()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body[0]["address"], "ana@example.com");
+        assert_eq!(body[0]["reason"], "hard_bounce");
+        assert_eq!(body[0]["detail"], "5.1.1");
+
+        let delete = || test::TestRequest::delete().uri("/admin/suppressions/ANA@example.com");
+        let (status, body) = call(&app, with_key(delete(), VIEWER_KEY).to_request()).await;
+        assert_eq!((status, body["code"].as_str()), (StatusCode::FORBIDDEN, Some("forbidden")));
+        assert!(suppressions.contains("ana@example.com"));
+
+        assert_eq!(call(&app, with_key(delete(), ADMIN_KEY).to_request()).await.0, StatusCode::NO_CONTENT);
+        assert!(suppressions.is_empty());
+        let (status, body) = call(&app, with_key(delete(), ADMIN_KEY).to_request()).await;
+        assert_eq!((status, body["code"].as_str()), (StatusCode::NOT_FOUND, Some("not_found")));
+    }
+
+    #[actix_web::test]
+    async fn clears_the_list() {
+        let state = testing::app_state().await;
+        let suppressions = state.email_sender.suppressions();
+        suppressions.add("ana@example.com", SuppressionReason::HardBounce, None).unwrap();
+        suppressions.add("luis@example.com", SuppressionReason::Complaint, None).unwrap();
+        let app = test::init_service(App::new().app_data(state.clone()).service((controller().scope)())).await;
+
+        let clear = || test::TestRequest::delete().uri("/admin/suppressions");
+        assert_eq!(call(&app, with_key(clear(), VIEWER_KEY).to_request()).await.0, StatusCode::FORBIDDEN);
+        assert_eq!(suppressions.len(), 2);
+
+        let (status, body) = call(&app, with_key(clear(), ADMIN_KEY).to_request()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["removed"], 2);
+        assert_eq!(call(&app, with_key(clear(), ADMIN_KEY).to_request()).await.1["removed"], 0);
+    }
+
+    #[actix_web::test]
+    async fn records_posted_bounce_reports() {
+        let state = testing::app_state().await;
+        let app = test::init_service(App::new().app_data(state.clone()).service((controller().scope)())).await;
+        let post = |body: &str| test::TestRequest::post().uri("/admin/suppressions/bounces").set_payload(body.to_string());
+
+        assert_eq!(call(&app, with_key(post(DSN), VIEWER_KEY).to_request()).await.0, StatusCode::FORBIDDEN);
+        assert!(state.email_sender.suppressions().is_empty());
+
+        let (status, body) = call(&app, with_key(post(DSN), ADMIN_KEY).to_request()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["suppressed"], serde_json::json!(["nadie@example.com"]));
+        assert!(state.email_sender.suppressions().contains("nadie@example.com"));
+
+        let (status, body) = call(&app, with_key(post("Subject: Hola\r\n\r\nHola"), ADMIN_KEY).to_request()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "validation_failed");
+        assert_eq!(body["details"]["field"], "body");
+        assert_eq!(body["message"], i18n::text(Locale::Es, "error.not_a_bounce_report"));
+    }
+}
@@ -0,0 +1,24 @@
+use actix_web::{web, HttpResponse, get};
+use crate::app_state::AppState;
+use crate::auth::ViewerUser;
+use crate::error::ErrorBody;
+use crate::webhooks::Delivery;
+use tracing::debug;
+
+/// Registro de entregas de webhooks, de la más reciente a la más antigua
+#[utoipa::path(
+    tag = "admin",
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Entregas registradas", body = [Delivery]),
+        (status = 401, description = "Falta el token o no es válido", body = ErrorBody),
+        (status = 403, description = "El rol del token no basta", body = ErrorBody),
+    )
+)]
+#[get("/deliveries")]
+pub async fn list_deliveries(user: ViewerUser, app_state: web::Data<AppState>) -> HttpResponse {
+    debug!("{} consulta las entregas de webhooks", user.0.subject);
+    HttpResponse::Ok().json(app_state.webhooks.deliveries())
+}
+
+controller!("/admin/webhooks" => [list_deliveries]);
@@ -0,0 +1,243 @@
+use actix_cors::Cors;
+use actix_web::http::header::HeaderName;
+use actix_web::http::Method;
+use anyhow::{anyhow, Result};
+use tracing::warn;
+
+use crate::common::ApplicationConfig;
+use crate::config::{Config, Mode};
+
+/// Effective CORS policy: mode defaults overridden by whatever `Config` defines
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorsSettings {
+    /// Allowed origins; `*` allows any origin
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<Method>,
+    /// Allowed request headers; empty allows any header
+    pub allowed_headers: Vec<HeaderName>,
+    pub allow_credentials: bool,
+    pub max_age: Option<usize>,
+}
+
+impl CorsSettings {
+    /// Permissive defaults for `dev`, strict defaults (no cross-origin access) everywhere else
+    pub fn defaults_for_mode(mode: Mode) -> Self {
+        let allowed_methods = vec![Method::GET, Method::POST, Method::PATCH, Method::DELETE];
+
+        if mode.permissive_cors() {
+            Self {
+                allowed_origins: vec!["*".to_string()],
+                allowed_methods,
+                allowed_headers: Vec::new(),
+                allow_credentials: false,
+                max_age: Some(3600),
+            }
+        } else {
+            Self {
+                allowed_origins: Vec::new(),
+                allowed_methods,
+                allowed_headers: vec![
+                    HeaderName::from_static("content-type"),
+                    HeaderName::from_static("authorization"),
+                    HeaderName::from_static("x-api-key"),
+                ],
+                allow_credentials: false,
+                max_age: Some(600),
+            }
+        }
+    }
+
+    /// Builds the policy for the configured mode, validating methods and headers
+    pub fn from_config() -> Result<Self> {
+        let mode = Config::get_mode();
+        let mut settings = Self::defaults_for_mode(mode);
+
+        if let Some(origins) = Config::get_cors_allowed_origins() {
+            settings.allowed_origins = origins;
+        }
+        if let Some(methods) = Config::get_cors_allowed_methods() {
+            settings.allowed_methods = methods
+                .iter()
+                .map(|m| {
+                    Method::from_bytes(m.to_ascii_uppercase().as_bytes())
+                        .map_err(|_| anyhow!("cors_allowed_methods: método inválido '{}'", m))
+                })
+                .collect::<Result<_>>()?;
+        }
+        if let Some(headers) = Config::get_cors_allowed_headers() {
+            settings.allowed_headers = headers
+                .iter()
+                .filter(|h| h.as_str() != "*")
+                .map(|h| {
+                    HeaderName::from_bytes(h.as_bytes())
+                        .map_err(|_| anyhow!("cors_allowed_headers: cabecera inválida '{}'", h))
+                })
+                .collect::<Result<_>>()?;
+        }
+        if let Some(allow_credentials) = Config::get_cors_allow_credentials() {
+            settings.allow_credentials = allow_credentials;
+        }
+        if let Some(max_age) = Config::get_cors_max_age() {
+            settings.max_age = Some(max_age);
+        }
+
+        if settings.allowed_origins.is_empty() {
+            warn!("CORS sin orígenes permitidos en modo '{}': se rechazarán peticiones cross-origin", mode);
+        }
+        if settings.allows_any_origin() && !mode.permissive_cors() {
+            warn!("CORS permite cualquier origen en modo '{}'", mode);
+        }
+
+        Ok(settings)
+    }
+
+    pub fn allows_any_origin(&self) -> bool {
+        self.allowed_origins.iter().any(|o| o == "*")
+    }
+
+    /// Builds the actix middleware for this policy. Each request's origin is checked against the reloadable
+    /// `cors_allowed_origins` setting, so a config reload changes the allowed origins live
+    pub fn build_live(&self) -> Cors {
+        self.build_with_origins(Config::get_cors_allowed_origins)
+    }
+
+    /// Origins come from `current` on every request; `None` falls back to this policy's origins
+    fn build_with_origins<F>(&self, current: F) -> Cors
+    where
+        F: Fn() -> Option<Vec<String>> + 'static,
+    {
+        let fallback = self.allowed_origins.clone();
+        self.base().allowed_origin_fn(move |origin, _| {
+            let Ok(origin) = origin.to_str() else {
+                return false;
+            };
+            let allowed = |origins: &[String]| origins.iter().any(|o| o == "*" || o == origin);
+            match current() {
+                Some(origins) => allowed(&origins),
+                None => allowed(&fallback),
+            }
+        })
+    }
+
+    /// Everything except the origin policy
+    fn base(&self) -> Cors {
+        let mut cors = Cors::default()
+            .allowed_methods(self.allowed_methods.clone())
+            .max_age(self.max_age);
+
+        cors = if self.allowed_headers.is_empty() {
+            cors.allow_any_header()
+        } else {
+            cors.allowed_headers(self.allowed_headers.clone())
+        };
+
+        if self.allow_credentials {
+            cors = cors.supports_credentials();
+        }
+
+        cors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::{header, StatusCode};
+    use actix_web::{test, web, App, HttpResponse};
+
+    fn preflight(origin: &str, method: &str) -> test::TestRequest {
+        test::TestRequest::default()
+            .method(Method::OPTIONS)
+            .uri("/api/v1/contact")
+            .insert_header((header::ORIGIN, origin))
+            .insert_header((header::ACCESS_CONTROL_REQUEST_METHOD, method))
+            .insert_header((header::ACCESS_CONTROL_REQUEST_HEADERS, "content-type"))
+    }
+
+    macro_rules! service_with {
+        ($settings:expr) => {
+            test::init_service(
+                App::new()
+                    .wrap($settings.build_with_origins(|| None))
+                    .route("/api/v1/contact", web::post().to(HttpResponse::Ok)),
+            )
+            .await
+        };
+    }
+
+    #[actix_web::test]
+    async fn dev_preflight_allows_any_origin() {
+        let app = service_with!(CorsSettings::defaults_for_mode(Mode::Dev));
+
+        let res = test::call_service(&app, preflight("http://localhost:3000", "POST").to_request()).await;
+
+        assert_eq!(res.status(), StatusCode::OK);
+        let headers = res.headers();
+        assert_eq!(headers.get(header::ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(), "http://localhost:3000");
+        assert_eq!(headers.get(header::ACCESS_CONTROL_MAX_AGE).unwrap(), "3600");
+        assert!(headers.get(header::ACCESS_CONTROL_ALLOW_CREDENTIALS).is_none());
+    }
+
+    #[actix_web::test]
+    async fn prod_preflight_rejects_unlisted_origin() {
+        let mut settings = CorsSettings::defaults_for_mode(Mode::Prod);
+        settings.allowed_origins = vec!["https://www.example.com".to_string()];
+        let app = service_with!(settings);
+
+        let res = test::call_service(&app, preflight("https://evil.example", "POST").to_request()).await;
+
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        assert!(res.headers().get(header::ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+    }
+
+    #[actix_web::test]
+    async fn prod_preflight_allows_listed_origin_with_credentials() {
+        let mut settings = CorsSettings::defaults_for_mode(Mode::Prod);
+        settings.allowed_origins = vec!["https://www.example.com".to_string()];
+        settings.allow_credentials = true;
+        let app = service_with!(settings);
+
+        let res = test::call_service(&app, preflight("https://www.example.com", "POST").to_request()).await;
+
+        assert_eq!(res.status(), StatusCode::OK);
+        let headers = res.headers();
+        assert_eq!(headers.get(header::ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(), "https://www.example.com");
+        assert_eq!(headers.get(header::ACCESS_CONTROL_ALLOW_CREDENTIALS).unwrap(), "true");
+        assert_eq!(headers.get(header::ACCESS_CONTROL_MAX_AGE).unwrap(), "600");
+    }
+
+    #[actix_web::test]
+    async fn prod_preflight_rejects_unlisted_method() {
+        let mut settings = CorsSettings::defaults_for_mode(Mode::Prod);
+        settings.allowed_origins = vec!["https://www.example.com".to_string()];
+        let app = service_with!(settings);
+
+        let res = test::call_service(&app, preflight("https://www.example.com", "PUT").to_request()).await;
+
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn live_policy_follows_reloaded_origins() {
+        use std::sync::{Arc, Mutex};
+
+        let origins = Arc::new(Mutex::new(None));
+        let current = origins.clone();
+        let cors = CorsSettings::defaults_for_mode(Mode::Prod)
+            .build_with_origins(move || current.lock().unwrap().clone());
+        let app = test::init_service(
+            App::new()
+                .wrap(cors)
+                .route("/api/v1/contact", web::post().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let res = test::call_service(&app, preflight("https://www.example.com", "POST").to_request()).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        *origins.lock().unwrap() = Some(vec!["https://www.example.com".to_string()]);
+        let res = test::call_service(&app, preflight("https://www.example.com", "POST").to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get(header::ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(), "https://www.example.com");
+    }
+}
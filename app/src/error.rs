@@ -0,0 +1,335 @@
+use std::fmt;
+
+use actix_web::error::{JsonPayloadError, PathError, QueryPayloadError};
+use actix_web::http::{header, StatusCode};
+use actix_web::{web, HttpRequest, HttpResponse, ResponseError};
+use email_sender::EmailError;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tracing::error;
+use utoipa::ToSchema;
+
+use crate::auth::AuthError;
+use crate::common::ApplicationConfig;
+use crate::config::Config;
+use crate::forms::FieldError;
+use crate::i18n::{self, Locale, Message};
+use crate::request_id;
+
+/// Largest JSON body accepted by any endpoint
+pub const JSON_LIMIT: usize = 64 * 1024;
+
+/// Suggested wait after a temporary mail failure
+const MAIL_RETRY_AFTER_SECS: u64 = 60;
+
+/// Error returned by every endpoint, rendered as an [`ErrorBody`].
+/// `code` values are part of the API contract: clients match on them, so never rename one.
+#[derive(Debug)]
+pub enum ApiError {
+    /// The body is not JSON or does not match the expected shape
+    InvalidJson(String),
+    PayloadTooLarge { limit: usize },
+    UnsupportedMediaType,
+    InvalidQuery(String),
+    InvalidPath(String),
+    /// The request is well formed but a field is not acceptable
+    Validation { field: &'static str, message: Message },
+    /// Submission of a configured form with one or more invalid fields
+    InvalidFields(Vec<FieldError>),
+    NotFound(Message),
+    Auth(AuthError),
+    RateLimited {
+        /// Window that was exhausted: `1m` or `12h`
+        window: &'static str,
+        remaining: (usize, usize),
+        retry_after_secs: u64,
+    },
+    ServiceUnavailable(Message),
+    /// The notification could not be handed to the mail transport
+    Mail(EmailError),
+    Internal(anyhow::Error),
+}
+
+/// JSON body of every error response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorBody {
+    pub success: bool,
+    pub code: &'static str,
+    pub message: String,
+    pub details: Option<Value>,
+    pub request_id: Option<String>,
+}
+
+impl ApiError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::InvalidJson(_) => "invalid_json",
+            ApiError::PayloadTooLarge { .. } => "payload_too_large",
+            ApiError::UnsupportedMediaType => "unsupported_media_type",
+            ApiError::InvalidQuery(_) => "invalid_query",
+            ApiError::InvalidPath(_) => "invalid_path",
+            ApiError::Validation { .. } | ApiError::InvalidFields(_) => "validation_failed",
+            ApiError::NotFound(_) => "not_found",
+            ApiError::Auth(AuthError::MissingCredentials) => "unauthenticated",
+            ApiError::Auth(AuthError::InvalidCredentials) => "invalid_credentials",
+            ApiError::Auth(AuthError::Forbidden(_)) => "forbidden",
+            ApiError::RateLimited { .. } => "rate_limited",
+            ApiError::ServiceUnavailable(_) => "service_unavailable",
+            ApiError::Mail(e) if e.is_retryable() => "mail_unavailable",
+            ApiError::Mail(EmailError::Suppressed { .. }) => "recipient_suppressed",
+            ApiError::Mail(_) => "mail_delivery_failed",
+            ApiError::Internal(_) => "internal_error",
+        }
+    }
+
+    fn details(&self, locale: Locale) -> Option<Value> {
+        match self {
+            ApiError::InvalidJson(cause) | ApiError::InvalidQuery(cause) | ApiError::InvalidPath(cause) => {
+                Some(json!({ "cause": cause }))
+            }
+            ApiError::PayloadTooLarge { limit } => Some(json!({ "limit_bytes": limit })),
+            ApiError::Validation { field, .. } => Some(json!({ "field": field })),
+            ApiError::InvalidFields(errors) => {
+                let fields: serde_json::Map<String, Value> = errors
+                    .iter()
+                    .map(|e| (e.field.clone(), Value::String(e.message.render(locale))))
+                    .collect();
+                Some(json!({ "fields": fields }))
+            }
+            ApiError::Auth(AuthError::Forbidden(role)) => Some(json!({ "required_role": role })),
+            ApiError::RateLimited { window, remaining, retry_after_secs } => Some(json!({
+                "window": window,
+                "remaining_requests": remaining,
+                "retry_after_secs": retry_after_secs,
+            })),
+            // El error original solo se expone en modo dev
+            ApiError::Mail(e) if Config::get_mode().verbose_errors() => Some(json!({
+                "retryable": e.is_retryable(),
+                "smtp_code": e.smtp_code(),
+                "cause": e.to_string(),
+            })),
+            ApiError::Mail(e) => Some(json!({ "retryable": e.is_retryable() })),
+            ApiError::Internal(e) if Config::get_mode().verbose_errors() => {
+                Some(json!({ "cause": format!("{:#}", e) }))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl ApiError {
+    /// Client-facing message in `locale`
+    pub fn message(&self, locale: Locale) -> String {
+        let message = match self {
+            ApiError::InvalidJson(_) => Message::new("error.invalid_json"),
+            ApiError::PayloadTooLarge { limit } => Message::new("error.payload_too_large").arg("limit", limit),
+            ApiError::UnsupportedMediaType => Message::new("error.unsupported_media_type"),
+            ApiError::InvalidQuery(_) => Message::new("error.invalid_query"),
+            ApiError::InvalidPath(_) => Message::new("error.invalid_path"),
+            ApiError::InvalidFields(_) => Message::new("error.invalid_fields"),
+            ApiError::Validation { message, .. } | ApiError::NotFound(message) | ApiError::ServiceUnavailable(message) => {
+                return message.render(locale);
+            }
+            ApiError::Auth(AuthError::MissingCredentials) => Message::new("error.unauthenticated"),
+            ApiError::Auth(AuthError::InvalidCredentials) => Message::new("error.invalid_credentials"),
+            ApiError::Auth(AuthError::Forbidden(role)) => Message::new("error.forbidden").arg("role", role),
+            ApiError::RateLimited { window: "1m", .. } => Message::new("error.rate_limited_minute"),
+            ApiError::RateLimited { window: "12h", .. } => Message::new("error.rate_limited_12h"),
+            ApiError::RateLimited { .. } => Message::new("error.rate_limited"),
+            ApiError::Mail(_) => Message::new("error.mail_failed"),
+            ApiError::Internal(_) => Message::new("error.internal"),
+        };
+        message.render(locale)
+    }
+}
+
+/// Message in the default language, for logs
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message(Locale::default()))
+    }
+}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::InvalidJson(_)
+            | ApiError::InvalidQuery(_)
+            | ApiError::InvalidPath(_)
+            | ApiError::Validation { .. }
+            | ApiError::InvalidFields(_) => StatusCode::BAD_REQUEST,
+            ApiError::PayloadTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            ApiError::UnsupportedMediaType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Auth(AuthError::Forbidden(_)) => StatusCode::FORBIDDEN,
+            ApiError::Auth(_) => StatusCode::UNAUTHORIZED,
+            ApiError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::ServiceUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::Mail(e) if e.is_retryable() => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::Mail(_) => StatusCode::BAD_GATEWAY,
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        match self {
+            ApiError::Mail(e) => error!(code = self.code(), error = %e, "{}", self),
+            ApiError::Internal(e) => error!(code = self.code(), error = %format!("{:#}", e), "{}", self),
+            _ => {}
+        }
+
+        let mut response = HttpResponse::build(self.status_code());
+        match self {
+            ApiError::Auth(AuthError::MissingCredentials | AuthError::InvalidCredentials) => {
+                response.insert_header((header::WWW_AUTHENTICATE, "Bearer"));
+            }
+            ApiError::RateLimited { retry_after_secs, .. } => {
+                response.insert_header((header::RETRY_AFTER, retry_after_secs.to_string()));
+            }
+            ApiError::Mail(e) if e.is_retryable() => {
+                response.insert_header((header::RETRY_AFTER, MAIL_RETRY_AFTER_SECS.to_string()));
+            }
+            _ => {}
+        }
+
+        let locale = i18n::current();
+        response.json(ErrorBody {
+            success: false,
+            code: self.code(),
+            message: self.message(locale),
+            details: self.details(locale),
+            request_id: request_id::current().map(|id| id.to_string()),
+        })
+    }
+}
+
+impl From<AuthError> for ApiError {
+    fn from(e: AuthError) -> Self {
+        ApiError::Auth(e)
+    }
+}
+
+impl From<JsonPayloadError> for ApiError {
+    fn from(e: JsonPayloadError) -> Self {
+        match e {
+            JsonPayloadError::OverflowKnownLength { limit, .. } | JsonPayloadError::Overflow { limit } => {
+                ApiError::PayloadTooLarge { limit }
+            }
+            JsonPayloadError::ContentType => ApiError::UnsupportedMediaType,
+            other => ApiError::InvalidJson(other.to_string()),
+        }
+    }
+}
+
+/// Body extractor settings: size limit and errors rendered as [`ErrorBody`]
+pub fn json_config() -> web::JsonConfig {
+    web::JsonConfig::default()
+        .limit(JSON_LIMIT)
+        .error_handler(|e, _req: &HttpRequest| ApiError::from(e).into())
+}
+
+pub fn query_config() -> web::QueryConfig {
+    web::QueryConfig::default()
+        .error_handler(|e: QueryPayloadError, _req: &HttpRequest| ApiError::InvalidQuery(e.to_string()).into())
+}
+
+pub fn path_config() -> web::PathConfig {
+    web::PathConfig::default()
+        .error_handler(|e: PathError, _req: &HttpRequest| ApiError::InvalidPath(e.to_string()).into())
+}
+
+/// Fallback for unknown routes
+pub async fn route_not_found() -> Result<HttpResponse, ApiError> {
+    Err(ApiError::NotFound(Message::new("error.route_not_found")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request_id::{assign_request_id, REQUEST_ID_HEADER};
+    use actix_web::middleware::from_fn;
+    use actix_web::{post, test, App};
+    use email_sender::SuppressionReason;
+
+    #[derive(serde::Deserialize)]
+    struct Note {
+        #[allow(dead_code)]
+        body: String,
+    }
+
+    #[post("/notes")]
+    async fn create_note(_note: web::Json<Note>) -> HttpResponse {
+        HttpResponse::Created().finish()
+    }
+
+    async fn call(req: test::TestRequest) -> (StatusCode, Value) {
+        let app = test::init_service(
+            App::new()
+                .app_data(json_config())
+                .wrap(from_fn(assign_request_id))
+                .service(create_note)
+                .default_service(web::to(route_not_found)),
+        )
+        .await;
+        let res = test::call_service(&app, req.insert_header((REQUEST_ID_HEADER, "req-42")).to_request()).await;
+        (res.status(), test::read_body_json(res).await)
+    }
+
+    #[actix_web::test]
+    async fn body_errors_use_the_json_envelope() {
+        let (status, body) = call(
+            test::TestRequest::post()
+                .uri("/notes")
+                .insert_header((header::CONTENT_TYPE, "application/json"))
+                .set_payload("{\"body\": 1"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["success"], false);
+        assert_eq!(body["code"], "invalid_json");
+        assert_eq!(body["request_id"], "req-42");
+        assert!(body["details"]["cause"].is_string());
+
+        let (status, body) = call(
+            test::TestRequest::post()
+                .uri("/notes")
+                .insert_header((header::CONTENT_TYPE, "application/json"))
+                .set_payload(format!("{{\"body\": \"{}\"}}", "a".repeat(JSON_LIMIT))),
+        )
+        .await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(body["details"]["limit_bytes"], JSON_LIMIT);
+
+        let (status, body) = call(test::TestRequest::get().uri("/nope")).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["code"], "not_found");
+    }
+
+    #[actix_web::test]
+    async fn rate_limit_sets_retry_after() {
+        let error = ApiError::RateLimited { window: "1m", remaining: (0, 3), retry_after_secs: 60 };
+        let res = error.error_response();
+        assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(res.headers().get(header::RETRY_AFTER).unwrap(), "60");
+        assert_eq!(error.details(Locale::Es).unwrap()["remaining_requests"], json!([0, 3]));
+    }
+
+    #[actix_web::test]
+    async fn mail_errors_follow_retryability() {
+        let transient = ApiError::Mail(EmailError::Timeout);
+        assert_eq!(transient.code(), "mail_unavailable");
+        assert_eq!(transient.error_response().status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(transient.error_response().headers().contains_key(header::RETRY_AFTER));
+
+        let permanent = ApiError::Mail(EmailError::PermanentRejection { code: 550, message: "no".to_string() });
+        assert_eq!(permanent.code(), "mail_delivery_failed");
+        assert_eq!(permanent.status_code(), StatusCode::BAD_GATEWAY);
+
+        let suppressed = ApiError::Mail(EmailError::Suppressed {
+            address: "ops@tusitio.com".to_string(),
+            reason: SuppressionReason::HardBounce,
+        });
+        assert_eq!(suppressed.code(), "recipient_suppressed");
+        assert!(!suppressed.error_response().headers().contains_key(header::RETRY_AFTER));
+    }
+}
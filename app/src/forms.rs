@@ -0,0 +1,385 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use regex::Regex;
+use serde::{Deserialize, Deserializer};
+use serde_json::{Map, Value};
+
+use crate::i18n::Message;
+use crate::notifications::ChannelSpec;
+
+/// Forms served at `/api/v1/forms/{form_id}`, read from `forms_file`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Forms(BTreeMap<String, FormDefinition>);
+
+/// One form: its fields, who receives it and how often a client may send it
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FormDefinition {
+    /// Name shown in the notification subject; the form id if unset
+    #[serde(default)]
+    pub title: Option<String>,
+    pub fields: Vec<FieldDefinition>,
+    /// Notification recipients; `admin_emails` if empty
+    #[serde(default)]
+    pub recipients: Vec<String>,
+    #[serde(default)]
+    pub subject: Option<String>,
+    /// Where submissions are announced: `email` (to the recipients) and chat incoming webhooks
+    #[serde(default = "default_channels")]
+    pub channels: Vec<ChannelSpec>,
+    /// HTML notification with `{{field}}`, `{{form}}`, `{{ip}}` and `{{date}}` placeholders.
+    /// Given as a path relative to the forms file, [`load`] replaces it with the file contents.
+    /// The generic field table is used if unset.
+    #[serde(default)]
+    pub template: Option<String>,
+    /// Own limits `(per minute, per 12 hours)`, counted apart from the other forms;
+    /// shares the contact form limits if unset
+    #[serde(default)]
+    pub rate_limit: Option<RateLimitPolicy>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimitPolicy {
+    pub per_minute: usize,
+    pub per_12h: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FieldDefinition {
+    pub name: String,
+    /// Shown in the notification instead of the field name
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(rename = "type", default)]
+    pub kind: FieldType,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub min_length: Option<usize>,
+    #[serde(default)]
+    pub max_length: Option<usize>,
+    #[serde(default)]
+    pub min: Option<f64>,
+    #[serde(default)]
+    pub max: Option<f64>,
+    #[serde(default)]
+    pub pattern: Option<Pattern>,
+    /// Accepted values of a `select` field
+    #[serde(default)]
+    pub options: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldType {
+    #[default]
+    Text,
+    Email,
+    Number,
+    Boolean,
+    Select,
+}
+
+/// Regular expression a text value must match entirely
+#[derive(Debug, Clone)]
+pub struct Pattern(Regex);
+
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
+}
+
+impl<'de> Deserialize<'de> for Pattern {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        Regex::new(&format!("^(?:{})$", pattern))
+            .map(Pattern)
+            .map_err(|e| serde::de::Error::custom(format!("patrón inválido '{}': {}", pattern, e)))
+    }
+}
+
+/// A field that failed validation and why
+#[derive(Debug)]
+pub struct FieldError {
+    pub field: String,
+    pub message: Message,
+}
+
+impl FieldError {
+    fn new(field: &str, key: &'static str) -> Self {
+        Self {
+            field: field.to_string(),
+            message: Message::new(key),
+        }
+    }
+}
+
+fn default_channels() -> Vec<ChannelSpec> {
+    vec![ChannelSpec::Email]
+}
+
+impl Forms {
+    pub fn get(&self, id: &str) -> Option<&FormDefinition> {
+        self.0.get(id)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &FormDefinition> {
+        self.0.values()
+    }
+}
+
+impl FormDefinition {
+    pub fn title<'a>(&'a self, id: &'a str) -> &'a str {
+        self.title.as_deref().unwrap_or(id)
+    }
+
+    pub fn field(&self, name: &str) -> Option<&FieldDefinition> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+
+    /// Checks every field of the submission against the definition and returns the values to keep,
+    /// trimmed and without empty optional fields. Unknown fields are rejected.
+    pub fn validate(&self, values: &Map<String, Value>) -> Result<BTreeMap<String, Value>, Vec<FieldError>> {
+        let mut errors: Vec<FieldError> = values
+            .keys()
+            .filter(|name| self.field(name).is_none())
+            .map(|name| FieldError::new(name, "error.field_unknown"))
+            .collect();
+
+        let mut accepted = BTreeMap::new();
+        for field in &self.fields {
+            match values.get(&field.name).map(normalize).filter(|v| !is_empty(v)) {
+                None if field.required => errors.push(FieldError::new(&field.name, "error.field_required")),
+                None => {}
+                Some(value) => match field.check(&value) {
+                    Ok(()) => {
+                        accepted.insert(field.name.clone(), value);
+                    }
+                    Err(message) => errors.push(FieldError {
+                        field: field.name.clone(),
+                        message,
+                    }),
+                },
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(accepted)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl FieldDefinition {
+    pub fn label(&self) -> &str {
+        self.label.as_deref().unwrap_or(&self.name)
+    }
+
+    fn check(&self, value: &Value) -> Result<(), Message> {
+        match (self.kind, value) {
+            (FieldType::Text | FieldType::Email | FieldType::Select, Value::String(text)) => {
+                let length = text.chars().count();
+                if let Some(min) = self.min_length.filter(|min| length < *min) {
+                    return Err(Message::new("error.field_too_short").arg("min", min));
+                }
+                if let Some(max) = self.max_length.filter(|max| length > *max) {
+                    return Err(Message::new("error.field_too_long").arg("max", max));
+                }
+                if self.kind == FieldType::Email && !looks_like_email(text) {
+                    return Err(Message::new("error.field_invalid_email"));
+                }
+                if self.kind == FieldType::Select && !self.options.contains(text) {
+                    return Err(Message::new("error.field_invalid_option").arg("options", self.options.join(", ")));
+                }
+                if self.pattern.as_ref().is_some_and(|p| !p.0.is_match(text)) {
+                    return Err(Message::new("error.field_invalid_format"));
+                }
+                Ok(())
+            }
+            (FieldType::Number, Value::Number(number)) => {
+                let number = number.as_f64().unwrap_or_default();
+                if let Some(min) = self.min.filter(|min| number < *min) {
+                    return Err(Message::new("error.field_below_min").arg("min", min));
+                }
+                if let Some(max) = self.max.filter(|max| number > *max) {
+                    return Err(Message::new("error.field_above_max").arg("max", max));
+                }
+                Ok(())
+            }
+            (FieldType::Boolean, Value::Bool(_)) => Ok(()),
+            _ => Err(Message::new("error.field_wrong_type").arg("type", self.kind.as_str())),
+        }
+    }
+}
+
+impl FieldType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FieldType::Text => "text",
+            FieldType::Email => "email",
+            FieldType::Number => "number",
+            FieldType::Boolean => "boolean",
+            FieldType::Select => "select",
+        }
+    }
+}
+
+fn normalize(value: &Value) -> Value {
+    match value {
+        Value::String(s) => Value::String(s.trim().to_string()),
+        other => other.clone(),
+    }
+}
+
+/// Missing, `null` and blank strings count as not sent
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(s) => s.is_empty(),
+        _ => false,
+    }
+}
+
+pub fn looks_like_email(address: &str) -> bool {
+    matches!(address.split_once('@'), Some((local, domain)) if !local.is_empty() && domain.contains('.'))
+}
+
+/// Reads the form definitions, a TOML or YAML table keyed by form id, and the templates they name
+pub fn load(path: &Path) -> Result<Forms, String> {
+    let contents = fs::read_to_string(path).map_err(|e| format!("no se pudo leer '{}': {}", path.display(), e))?;
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default();
+    let mut forms: BTreeMap<String, FormDefinition> = match extension.to_ascii_lowercase().as_str() {
+        "toml" => toml::from_str(&contents).map_err(|e| format!("TOML inválido: {}", e))?,
+        "yaml" | "yml" => serde_yaml::from_str(&contents).map_err(|e| format!("YAML inválido: {}", e))?,
+        other => return Err(format!("formato no soportado '{}' (usa .toml, .yaml o .yml)", other)),
+    };
+
+    let dir = path.parent().unwrap_or(Path::new(""));
+    for (id, form) in &mut forms {
+        check_definition(id, form)?;
+        if let Some(template) = &form.template {
+            let template_path = dir.join(template);
+            let html = fs::read_to_string(&template_path)
+                .map_err(|e| format!("{}: no se pudo leer la plantilla '{}': {}", id, template_path.display(), e))?;
+            form.template = Some(html);
+        }
+    }
+
+    Ok(Forms(forms))
+}
+
+fn check_definition(id: &str, form: &FormDefinition) -> Result<(), String> {
+    let invalid = |message: String| Err(format!("{}: {}", id, message));
+
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return invalid("el id solo admite letras, números, '-' y '_'".to_string());
+    }
+    if form.fields.is_empty() {
+        return invalid("el formulario no tiene campos".to_string());
+    }
+
+    let mut names = HashSet::new();
+    for field in &form.fields {
+        if !names.insert(field.name.as_str()) {
+            return invalid(format!("el campo '{}' está repetido", field.name));
+        }
+        if field.kind == FieldType::Select && field.options.is_empty() {
+            return invalid(format!("el campo '{}' es de tipo select y no tiene options", field.name));
+        }
+    }
+
+    if let Some(address) = form.recipients.iter().find(|a| !looks_like_email(a)) {
+        return invalid(format!("destinatario inválido '{}'", address));
+    }
+    if let Some(limits) = form.rate_limit {
+        if limits.per_minute == 0 || limits.per_12h < limits.per_minute {
+            return invalid(format!(
+                "los límites deben cumplir 0 < por minuto ({}) <= por 12 horas ({})",
+                limits.per_minute, limits.per_12h
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn quote() -> FormDefinition {
+        toml::from_str(
+            r#"
+recipients = ["ventas@tusitio.com"]
+
+[[fields]]
+name = "email"
+type = "email"
+required = true
+
+[[fields]]
+name = "budget"
+type = "number"
+min = 100
+
+[[fields]]
+name = "plan"
+type = "select"
+options = ["basic", "pro"]
+required = true
+
+[[fields]]
+name = "phone"
+pattern = "[0-9 +]{6,}"
+"#,
+        )
+        .unwrap()
+    }
+
+    fn errors(values: Value) -> Vec<(String, Message)> {
+        let Value::Object(values) = values else { unreachable!() };
+        quote()
+            .validate(&values)
+            .unwrap_err()
+            .into_iter()
+            .map(|e| (e.field, e.message))
+            .collect()
+    }
+
+    #[test]
+    fn accepts_valid_submissions_and_drops_empty_optionals() {
+        let Value::Object(values) = json!({ "email": " ana@example.com ", "plan": "pro", "phone": "" }) else {
+            unreachable!()
+        };
+        let accepted = quote().validate(&values).unwrap();
+        assert_eq!(accepted.get("email"), Some(&json!("ana@example.com")));
+        assert!(!accepted.contains_key("phone"));
+    }
+
+    #[test]
+    fn reports_every_invalid_field() {
+        assert_eq!(
+            errors(json!({ "email": "no", "budget": 10, "plan": "gold", "phone": "abc", "extra": 1 })),
+            [
+                ("extra".to_string(), Message::new("error.field_unknown")),
+                ("email".to_string(), Message::new("error.field_invalid_email")),
+                ("budget".to_string(), Message::new("error.field_below_min").arg("min", 100)),
+                ("plan".to_string(), Message::new("error.field_invalid_option").arg("options", "basic, pro")),
+                ("phone".to_string(), Message::new("error.field_invalid_format")),
+            ]
+        );
+        assert_eq!(
+            errors(json!({ "budget": "mucho", "plan": "basic" })),
+            [
+                ("email".to_string(), Message::new("error.field_required")),
+                ("budget".to_string(), Message::new("error.field_wrong_type").arg("type", "number")),
+            ]
+        );
+    }
+}
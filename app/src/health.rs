@@ -0,0 +1,208 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use email_sender::EmailSender;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Maximum time a single dependency check may take before it is reported as down
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub type CheckFuture<'a> = Pin<Box<dyn Future<Output = Result<Option<String>>> + Send + 'a>>;
+
+/// A dependency probed by the readiness endpoint.
+/// `check` resolves to optional details on success and to an error when the dependency is unusable.
+pub trait HealthCheck: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn check(&self) -> CheckFuture<'_>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Up,
+    Down,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ComponentReport {
+    pub name: &'static str,
+    pub status: HealthStatus,
+    pub latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct HealthReport {
+    pub status: HealthStatus,
+    pub checked_at: DateTime<Utc>,
+    pub components: Vec<ComponentReport>,
+}
+
+/// Registry of dependency checks run concurrently on every readiness probe
+#[derive(Default)]
+pub struct HealthRegistry {
+    checks: Vec<Box<dyn HealthCheck>>,
+}
+
+impl HealthRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, check: impl HealthCheck + 'static) {
+        self.checks.push(Box::new(check));
+    }
+
+    pub async fn run(&self) -> HealthReport {
+        let components = futures_util::future::join_all(self.checks.iter().map(|check| run_check(check.as_ref()))).await;
+
+        let status = if components.iter().all(|c| c.status == HealthStatus::Up) {
+            HealthStatus::Up
+        } else {
+            HealthStatus::Down
+        };
+
+        HealthReport {
+            status,
+            checked_at: Utc::now(),
+            components,
+        }
+    }
+}
+
+async fn run_check(check: &dyn HealthCheck) -> ComponentReport {
+    let started = Instant::now();
+    let result = tokio::time::timeout(CHECK_TIMEOUT, check.check())
+        .await
+        .unwrap_or_else(|_| Err(anyhow!("Tiempo de espera agotado tras {:?}", CHECK_TIMEOUT)));
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    match result {
+        Ok(details) => ComponentReport {
+            name: check.name(),
+            status: HealthStatus::Up,
+            latency_ms,
+            details,
+        },
+        Err(e) => ComponentReport {
+            name: check.name(),
+            status: HealthStatus::Down,
+            latency_ms,
+            details: Some(e.to_string()),
+        },
+    }
+}
+
+/// Checks that the SMTP relay accepts connections, without sending mail
+pub struct SmtpHealthCheck {
+    email_sender: EmailSender,
+}
+
+impl SmtpHealthCheck {
+    pub fn new(email_sender: EmailSender) -> Self {
+        Self { email_sender }
+    }
+}
+
+impl HealthCheck for SmtpHealthCheck {
+    fn name(&self) -> &'static str {
+        "smtp"
+    }
+
+    fn check(&self) -> CheckFuture<'_> {
+        let email_sender = self.email_sender.clone();
+        Box::pin(async move {
+            let probe = tokio::task::spawn_blocking(move || email_sender.probe()).await?;
+            if !probe.is_usable() {
+                return Err(anyhow!(probe.error.unwrap_or_else(|| "Relay SMTP no utilizable".to_string())));
+            }
+            Ok(Some(format!(
+                "{} ({}, auth ok, {} ms)",
+                probe.banner.unwrap_or_default(),
+                probe.tls_version.as_deref().unwrap_or("TLS"),
+                probe.latency_ms
+            )))
+        })
+    }
+}
+
+/// Checks that the `file` transport can still write to its mail directory
+pub struct MailDirHealthCheck {
+    email_sender: EmailSender,
+}
+
+impl MailDirHealthCheck {
+    pub fn new(email_sender: EmailSender) -> Self {
+        Self { email_sender }
+    }
+}
+
+impl HealthCheck for MailDirHealthCheck {
+    fn name(&self) -> &'static str {
+        "mail_dir"
+    }
+
+    fn check(&self) -> CheckFuture<'_> {
+        let email_sender = self.email_sender.clone();
+        Box::pin(async move {
+            let destination = email_sender.destination();
+            tokio::task::spawn_blocking(move || email_sender.test_connection()).await??;
+            Ok(Some(format!("{} admite escritura", destination)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct StaticCheck(&'static str, bool);
+
+    impl HealthCheck for StaticCheck {
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        fn check(&self) -> CheckFuture<'_> {
+            let ok = self.1;
+            Box::pin(async move {
+                if ok {
+                    Ok(None)
+                } else {
+                    Err(anyhow!("caído"))
+                }
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn report_is_down_when_any_component_fails() {
+        let mut registry = HealthRegistry::new();
+        registry.register(StaticCheck("a", true));
+        assert_eq!(registry.run().await.status, HealthStatus::Up);
+
+        registry.register(StaticCheck("b", false));
+        let report = registry.run().await;
+        assert_eq!(report.status, HealthStatus::Down);
+        assert_eq!(report.components[1].details.as_deref(), Some("caído"));
+    }
+
+    #[tokio::test]
+    async fn mail_dir_is_down_once_it_can_not_be_written() {
+        let dir = std::env::temp_dir().join(format!("health-mail-{}", std::process::id()));
+        let mut registry = HealthRegistry::new();
+        registry.register(MailDirHealthCheck::new(EmailSender::file(&dir, "no-reply@tusitio.com").unwrap()));
+        assert_eq!(registry.run().await.status, HealthStatus::Up);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        let report = registry.run().await;
+        assert_eq!(report.status, HealthStatus::Down);
+        assert_eq!(report.components[0].name, "mail_dir");
+    }
+}
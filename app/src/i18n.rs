@@ -0,0 +1,226 @@
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{self, HeaderValue};
+use actix_web::middleware::Next;
+use chrono::{DateTime, Datelike, Utc};
+use once_cell::sync::Lazy;
+
+use crate::config::Config;
+
+/// Languages with a message catalog under `app/locales`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Locale {
+    #[default]
+    Es,
+    En,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::Es, Locale::En];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Locale::Es => "es",
+            Locale::En => "en",
+        }
+    }
+
+    fn source(self) -> &'static str {
+        match self {
+            Locale::Es => include_str!("../locales/es.toml"),
+            Locale::En => include_str!("../locales/en.toml"),
+        }
+    }
+}
+
+/// Accepts language tags such as `en`, `en-US` or `es_MX`; only the primary subtag matters
+impl FromStr for Locale {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let primary = s.trim().split(['-', '_']).next().unwrap_or_default();
+        match primary.to_ascii_lowercase().as_str() {
+            "es" => Ok(Locale::Es),
+            "en" => Ok(Locale::En),
+            other => Err(format!("Idioma no soportado '{}' (usa es o en)", other)),
+        }
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+type Catalog = HashMap<String, toml::Value>;
+
+static CATALOGS: Lazy<HashMap<Locale, Catalog>> = Lazy::new(|| {
+    Locale::ALL
+        .into_iter()
+        .map(|locale| {
+            let table: toml::Table = toml::from_str(locale.source())
+                .unwrap_or_else(|e| panic!("Catálogo '{}' inválido: {}", locale, e));
+            let mut catalog = Catalog::new();
+            flatten("", table, &mut catalog);
+            (locale, catalog)
+        })
+        .collect()
+});
+
+fn flatten(prefix: &str, table: toml::Table, catalog: &mut Catalog) {
+    for (key, value) in table {
+        let key = if prefix.is_empty() { key } else { format!("{}.{}", prefix, key) };
+        match value {
+            toml::Value::Table(table) => flatten(&key, table, catalog),
+            value => {
+                catalog.insert(key, value);
+            }
+        }
+    }
+}
+
+fn lookup(locale: Locale, key: &str) -> Option<&'static toml::Value> {
+    CATALOGS[&locale].get(key).or_else(|| CATALOGS[&Locale::default()].get(key))
+}
+
+/// Message of `key` in `locale`, falling back to Spanish and then to the key itself
+pub fn text(locale: Locale, key: &str) -> String {
+    lookup(locale, key)
+        .and_then(toml::Value::as_str)
+        .unwrap_or(key)
+        .to_string()
+}
+
+/// A catalog message with its `{placeholder}` arguments, rendered once the locale is known
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    key: &'static str,
+    args: Vec<(&'static str, String)>,
+}
+
+impl Message {
+    pub fn new(key: &'static str) -> Self {
+        Self { key, args: Vec::new() }
+    }
+
+    pub fn arg(mut self, name: &'static str, value: impl ToString) -> Self {
+        self.args.push((name, value.to_string()));
+        self
+    }
+
+    pub fn render(&self, locale: Locale) -> String {
+        self.args
+            .iter()
+            .fold(text(locale, self.key), |text, (name, value)| text.replace(&format!("{{{}}}", name), value))
+    }
+}
+
+/// Long date and time in the conventions of `locale`, e.g. `19 de octubre de 2026, 14:05 UTC`
+pub fn format_datetime(locale: Locale, at: DateTime<Utc>) -> String {
+    let month = lookup(locale, "date.months")
+        .and_then(toml::Value::as_array)
+        .and_then(|months| months.get(at.month0() as usize))
+        .and_then(toml::Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+
+    Message::new("date.format")
+        .arg("day", at.day())
+        .arg("month", month)
+        .arg("year", at.year())
+        .arg("time", at.format(&text(locale, "date.time")))
+        .render(locale)
+}
+
+/// Picks the supported language with the highest `q` in an `Accept-Language` header
+pub fn negotiate(accept_language: Option<&str>) -> Option<Locale> {
+    let mut ranges: Vec<(f32, &str)> = accept_language?
+        .split(',')
+        .filter_map(|range| {
+            let mut parts = range.split(';');
+            let tag = parts.next()?.trim();
+            let q = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())?;
+            (q > 0.0).then_some((q, tag))
+        })
+        .collect();
+    // Orden estable: a igual calidad gana el primero de la cabecera
+    ranges.sort_by(|a, b| b.0.total_cmp(&a.0));
+    ranges.into_iter().find_map(|(_, tag)| tag.parse().ok())
+}
+
+tokio::task_local! {
+    static CURRENT: Cell<Locale>;
+}
+
+/// Language of the request being handled; the configured default outside a request
+pub fn current() -> Locale {
+    CURRENT.try_with(Cell::get).unwrap_or_else(|_| Config::get_default_locale())
+}
+
+/// Overrides the language of the current request, e.g. with the `locale` field of a body
+pub fn set_current(locale: Locale) {
+    let _ = CURRENT.try_with(|current| current.set(locale));
+}
+
+/// Middleware that negotiates the language from `Accept-Language` and reports it in `Content-Language`
+pub async fn negotiate_locale(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let accept_language = req.headers().get(header::ACCEPT_LANGUAGE).and_then(|v| v.to_str().ok());
+    let locale = negotiate(accept_language).unwrap_or_else(Config::get_default_locale);
+
+    let (res, locale) = CURRENT
+        .scope(Cell::new(locale), async move {
+            let res = next.call(req).await;
+            (res, current())
+        })
+        .await;
+
+    let mut res = res?;
+    res.headers_mut()
+        .insert(header::CONTENT_LANGUAGE, HeaderValue::from_static(locale.as_str()));
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn catalogs_define_the_same_keys() {
+        let keys = |locale| CATALOGS[&locale].keys().cloned().collect::<BTreeSet<_>>();
+        assert_eq!(keys(Locale::Es), keys(Locale::En));
+        assert_eq!(text(Locale::En, "no.such.key"), "no.such.key");
+    }
+
+    #[test]
+    fn negotiates_by_quality() {
+        assert_eq!(negotiate(Some("fr-FR, en-US;q=0.8, es;q=0.9")), Some(Locale::Es));
+        assert_eq!(negotiate(Some("en-GB,en;q=0.9")), Some(Locale::En));
+        assert_eq!(negotiate(Some("fr, de;q=0.5")), None);
+        assert_eq!(negotiate(Some("es;q=0, en;q=0.1")), Some(Locale::En));
+        assert_eq!(negotiate(None), None);
+    }
+
+    #[test]
+    fn renders_messages_and_dates() {
+        let message = Message::new("error.contact_not_found").arg("id", 7);
+        assert_eq!(message.render(Locale::Es), "Solicitud de contacto 7 no encontrada");
+        assert_eq!(message.render(Locale::En), "Contact request 7 not found");
+
+        let at = Utc.with_ymd_and_hms(2026, 10, 19, 14, 5, 0).unwrap();
+        assert_eq!(format_datetime(Locale::Es, at), "19 de octubre de 2026, 14:05 UTC");
+        assert_eq!(format_datetime(Locale::En, at), "October 19, 2026, 2:05 PM UTC");
+    }
+}
@@ -0,0 +1,74 @@
+use std::fmt;
+
+use anyhow::{anyhow, Result};
+use once_cell::sync::OnceCell;
+use tracing_subscriber::{reload, EnvFilter, Registry};
+
+pub type FilterLayer = reload::Layer<EnvFilter, Registry>;
+
+static FILTER_HANDLE: OnceCell<reload::Handle<EnvFilter, Registry>> = OnceCell::new();
+
+/// Why [`set_filter`] did not change the filter
+#[derive(Debug)]
+pub enum FilterError {
+    /// The directives don't parse
+    Invalid(anyhow::Error),
+    /// Logging was not initialized with a reloadable filter
+    Unavailable,
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterError::Invalid(e) => write!(f, "{}", e),
+            FilterError::Unavailable => f.write_str("El sistema de logs no está inicializado"),
+        }
+    }
+}
+
+impl std::error::Error for FilterError {}
+
+/// Parses `RUST_LOG`-style directives (`info,app=debug,actix_server=warn`)
+pub fn parse_filter(directives: &str) -> Result<EnvFilter> {
+    EnvFilter::try_new(directives).map_err(|e| anyhow!("Filtro de log inválido '{}': {}", directives, e))
+}
+
+/// Builds the reloadable filter layer and keeps its handle for later changes.
+/// Must be the first layer on top of the registry.
+pub fn filter_layer(directives: &str) -> Result<FilterLayer> {
+    let (layer, handle) = reload::Layer::new(parse_filter(directives)?);
+    FILTER_HANDLE
+        .set(handle)
+        .map_err(|_| anyhow!("El filtro de logs ya fue inicializado"))?;
+    Ok(layer)
+}
+
+/// Directives currently in effect, if logging has been initialized
+pub fn current_filter() -> Option<String> {
+    FILTER_HANDLE
+        .get()?
+        .with_current(|filter| filter.to_string())
+        .ok()
+}
+
+/// Replaces the active filter without restarting the process
+pub fn set_filter(directives: &str) -> Result<(), FilterError> {
+    let filter = parse_filter(directives).map_err(FilterError::Invalid)?;
+    FILTER_HANDLE
+        .get()
+        .ok_or(FilterError::Unavailable)?
+        .reload(filter)
+        // El subscriber que tenía la capa ya no existe
+        .map_err(|_| FilterError::Unavailable)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_directives() {
+        assert!(parse_filter("info,app=debug,actix_server=warn").is_ok());
+        assert!(parse_filter("app=verbose").is_err());
+    }
+}
@@ -1,15 +1,25 @@
-use email_sender::EmailSender;
+use email_sender::{DkimSigner, EmailSender, SuppressionList};
 
 use once_cell::sync::OnceCell;
 // use actix_files as fs;
-use actix_web::{App, HttpServer, middleware::Logger, web};
-use common::{Application, ApplicationConfig};
-use config::Config;
+use actix_web::{App, HttpServer, dev::Server, middleware::{from_fn, Condition}, web};
+use common::{Application, ApplicationConfig, Routes};
+use config::{Config, MailTransport};
+use services::Services;
+use shutdown::Shutdown;
+use std::net::TcpListener;
 use tracing::info;
 use anyhow::{anyhow, Result};
+use clap::Parser;
 use app_state::AppState;
+use cors::CorsSettings;
+use bounces::BounceMailbox;
 
 // Internal modules
+mod auth;
+mod banner;
+mod bounces;
+mod cli;
 mod common;
 mod config;
 mod controllers;
@@ -18,56 +28,210 @@ mod models;
 //mod schema;
 //mod middlewares;
 mod app_state;
+mod contact_store;
+mod cors;
+mod error;
+mod forms;
+mod health;
+mod i18n;
+mod logging;
+mod metrics;
+mod notifications;
+mod openapi;
 mod rate_limiter;
+mod request_id;
+mod routing;
+mod security_headers;
+mod services;
+mod shutdown;
+mod telemetry;
+mod templates;
+mod webhooks;
+#[cfg(test)]
+mod testing;
 
-struct AppServer;
+struct AppServer {
+    cli: cli::Cli,
+    lifecycle: Shutdown,
+    state: OnceCell<web::Data<AppState>>,
+}
 
-static APP_STATE: OnceCell<web::Data<AppState>> = OnceCell::new();
+impl AppServer {
+    fn new(cli: cli::Cli) -> Self {
+        Self {
+            cli,
+            lifecycle: Shutdown::new(),
+            state: OnceCell::new(),
+        }
+    }
+
+    fn state(&self) -> &web::Data<AppState> {
+        self.state.get().expect("App state not initialized")
+    }
+}
 
 impl Application for AppServer {
+    fn load_config(&self) -> Result<()> {
+        Config::init(&self.cli.config_sources())
+    }
+
+    fn lifecycle(&self) -> &Shutdown {
+        &self.lifecycle
+    }
+
     async fn setup(&self) -> Result<()> {
         //info!("Initializing the database...");
         //Database::init(Config::get_max_pool_size(), Config::get_with_migrations())?;
         //info!("Migrations applied successfully");
 
-        info!("Initializing SMTP client");
-        let email_sender = EmailSender::new(
-            Config::get_smtp_server(),
-            Config::get_smtp_user(),
-            Config::get_smtp_password(),
-            Config::get_smtp_from(),
-        )?;
-        info!("SMTP client initialized successfully");
-
-        let app_state = web::Data::new(AppState::new(email_sender).await?);
-        APP_STATE.set(app_state).map_err(|_| anyhow!("APP_STATE ya fue inicializado"))?;
+        let email_sender = match Config::get_mail_transport() {
+            MailTransport::Smtp => {
+                info!("Initializing SMTP client");
+                let sender = EmailSender::new(
+                    Config::get_smtp_server(),
+                    Config::get_smtp_user(),
+                    Config::get_smtp_password().expose(),
+                    Config::get_smtp_from(),
+                )?;
+                info!("SMTP client initialized successfully");
+                sender
+            }
+            MailTransport::File => EmailSender::file(Config::get_mail_dir(), Config::get_smtp_from())?,
+            MailTransport::Capture => EmailSender::capture(Config::get_smtp_from()),
+        };
+        let email_sender = match Config::get_dkim() {
+            Some((domain, selector, key_file)) => {
+                let signer = DkimSigner::from_file(domain, selector, key_file, Config::get_dkim_headers())?;
+                info!("Signing outgoing mail with DKIM ({}, selector {}, {})", domain, selector, signer.algorithm());
+                email_sender.with_dkim(signer)
+            }
+            None => email_sender,
+        };
+        // Direcciones que rebotaron o se quejaron: no se les vuelve a escribir
+        let suppressions = match Config::get_suppression_file() {
+            Some(path) => {
+                let list = SuppressionList::open(path)?;
+                info!("Loaded {} suppressed addresses from {}", list.len(), path.display());
+                list
+            }
+            None => SuppressionList::new(),
+        };
+        let email_sender = email_sender.with_suppressions(suppressions);
+
+        if Config::get_smtp_check_on_startup() && Config::get_mail_transport() == MailTransport::Smtp {
+            info!("Probing SMTP relay {}", Config::get_smtp_server());
+            let sender = email_sender.clone();
+            let probe = tokio::task::spawn_blocking(move || sender.probe()).await?;
+            if !probe.is_usable() {
+                return Err(anyhow!(
+                    "El relay SMTP no es utilizable: {}",
+                    probe.error.unwrap_or_else(|| "conexión sin cifrar".to_string())
+                ));
+            }
+            info!(
+                "SMTP relay ready: {} ({}, {} ms)",
+                probe.banner.unwrap_or_default(),
+                probe.tls_version.as_deref().unwrap_or("TLS"),
+                probe.latency_ms
+            );
+        }
+
+        let app_state = web::Data::new(AppState::new(email_sender, self.lifecycle.clone()).await?);
+
+        // Guardar las solicitudes de contacto una vez drenadas las peticiones y los correos
+        if let Some(path) = Config::get_state_file() {
+            let store = app_state.contact_store.clone();
+            self.lifecycle.on_shutdown("contact_store", move || async move {
+                store.save(path)?;
+                info!("Contact requests saved to {}", path.display());
+                Ok(())
+            });
+        }
+
+        self.state.set(app_state).map_err(|_| anyhow!("El estado ya fue inicializado"))?;
         Ok(())
     }
 
-    async fn create_server(&self) -> Result<()> {
+    fn routes(&self, routes: &mut Routes) {
+        controllers::register(routes);
+    }
+
+    fn services(&self, services: &mut Services) {
+        // Limpieza de IPs expiradas y recarga en caliente con SIGHUP o al cambiar el archivo de configuración
+        services.register(self.state().rate_limiter.clone());
+        config::register_watchers(services);
+        // Informes de rebote y queja que el MTA deja en el buzón
+        if let Some(dir) = Config::get_bounce_mailbox_dir() {
+            let suppressions = self.state().email_sender.suppressions().clone();
+            services.register(BounceMailbox::new(dir, Config::get_bounce_poll_interval(), suppressions));
+        }
+    }
+
+    fn create_server(&self, listener: TcpListener, routes: Routes) -> Result<Server> {
         info!("Starting the server...");
 
-        let state = APP_STATE.get().expect("App state not initialized").clone();
+        let state = self.state().clone();
+        let cors_settings = CorsSettings::from_config()?;
+        banner::log_startup(&state.email_sender, &cors_settings);
+        let strict = Config::get_mode().strict();
 
         let server = HttpServer::new(move || {
             App::new()
                 // Inyectar el estado con cosas como el cliente SMTP
                 .app_data(state.clone())
-                .wrap(Logger::default())
-
-                .service(
-                    web::scope("/api/v1")
-                        .service(controllers::email::routes())
-                )
+                .wrap(from_fn(i18n::negotiate_locale))
+                .wrap(from_fn(metrics::track_http))
+                .wrap(from_fn(request_id::assign_request_id))
+                .wrap(cors_settings.build_live())
+                .wrap(Condition::new(strict, security_headers::secure_headers()))
+                // Errores de extracción con el mismo cuerpo JSON que el resto de la API
+                .app_data(error::json_config())
+                .app_data(error::query_config())
+                .app_data(error::path_config())
+                .configure(|cfg| routes.configure(cfg))
+                .default_service(web::to(error::route_not_found))
         });
 
-        info!("Listening on http://{}", Config::get_addrs());
-        server.bind(Config::get_addrs())?.run().await?;
-        Ok(())
+        // Las señales las gestiona `Application::start` para que SIGTERM y Ctrl-C siempre hagan un apagado ordenado
+        Ok(server
+            .shutdown_timeout(Config::get_shutdown_timeout().as_secs())
+            .disable_signals()
+            .listen(listener)?
+            .run())
     }
 }
 
 #[actix_web::main]
 async fn main() -> Result<()> {
-    AppServer.start().await
-}
\ No newline at end of file
+    let cli = cli::Cli::parse();
+    if let Some(command) = &cli.command {
+        return cli::run(command, &cli);
+    }
+
+    AppServer::new(cli).start().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testing::TestServer;
+
+    #[actix_web::test]
+    async fn serves_the_application_routes() {
+        let server = TestServer::start(AppServer::new(cli::Cli::parse_from(["app"]))).await.unwrap();
+
+        let (status, body) = server.get("/health/live").await.unwrap();
+        assert_eq!(status, 200);
+        assert!(body.contains(r#""status":"up""#), "{}", body);
+        assert_eq!(server.get("/metrics").await.unwrap().0, 200);
+        // Las rutas de la API necesitan credenciales y las desconocidas responden con el error JSON común
+        assert_eq!(server.get("/api/v1/admin/suppressions").await.unwrap().0, 401);
+        let (status, body) = server.get("/api/v1/nope").await.unwrap();
+        assert_eq!(status, 404);
+        assert!(body.contains(r#""code":"not_found""#), "{}", body);
+
+        let app = server.stop().await.unwrap();
+        assert!(app.lifecycle.token().is_cancelled());
+        assert_eq!(app.state().email_sender.transport_name(), "capture");
+    }
+}
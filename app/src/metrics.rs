@@ -0,0 +1,164 @@
+use std::time::{Duration, Instant};
+
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::web;
+use anyhow::Result;
+use prometheus::core::Collector;
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, Opts, Registry, TextEncoder,
+};
+
+use crate::app_state::AppState;
+
+/// Prometheus collectors shared by the whole application.
+/// Subsystems add their own collectors with [`Metrics::register`] and keep a clone to update them.
+pub struct Metrics {
+    registry: Registry,
+    pub http_requests_total: IntCounterVec,
+    pub http_request_duration_seconds: HistogramVec,
+    pub emails_sent_total: IntCounterVec,
+    pub emails_failed_total: IntCounterVec,
+    pub email_send_duration_seconds: HistogramVec,
+    pub rate_limit_rejections_total: IntCounterVec,
+    pub rate_limiter_tracked_ips: IntGauge,
+}
+
+impl Metrics {
+    pub fn new() -> Result<Self> {
+        let registry = Registry::new();
+
+        let http_requests_total = IntCounterVec::new(
+            Opts::new("http_requests_total", "HTTP requests handled, by route and status"),
+            &["method", "route", "status"],
+        )?;
+        let http_request_duration_seconds = HistogramVec::new(
+            HistogramOpts::new("http_request_duration_seconds", "HTTP request latency, by route"),
+            &["method", "route"],
+        )?;
+        let emails_sent_total = IntCounterVec::new(
+            Opts::new("emails_sent_total", "Email sends that succeeded, by transport"),
+            &["transport"],
+        )?;
+        let emails_failed_total = IntCounterVec::new(
+            Opts::new("emails_failed_total", "Email sends that failed, by transport"),
+            &["transport"],
+        )?;
+        let email_send_duration_seconds = HistogramVec::new(
+            HistogramOpts::new("email_send_duration_seconds", "Time spent handing mail to the transport")
+                .buckets(vec![0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0]),
+            &["transport"],
+        )?;
+        let rate_limit_rejections_total = IntCounterVec::new(
+            Opts::new("rate_limit_rejections_total", "Requests rejected by the rate limiter, by window"),
+            &["window"],
+        )?;
+        let rate_limiter_tracked_ips = IntGauge::new(
+            "rate_limiter_tracked_ips",
+            "Client IPs currently tracked by the rate limiter",
+        )?;
+
+        registry.register(Box::new(http_requests_total.clone()))?;
+        registry.register(Box::new(http_request_duration_seconds.clone()))?;
+        registry.register(Box::new(emails_sent_total.clone()))?;
+        registry.register(Box::new(emails_failed_total.clone()))?;
+        registry.register(Box::new(email_send_duration_seconds.clone()))?;
+        registry.register(Box::new(rate_limit_rejections_total.clone()))?;
+        registry.register(Box::new(rate_limiter_tracked_ips.clone()))?;
+
+        Ok(Self {
+            registry,
+            http_requests_total,
+            http_request_duration_seconds,
+            emails_sent_total,
+            emails_failed_total,
+            email_send_duration_seconds,
+            rate_limit_rejections_total,
+            rate_limiter_tracked_ips,
+        })
+    }
+
+    /// Adds a collector to the ones rendered by `/metrics`; fails if its name is already taken
+    pub fn register(&self, collector: Box<dyn Collector>) -> Result<()> {
+        Ok(self.registry.register(collector)?)
+    }
+
+    /// Records the outcome of one send through `transport`
+    pub fn observe_email(&self, transport: &str, elapsed: Duration, success: bool) {
+        self.email_send_duration_seconds
+            .with_label_values(&[transport])
+            .observe(elapsed.as_secs_f64());
+
+        let counter = if success {
+            &self.emails_sent_total
+        } else {
+            &self.emails_failed_total
+        };
+        counter.with_label_values(&[transport]).inc();
+    }
+
+    /// Encodes every registered collector in the Prometheus text format
+    pub fn render(&self) -> Result<String> {
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        Ok(String::from_utf8(buffer)?)
+    }
+}
+
+/// Middleware that counts requests and observes their latency per matched route
+pub async fn track_http(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let state = req.app_data::<web::Data<AppState>>().cloned();
+    let method = req.method().to_string();
+    let started = Instant::now();
+
+    let res = next.call(req).await?;
+
+    if let Some(state) = state {
+        // Usar el patrón de la ruta evita una serie temporal por cada id
+        let route = res.request().match_pattern().unwrap_or_else(|| "unmatched".to_string());
+        let status = res.status().as_u16().to_string();
+
+        state
+            .metrics
+            .http_requests_total
+            .with_label_values(&[&method, &route, &status])
+            .inc();
+        state
+            .metrics
+            .http_request_duration_seconds
+            .with_label_values(&[&method, &route])
+            .observe(started.elapsed().as_secs_f64());
+    }
+
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_registered_collectors() {
+        let metrics = Metrics::new().unwrap();
+        metrics.observe_email("smtp", Duration::from_millis(120), true);
+        metrics.observe_email("smtp", Duration::from_millis(80), false);
+        metrics.rate_limit_rejections_total.with_label_values(&["1m"]).inc();
+        metrics.rate_limiter_tracked_ips.set(3);
+
+        let extra = IntGauge::new("extra_gauge", "Registered by a subsystem").unwrap();
+        metrics.register(Box::new(extra.clone())).unwrap();
+        assert!(metrics.register(Box::new(extra.clone())).is_err());
+        extra.set(7);
+
+        let output = metrics.render().unwrap();
+        assert!(output.contains("emails_sent_total{transport=\"smtp\"} 1"));
+        assert!(output.contains("emails_failed_total{transport=\"smtp\"} 1"));
+        assert!(output.contains("rate_limit_rejections_total{window=\"1m\"} 1"));
+        assert!(output.contains("rate_limiter_tracked_ips 3"));
+        assert!(output.contains("extra_gauge 7"));
+    }
+}
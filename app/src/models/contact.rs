@@ -0,0 +1,70 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::{IntoParams, ToSchema};
+
+/// Triage state of a stored contact submission
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ContactStatus {
+    #[default]
+    New,
+    Handled,
+    Spam,
+}
+
+/// Internal note attached to a submission by the sales team
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct ContactNote {
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Contact request as kept server-side, with triage metadata
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct ContactSubmission {
+    pub id: u64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub ip: String,
+    pub name: String,
+    pub company: String,
+    pub email: String,
+    pub service: String,
+    pub message: String,
+    pub status: ContactStatus,
+    pub notes: Vec<ContactNote>,
+    /// Configured form the submission came from; the contact form if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub form: Option<String>,
+    /// Every value of a configured form, including those copied to the fields above
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, Value>,
+}
+
+/// Query parameters accepted by the admin listing endpoint
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ContactFilter {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub service: Option<String>,
+    pub status: Option<ContactStatus>,
+    /// Form id; `contact` selects the contact form
+    pub form: Option<String>,
+    pub q: Option<String>,
+    pub page: Option<usize>,
+    pub per_page: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateStatusRequest {
+    pub status: ContactStatus,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AddNoteRequest {
+    pub body: String,
+}
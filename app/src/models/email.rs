@@ -1,10 +1,14 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
 pub struct ContactRequest {
     pub name: String,
     pub company: String,
     pub email: String,
     pub service: String,
     pub message: String,
+    /// Idioma de las respuestas y del acuse de recibo (`es`, `en`); si falta se usa `Accept-Language`
+    #[serde(default)]
+    pub locale: Option<String>,
 }
\ No newline at end of file
@@ -1 +1,2 @@
+pub mod contact;
 pub mod email;
\ No newline at end of file
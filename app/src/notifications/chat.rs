@@ -0,0 +1,232 @@
+use serde_json::{json, Value};
+
+use super::{Notification, NotificationChannel, NotifyError, NotifyFuture};
+
+/// Discord rejects embed field values longer than this
+const DISCORD_FIELD_LIMIT: usize = 1024;
+/// Discord limits for field names, the embed title, the number of fields and the whole embed
+const DISCORD_NAME_LIMIT: usize = 256;
+const DISCORD_FIELDS_PER_EMBED: usize = 25;
+const DISCORD_EMBED_LIMIT: usize = 6000;
+/// Slack allows at most this many fields per section block
+const SLACK_FIELDS_PER_SECTION: usize = 10;
+/// Slack rejects section fields longer than this, header texts longer than the second and more blocks than the third
+const SLACK_FIELD_LIMIT: usize = 2000;
+const SLACK_HEADER_LIMIT: usize = 150;
+const SLACK_BLOCK_LIMIT: usize = 50;
+
+/// Message format of the incoming webhook
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChatFormat {
+    Slack,
+    Discord,
+    Teams,
+}
+
+/// Message posted to a Slack, Discord or Microsoft Teams incoming webhook
+pub struct ChatChannel {
+    format: ChatFormat,
+    url: String,
+    client: reqwest::Client,
+}
+
+impl ChatChannel {
+    pub fn new(format: ChatFormat, url: &str, client: reqwest::Client) -> Self {
+        Self {
+            format,
+            url: url.to_string(),
+            client,
+        }
+    }
+
+    fn payload(&self, notification: &Notification) -> Value {
+        match self.format {
+            ChatFormat::Slack => slack(notification),
+            ChatFormat::Discord => discord(notification),
+            ChatFormat::Teams => teams(notification),
+        }
+    }
+}
+
+impl NotificationChannel for ChatChannel {
+    fn name(&self) -> &'static str {
+        match self.format {
+            ChatFormat::Slack => "slack",
+            ChatFormat::Discord => "discord",
+            ChatFormat::Teams => "teams",
+        }
+    }
+
+    fn notify<'a>(&'a self, notification: &'a Notification) -> NotifyFuture<'a> {
+        Box::pin(async move {
+            let response = self
+                .client
+                .post(&self.url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(self.payload(notification).to_string())
+                .send()
+                .await
+                .map_err(|e| NotifyError::Http(e.without_url().to_string()))?;
+
+            match response.status() {
+                status if status.is_success() => Ok(()),
+                status => Err(NotifyError::Http(format!("HTTP {}", status))),
+            }
+        })
+    }
+}
+
+/// `text` cut to at most `max` characters, ending in an ellipsis when cut
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    text.chars().take(max.saturating_sub(1)).chain(['…']).collect()
+}
+
+/// Escapes the control characters of Slack mrkdwn, so submitted values can't mention
+/// `<!channel>` or `<!here>` or add `<url|links>`
+fn slack_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Escaped `*label*` and value within Slack's field limit, without cutting an entity in half
+fn slack_field(label: &str, value: &str) -> String {
+    let text = format!("*{}*\n{}", slack_escape(label), slack_escape(value));
+    let mut cut = truncate(&text, SLACK_FIELD_LIMIT);
+    if cut != text {
+        cut.pop();
+        if let Some(amp) = cut.rfind('&').filter(|&amp| !cut[amp..].contains(';')) {
+            cut.truncate(amp);
+        }
+        cut.push('…');
+    }
+    cut
+}
+
+/// Block Kit: a header and the fields in sections of at most ten
+fn slack(notification: &Notification) -> Value {
+    let mut blocks = vec![json!({
+        "type": "header",
+        "text": { "type": "plain_text", "text": truncate(&notification.title, SLACK_HEADER_LIMIT) },
+    })];
+    for chunk in notification.fields.chunks(SLACK_FIELDS_PER_SECTION).take(SLACK_BLOCK_LIMIT - 1) {
+        let fields: Vec<Value> = chunk
+            .iter()
+            .map(|(label, value)| json!({ "type": "mrkdwn", "text": slack_field(label, value) }))
+            .collect();
+        blocks.push(json!({ "type": "section", "fields": fields }));
+    }
+    json!({ "text": slack_escape(&notification.title), "blocks": blocks })
+}
+
+/// One embed with a field per value, within the field count and total size limits of an embed
+fn discord(notification: &Notification) -> Value {
+    let title = truncate(&notification.title, DISCORD_NAME_LIMIT);
+    let mut budget = DISCORD_EMBED_LIMIT - title.chars().count();
+    let mut fields = Vec::new();
+    for (label, value) in notification.fields.iter().take(DISCORD_FIELDS_PER_EMBED) {
+        let name = truncate(label, DISCORD_NAME_LIMIT);
+        let room = budget.saturating_sub(name.chars().count()).min(DISCORD_FIELD_LIMIT);
+        if room == 0 {
+            break;
+        }
+        let value = match value.as_str() {
+            "" => "-".to_string(),
+            value => truncate(value, room),
+        };
+        budget -= name.chars().count() + value.chars().count();
+        fields.push(json!({ "name": name, "value": value, "inline": false }));
+    }
+    json!({ "embeds": [{ "title": title, "fields": fields }] })
+}
+
+/// Legacy MessageCard, the format accepted by Teams incoming webhooks
+fn teams(notification: &Notification) -> Value {
+    let facts: Vec<Value> = notification
+        .fields
+        .iter()
+        .map(|(label, value)| json!({ "name": label, "value": value }))
+        .collect();
+    json!({
+        "@type": "MessageCard",
+        "@context": "https://schema.org/extensions",
+        "summary": notification.title,
+        "title": notification.title,
+        "sections": [{ "facts": facts }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_each_chat_format() {
+        let notification = Notification {
+            title: "Nueva solicitud de contacto".to_string(),
+            html: String::new(),
+            fields: vec![
+                ("Nombre".to_string(), "Ana".to_string()),
+                ("Mensaje".to_string(), "x".repeat(2000)),
+            ],
+            recipients: Vec::new(),
+        };
+        let channel = |format| ChatChannel::new(format, "http://localhost", reqwest::Client::new());
+
+        let slack = channel(ChatFormat::Slack).payload(&notification);
+        assert_eq!(slack["text"], "Nueva solicitud de contacto");
+        assert_eq!(slack["blocks"][1]["fields"][0]["text"], "*Nombre*\nAna");
+
+        let discord = channel(ChatFormat::Discord).payload(&notification);
+        assert_eq!(discord["embeds"][0]["fields"][0]["name"], "Nombre");
+        assert_eq!(discord["embeds"][0]["fields"][1]["value"].as_str().unwrap().chars().count(), DISCORD_FIELD_LIMIT);
+
+        let teams = channel(ChatFormat::Teams).payload(&notification);
+        assert_eq!(teams["@type"], "MessageCard");
+        assert_eq!(teams["sections"][0]["facts"][0]["value"], "Ana");
+    }
+
+    #[test]
+    fn escapes_slack_mrkdwn_and_keeps_fields_within_limits() {
+        let notification = Notification {
+            title: "Nueva solicitud".to_string(),
+            html: String::new(),
+            fields: vec![
+                ("Mensaje".to_string(), "<!channel> mira <https://evil.example|aquí> & <b>".to_string()),
+                ("Largo".to_string(), "<".repeat(1000)),
+            ],
+            recipients: Vec::new(),
+        };
+        let slack = slack(&notification);
+        let fields = &slack["blocks"][1]["fields"];
+        assert_eq!(fields[0]["text"], "*Mensaje*\n&lt;!channel&gt; mira &lt;https://evil.example|aquí&gt; &amp; &lt;b&gt;");
+
+        let long = fields[1]["text"].as_str().unwrap();
+        assert!(long.chars().count() <= SLACK_FIELD_LIMIT);
+        assert!(long.ends_with("&lt;…"));
+    }
+
+    #[test]
+    fn caps_discord_embeds() {
+        let notification = Notification {
+            title: "Nuevo envío".to_string(),
+            html: String::new(),
+            fields: (0..40).map(|i| (format!("campo{}", i), "x".repeat(1000))).collect(),
+            recipients: Vec::new(),
+        };
+        let discord = discord(&notification);
+        let embed = &discord["embeds"][0];
+        let fields = embed["fields"].as_array().unwrap();
+        assert!(fields.len() <= DISCORD_FIELDS_PER_EMBED);
+
+        let size: usize = embed["title"].as_str().unwrap().chars().count()
+            + fields
+                .iter()
+                .map(|f| f["name"].as_str().unwrap().chars().count() + f["value"].as_str().unwrap().chars().count())
+                .sum::<usize>();
+        assert!(size <= DISCORD_EMBED_LIMIT);
+        assert_eq!(fields.len(), 6);
+        assert!(fields[5]["value"].as_str().unwrap().ends_with('…'));
+    }
+}
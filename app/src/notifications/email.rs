@@ -0,0 +1,29 @@
+use email_sender::EmailSender;
+
+use super::{Notification, NotificationChannel, NotifyError, NotifyFuture};
+
+/// HTML email to the notification recipients
+pub struct EmailChannel {
+    sender: EmailSender,
+}
+
+impl EmailChannel {
+    pub fn new(sender: EmailSender) -> Self {
+        Self { sender }
+    }
+}
+
+impl NotificationChannel for EmailChannel {
+    fn name(&self) -> &'static str {
+        "email"
+    }
+
+    fn notify<'a>(&'a self, notification: &'a Notification) -> NotifyFuture<'a> {
+        Box::pin(async move {
+            self.sender
+                .send_html_email(&notification.recipients, &notification.title, &notification.html)
+                .await
+                .map_err(NotifyError::Mail)
+        })
+    }
+}
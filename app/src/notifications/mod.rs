@@ -0,0 +1,276 @@
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use email_sender::{EmailError, EmailSender};
+use futures_util::future::join_all;
+use prometheus::{IntCounterVec, Opts};
+use serde::{Deserialize, Deserializer};
+use tracing::warn;
+
+use crate::app_state::AppState;
+use crate::config::Secret;
+use crate::error::ApiError;
+use crate::i18n::Message;
+use crate::metrics::Metrics;
+
+mod chat;
+mod email;
+
+pub use chat::{ChatChannel, ChatFormat};
+pub use email::EmailChannel;
+
+/// Maximum time a chat webhook may take to answer
+const CHAT_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub type NotifyFuture<'a> = Pin<Box<dyn Future<Output = Result<(), NotifyError>> + Send + 'a>>;
+
+/// Somewhere the admins are told about a new submission
+pub trait NotificationChannel: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn notify<'a>(&'a self, notification: &'a Notification) -> NotifyFuture<'a>;
+}
+
+/// A new submission, rendered for every kind of channel
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub title: String,
+    /// Body of the email channel
+    pub html: String,
+    /// `(label, value)` pairs shown by the chat channels
+    pub fields: Vec<(String, String)>,
+    /// Addresses of the email channel
+    pub recipients: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum NotifyError {
+    Mail(EmailError),
+    Http(String),
+}
+
+impl fmt::Display for NotifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NotifyError::Mail(e) => write!(f, "{}", e),
+            NotifyError::Http(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for NotifyError {}
+
+/// Channel as written in the configuration: `email`, or `slack:`, `discord:` or `teams:`
+/// followed by the incoming-webhook URL. The URL is kept as a [`Secret`] since it grants posting rights.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChannelSpec {
+    Email,
+    Chat(ChatFormat, Secret),
+}
+
+impl FromStr for ChannelSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("email") {
+            return Ok(ChannelSpec::Email);
+        }
+
+        let (kind, url) = s
+            .split_once(':')
+            .ok_or_else(|| format!("canal desconocido '{}' (email, slack:<url>, discord:<url> o teams:<url>)", s))?;
+        let format = match kind.to_ascii_lowercase().as_str() {
+            "slack" => ChatFormat::Slack,
+            "discord" => ChatFormat::Discord,
+            "teams" => ChatFormat::Teams,
+            other => return Err(format!("canal desconocido '{}' (email, slack, discord o teams)", other)),
+        };
+        match reqwest::Url::parse(url) {
+            Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => Ok(ChannelSpec::Chat(format, Secret::from(url))),
+            _ => Err(format!("URL inválida para el canal {}", kind)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ChannelSpec {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Result of one channel
+pub struct Outcome {
+    pub channel: &'static str,
+    pub elapsed: Duration,
+    pub result: Result<(), NotifyError>,
+}
+
+/// Builds the channels of a form and sends notifications through them
+#[derive(Clone)]
+pub struct Notifier {
+    email_sender: EmailSender,
+    client: reqwest::Client,
+    /// Notifications per channel and status, exported as `notifications_total`
+    sent_total: IntCounterVec,
+}
+
+impl Notifier {
+    pub fn new(email_sender: EmailSender, metrics: &Metrics) -> anyhow::Result<Self> {
+        let sent_total = IntCounterVec::new(
+            Opts::new("notifications_total", "Submission notifications, by channel and status"),
+            &["channel", "status"],
+        )?;
+        metrics.register(Box::new(sent_total.clone()))?;
+
+        Ok(Self {
+            email_sender,
+            client: reqwest::Client::builder().timeout(CHAT_TIMEOUT).build()?,
+            sent_total,
+        })
+    }
+
+    pub fn channels(&self, specs: &[ChannelSpec]) -> Vec<Box<dyn NotificationChannel>> {
+        specs
+            .iter()
+            .map(|spec| -> Box<dyn NotificationChannel> {
+                match spec {
+                    ChannelSpec::Email => Box::new(EmailChannel::new(self.email_sender.clone())),
+                    ChannelSpec::Chat(format, url) => {
+                        Box::new(ChatChannel::new(*format, url.expose(), self.client.clone()))
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+/// Sends `notification` through every channel at once; a failing channel does not stop the others
+pub async fn fan_out(channels: &[Box<dyn NotificationChannel>], notification: &Notification) -> Vec<Outcome> {
+    join_all(channels.iter().map(|channel| async move {
+        let started = Instant::now();
+        let result = channel.notify(notification).await;
+        if let Err(e) = &result {
+            warn!(channel = channel.name(), error = %e, "No se pudo enviar la notificación");
+        }
+        Outcome {
+            channel: channel.name(),
+            elapsed: started.elapsed(),
+            result,
+        }
+    }))
+    .await
+}
+
+/// Notifies the admins through `specs` and records the metrics of every channel.
+/// Runs as a shutdown-tracked task, so it completes even if the client goes away.
+/// Fails only when no channel delivered it, with the mail error if the email channel was one of them.
+pub async fn deliver(app_state: &AppState, specs: &[ChannelSpec], notification: Notification) -> Result<(), ApiError> {
+    let channels = app_state.notifier.channels(specs);
+    let outcomes = app_state
+        .shutdown
+        .spawn(async move { fan_out(&channels, &notification).await })
+        .await
+        .map_err(|e| ApiError::Internal(anyhow::anyhow!("La tarea de notificación falló: {}", e)))?;
+
+    let transport = app_state.email_sender.transport_name();
+    for outcome in &outcomes {
+        if outcome.channel == "email" {
+            app_state.metrics.observe_email(transport, outcome.elapsed, outcome.result.is_ok());
+        }
+        let status = if outcome.result.is_ok() { "sent" } else { "failed" };
+        app_state.notifier.sent_total.with_label_values(&[outcome.channel, status]).inc();
+    }
+
+    if outcomes.is_empty() || outcomes.iter().any(|o| o.result.is_ok()) {
+        return Ok(());
+    }
+    let mut errors = outcomes.into_iter().filter_map(|o| o.result.err());
+    match errors.find(|e| matches!(e, NotifyError::Mail(_))) {
+        Some(NotifyError::Mail(e)) => Err(ApiError::Mail(e)),
+        _ => Err(ApiError::ServiceUnavailable(Message::new("error.notification_failed"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    struct Flaky {
+        fail: bool,
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl NotificationChannel for Flaky {
+        fn name(&self) -> &'static str {
+            if self.fail { "broken" } else { "working" }
+        }
+
+        fn notify<'a>(&'a self, _notification: &'a Notification) -> NotifyFuture<'a> {
+            Box::pin(async move {
+                self.calls.fetch_add(1, Ordering::SeqCst);
+                if self.fail {
+                    Err(NotifyError::Http("HTTP 500".to_string()))
+                } else {
+                    Ok(())
+                }
+            })
+        }
+    }
+
+    #[test]
+    fn parses_channel_specs() {
+        assert_eq!("email".parse::<ChannelSpec>(), Ok(ChannelSpec::Email));
+        assert_eq!(
+            "slack:https://hooks.slack.com/services/T/B/x".parse::<ChannelSpec>(),
+            Ok(ChannelSpec::Chat(ChatFormat::Slack, Secret::from("https://hooks.slack.com/services/T/B/x")))
+        );
+        assert!("teams:no-es-una-url".parse::<ChannelSpec>().is_err());
+        assert!("sms:+34600000000".parse::<ChannelSpec>().is_err());
+        // La URL del webhook no aparece en los logs
+        assert!(!format!("{:?}", "discord:https://discord.com/api/webhooks/1/abc".parse::<ChannelSpec>()).contains("abc"));
+    }
+
+    #[actix_web::test]
+    async fn one_failing_channel_does_not_stop_the_others() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let channels: Vec<Box<dyn NotificationChannel>> = vec![
+            Box::new(Flaky { fail: true, calls: calls.clone() }),
+            Box::new(Flaky { fail: false, calls: calls.clone() }),
+        ];
+        let notification = Notification {
+            title: "Nueva solicitud".to_string(),
+            html: String::new(),
+            fields: Vec::new(),
+            recipients: Vec::new(),
+        };
+
+        let outcomes = fan_out(&channels, &notification).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        let results: Vec<_> = outcomes.iter().map(|o| (o.channel, o.result.is_ok())).collect();
+        assert_eq!(results, [("broken", false), ("working", true)]);
+    }
+
+    #[actix_web::test]
+    async fn counts_deliveries_per_channel() {
+        let state = crate::testing::app_state().await;
+        let notification = Notification {
+            title: "Nueva solicitud".to_string(),
+            html: "<p>Hola</p>".to_string(),
+            fields: Vec::new(),
+            recipients: vec!["admin@example.com".to_string()],
+        };
+
+        deliver(&state, &[ChannelSpec::Email], notification).await.unwrap();
+        let output = state.metrics.render().unwrap();
+        assert!(output.contains("notifications_total{channel=\"email\",status=\"sent\"} 1"), "{}", output);
+        assert!(output.contains("emails_sent_total{transport=\"capture\"} 1"), "{}", output);
+    }
+}
@@ -0,0 +1,202 @@
+use actix_web::http::header;
+use actix_web::{web, HttpResponse, Scope};
+use crate::auth::API_KEY_HEADER;
+use crate::common::ApplicationConfig;
+use crate::config::Config;
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::openapi::OpenApi as Document;
+use utoipa::{Modify, OpenApi};
+
+/// Security scheme of the endpoints that take a JWT from `POST /api/v1/auth/token`
+pub const BEARER: &str = "bearer";
+/// Security scheme of `POST /api/v1/auth/token`
+pub const API_KEY: &str = "api_key";
+
+const SWAGGER_UI: &str = "https://unpkg.com/swagger-ui-dist@5";
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "base-server", description = "Formulario de contacto y API de administración"),
+    modifiers(&SecuritySchemes)
+)]
+struct BaseDoc;
+
+struct SecuritySchemes;
+
+impl Modify for SecuritySchemes {
+    fn modify(&self, openapi: &mut Document) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            BEARER,
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).bearer_format("JWT").build()),
+        );
+        components.add_security_scheme(
+            API_KEY,
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new(API_KEY_HEADER))),
+        );
+    }
+}
+
+/// Document the controllers are nested into: metadata and security schemes
+pub fn base() -> Document {
+    BaseDoc::openapi()
+}
+
+/// Adds `/openapi.json` to the API scope and, in dev mode, the Swagger UI at `/docs`
+pub fn configure(api: Scope, doc: Document) -> Scope {
+    let api = api.service(
+        web::resource("/openapi.json")
+            .app_data(web::Data::new(doc))
+            .route(web::get().to(spec)),
+    );
+
+    if Config::get_mode().api_docs() {
+        api.route("/docs", web::get().to(docs))
+    } else {
+        api
+    }
+}
+
+async fn spec(doc: web::Data<Document>) -> HttpResponse {
+    HttpResponse::Ok().json(doc.get_ref())
+}
+
+async fn docs() -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        // La página carga Swagger UI desde la CDN; el resto de la API mantiene la CSP cerrada
+        .insert_header((
+            header::CONTENT_SECURITY_POLICY,
+            format!("default-src 'none'; script-src {0} 'unsafe-inline'; style-src {0}; img-src {0} data:; connect-src 'self'", SWAGGER_UI),
+        ))
+        .body(format!(
+            r##"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="utf-8">
+    <title>base-server API</title>
+    <link rel="stylesheet" href="{0}/swagger-ui.css">
+</head>
+<body>
+    <div id="swagger-ui"></div>
+    <script src="{0}/swagger-ui-bundle.js"></script>
+    <script>SwaggerUIBundle({{ url: "openapi.json", dom_id: "#swagger-ui" }});</script>
+</body>
+</html>"##,
+            SWAGGER_UI
+        ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::Routes;
+    use crate::testing::TestServer;
+    use crate::{cli, controllers, AppServer};
+    use actix_web::http::{Method, StatusCode};
+    use actix_web::{test, App};
+    use clap::Parser;
+    use regex::Regex;
+
+    /// Routes served outside the document: the document itself and its UI
+    const UNDOCUMENTED: [(Method, &str); 2] = [(Method::GET, "/api/v1/openapi.json"), (Method::GET, "/api/v1/docs")];
+
+    fn operations(doc: &Document) -> Vec<(Method, String)> {
+        let mut operations = Vec::new();
+        for (path, item) in &doc.paths.paths {
+            let methods = [
+                (Method::GET, &item.get),
+                (Method::POST, &item.post),
+                (Method::PUT, &item.put),
+                (Method::PATCH, &item.patch),
+                (Method::DELETE, &item.delete),
+            ];
+            for (method, operation) in methods {
+                if operation.is_some() {
+                    operations.push((method, path.clone()));
+                }
+            }
+        }
+        operations
+    }
+
+    /// `(method, route)` of every request counted by `http_requests_total`,
+    /// where the route is the pattern the request matched or `unmatched`
+    fn counted(metrics: &str) -> Vec<(Method, String)> {
+        let line = Regex::new(r#"^http_requests_total\{method="(\w+)",route="([^"]*)""#).unwrap();
+        metrics
+            .lines()
+            .filter_map(|l| line.captures(l))
+            .map(|c| (c[1].parse().unwrap(), c[2].to_string()))
+            .collect()
+    }
+
+    /// `path` has the shape of `template`, a documented path with `{param}` segments
+    fn matches(template: &str, path: &str) -> bool {
+        let (template, path): (Vec<_>, Vec<_>) = (template.split('/').collect(), path.split('/').collect());
+        template.len() == path.len()
+            && template.iter().zip(&path).all(|(t, p)| t == p || (t.starts_with('{') && !p.is_empty()))
+    }
+
+    /// Sends every method to every known path of the application built by `create_server`. Documented operations
+    /// must reach the route of the document, as its metrics show, and every other request must go unanswered.
+    /// Other paths can't be served undocumented: `controller!` builds the scope and the document from the same handlers.
+    #[actix_web::test]
+    async fn spec_matches_the_routes_served() {
+        let mut routes = Routes::default();
+        controllers::register(&mut routes);
+        let documented = operations(&routes.openapi());
+        assert!(!documented.is_empty());
+        let known = |method: &Method, path: &str| {
+            let documented = documented.iter().map(|(m, p)| (m, p.as_str()));
+            let undocumented = UNDOCUMENTED.iter().map(|(m, p)| (m, *p));
+            documented.chain(undocumented).any(|(m, template)| m == method && matches(template, path))
+        };
+
+        // Sin credenciales ni cuerpo los handlers responden con error; solo importa que alguno responda.
+        // Una petición que ningún handler atiende acaba en 404 o 405, según el recurso
+        let server = TestServer::start(AppServer::new(cli::Cli::parse_from(["app"]))).await.unwrap();
+        let paths = documented.iter().map(|(_, path)| path.as_str()).chain(UNDOCUMENTED.iter().map(|(_, path)| *path));
+        for path in paths.collect::<std::collections::BTreeSet<_>>() {
+            let uri = path
+                .replace("{id}", "1")
+                .replace("{form_id}", "quote")
+                .replace("{address}", "ana@example.com");
+            for method in [Method::GET, Method::POST, Method::PUT, Method::PATCH, Method::DELETE] {
+                let (status, _) = server.request(method.as_str(), &uri).await.unwrap();
+                let answered = ![StatusCode::NOT_FOUND, StatusCode::METHOD_NOT_ALLOWED].contains(&status.try_into().unwrap());
+                assert_eq!(answered, known(&method, &uri), "{} {} ({})", method, uri, status);
+            }
+        }
+
+        let (_, metrics) = server.get("/metrics").await.unwrap();
+        let reached = counted(&metrics);
+        for (method, path) in &documented {
+            assert!(
+                reached.iter().any(|(m, route)| m == method && route == path),
+                "{} {} está documentado pero la petición llegó a otra ruta", method, path
+            );
+        }
+
+        server.stop().await.unwrap();
+    }
+
+    #[actix_web::test]
+    async fn serves_the_document_and_the_ui_in_dev() {
+        let mut routes = Routes::default();
+        controllers::register(&mut routes);
+        let app = test::init_service(App::new().configure(|cfg| routes.configure(cfg))).await;
+
+        let req = test::TestRequest::get().uri("/api/v1/openapi.json").to_request();
+        let doc: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert!(doc["paths"]["/api/v1/contact"]["post"].is_object());
+        assert!(doc["paths"]["/health/ready"]["get"].is_object());
+        assert!(doc["components"]["schemas"]["ContactRequest"].is_object());
+        assert!(doc["components"]["securitySchemes"][BEARER].is_object());
+
+        let req = test::TestRequest::get().uri("/api/v1/docs").to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(res.headers().contains_key(header::CONTENT_SECURITY_POLICY));
+    }
+}
@@ -3,6 +3,9 @@ use std::sync::{Mutex, Arc};
 use std::time::Duration as StdDuration;
 use chrono::{DateTime, Utc, Duration};
 use tokio::time::interval;
+use tokio_util::sync::CancellationToken;
+use crate::config::Config;
+use crate::services::{BackgroundService, ServiceFuture};
 
 #[derive(Debug, Clone)]
 pub struct RateLimitInfo {
@@ -18,8 +21,10 @@ impl RateLimitInfo {
         }
     }
 
-    pub fn can_make_request(&mut self) -> bool {
+    /// Registra la petición si cabe en los límites `(por minuto, por 12 horas)`
+    pub fn can_make_request(&mut self, limits: (usize, usize)) -> bool {
         let now = Utc::now();
+        let (per_minute, per_12h) = limits;
         
         // Limpiar requests antiguos (más de 1 minuto)
         self.requests_per_minute.retain(|&time| {
@@ -31,18 +36,13 @@ impl RateLimitInfo {
             now.signed_duration_since(time) < Duration::hours(12)
         });
 
-        // Si no hay requests recientes, la IP puede hacer peticiones y se libera memoria
-        if self.requests_per_minute.is_empty() && self.requests_per_12h.is_empty() {
-            return true;
-        }
-
-        // Verificar límite por minuto (máximo 2)
-        if self.requests_per_minute.len() >= 2 {
+        // Verificar límite por minuto
+        if self.requests_per_minute.len() >= per_minute {
             return false;
         }
 
-        // Verificar límite por 12 horas (máximo 4)
-        if self.requests_per_12h.len() >= 4 {
+        // Verificar límite por 12 horas
+        if self.requests_per_12h.len() >= per_12h {
             return false;
         }
 
@@ -53,8 +53,9 @@ impl RateLimitInfo {
         true
     }
 
-    pub fn get_remaining_requests(&self) -> (usize, usize) {
+    pub fn get_remaining_requests(&self, limits: (usize, usize)) -> (usize, usize) {
         let now = Utc::now();
+        let (per_minute, per_12h) = limits;
         
         let minute_requests = self.requests_per_minute.iter()
             .filter(|&time| now.signed_duration_since(*time) < Duration::minutes(1))
@@ -64,7 +65,31 @@ impl RateLimitInfo {
             .filter(|&time| now.signed_duration_since(*time) < Duration::hours(12))
             .count();
 
-        (2 - minute_requests, 4 - hour_12_requests)
+        // Los límites se pueden bajar en caliente por debajo de lo ya consumido
+        (per_minute.saturating_sub(minute_requests), per_12h.saturating_sub(hour_12_requests))
+    }
+
+    /// Tiempo hasta que las ventanas agotadas vuelvan a tener cupo para una petición
+    pub fn retry_after(&self, limits: (usize, usize)) -> Duration {
+        let now = Utc::now();
+        let (per_minute, per_12h) = limits;
+        let wait = |requests: &[DateTime<Utc>], limit: usize, window: Duration| {
+            let recent: Vec<&DateTime<Utc>> = requests
+                .iter()
+                .filter(|&&time| now.signed_duration_since(time) < window)
+                .collect();
+            if recent.len() < limit {
+                return Duration::zero();
+            }
+            // Tienen que caducar las peticiones que sobran más una; con límite 0 no caduca ninguna
+            match recent.get(recent.len() - limit) {
+                Some(&&time) => time + window - now,
+                None => window,
+            }
+        };
+
+        wait(&self.requests_per_minute, per_minute, Duration::minutes(1))
+            .max(wait(&self.requests_per_12h, per_12h, Duration::hours(12)))
     }
 }
 
@@ -81,20 +106,34 @@ impl RateLimiter {
     }
 
     pub fn check_rate_limit(&self, ip: &str) -> (bool, (usize, usize)) {
-        let mut requests = self.requests.lock().unwrap();
-        
-        let rate_info = requests.entry(ip.to_string()).or_insert_with(RateLimitInfo::new);
-        let can_request = rate_info.can_make_request();
-        let remaining = rate_info.get_remaining_requests();
+        // Los límites se leen en cada petición para que una recarga de configuración aplique al momento
+        self.check(ip, Config::get_rate_limits())
+    }
 
-        // Si la IP puede hacer peticiones (no hay requests recientes), eliminarla de memoria
-        if can_request && rate_info.requests_per_minute.is_empty() && rate_info.requests_per_12h.is_empty() {
-            requests.remove(ip);
-        }
+    /// Registra una petición de `key` (la IP, o formulario e IP) contra `limits` `(por minuto, por 12 horas)`
+    #[tracing::instrument(name = "rate_limit.check", skip(self))]
+    pub fn check(&self, key: &str, limits: (usize, usize)) -> (bool, (usize, usize)) {
+        let mut requests = self.requests.lock().unwrap();
+        let rate_info = requests.entry(key.to_string()).or_insert_with(RateLimitInfo::new);
+        let can_request = rate_info.can_make_request(limits);
+        let remaining = rate_info.get_remaining_requests(limits);
 
         (can_request, remaining)
     }
 
+    /// Segundos hasta que `key` pueda volver a hacer una petición con `limits`, para `Retry-After`
+    pub fn retry_after(&self, key: &str, limits: (usize, usize)) -> u64 {
+        let requests = self.requests.lock().unwrap();
+        let wait = requests.get(key).map_or(Duration::zero(), |info| info.retry_after(limits));
+        // Se redondea hacia arriba para no invitar a reintentar un instante antes de tiempo
+        (wait.num_milliseconds().max(0) as u64).div_ceil(1000)
+    }
+
+    /// Número de IPs con peticiones registradas actualmente
+    pub fn tracked_ips(&self) -> usize {
+        self.requests.lock().unwrap().len()
+    }
+
     /// Limpia automáticamente las IPs que ya pueden hacer peticiones
     pub fn cleanup_expired_ips(&self) {
         let now = Utc::now();
@@ -120,19 +159,22 @@ impl RateLimiter {
             requests.remove(&ip);
         }
     }
+}
 
-    /// Inicia la limpieza automática periódica
-    pub async fn start_auto_cleanup(&self) {
-        let rate_limiter = self.clone();
-        
-        tokio::spawn(async move {
+/// Limpieza periódica de IPs expiradas como servicio de fondo supervisado
+impl BackgroundService for RateLimiter {
+    fn name(&self) -> &'static str {
+        "rate_limiter_cleanup"
+    }
+
+    fn run(&self, shutdown: CancellationToken) -> ServiceFuture<'_> {
+        Box::pin(async move {
             let mut interval = interval(StdDuration::from_secs(86400)); // Limpiar cada 24 horas
-            
-            loop {
-                interval.tick().await;
-                rate_limiter.cleanup_expired_ips();
+            while shutdown.run_until_cancelled(interval.tick()).await.is_some() {
+                self.cleanup_expired_ips();
             }
-        });
+            Ok(())
+        })
     }
 }
 
@@ -141,3 +183,44 @@ impl Default for RateLimiter {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enforces_limits_from_the_first_request() {
+        let mut info = RateLimitInfo::new();
+
+        assert!(info.can_make_request((2, 4)));
+        assert!(info.can_make_request((2, 4)));
+        assert!(!info.can_make_request((2, 4)));
+        assert_eq!(info.get_remaining_requests((2, 4)), (0, 2));
+
+        // Un límite más alto recargado en caliente deja pasar la siguiente
+        assert!(info.can_make_request((3, 4)));
+        assert_eq!(info.get_remaining_requests((1, 4)), (0, 1));
+    }
+
+    #[test]
+    fn retry_after_waits_for_the_oldest_request_of_the_exhausted_window() {
+        let now = Utc::now();
+        let info = RateLimitInfo {
+            requests_per_minute: vec![now - Duration::seconds(10)],
+            requests_per_12h: vec![now - Duration::hours(11), now - Duration::hours(3), now - Duration::seconds(10)],
+        };
+
+        assert_eq!(info.retry_after((5, 10)), Duration::zero());
+        // Con la ventana de un minuto agotada basta con esperar a que caduque su petición
+        assert!((info.retry_after((1, 10)) - Duration::seconds(50)).num_seconds().abs() <= 1);
+        // Con la de 12 horas agotada, a que caduque la más antigua: una hora, no un minuto
+        assert!((info.retry_after((5, 3)) - Duration::hours(1)).num_seconds().abs() <= 1);
+        assert!((info.retry_after((5, 2)) - Duration::hours(9)).num_seconds().abs() <= 1);
+        assert_eq!(info.retry_after((5, 0)), Duration::hours(12));
+
+        let limiter = RateLimiter::new();
+        limiter.requests.lock().unwrap().insert("1.2.3.4".to_string(), info);
+        assert!((3599..=3600).contains(&limiter.retry_after("1.2.3.4", (5, 3))));
+        assert_eq!(limiter.retry_after("5.6.7.8", (5, 3)), 0);
+    }
+}
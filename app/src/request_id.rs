@@ -0,0 +1,205 @@
+use std::fmt;
+use std::future::{ready, Ready};
+use std::time::Instant;
+
+use actix_web::body::MessageBody;
+use actix_web::dev::{Payload, ServiceRequest, ServiceResponse};
+use actix_web::error::InternalError;
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::middleware::Next;
+use actix_web::{FromRequest, HttpMessage, HttpRequest};
+use tracing::{error, info, info_span, warn, Instrument};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use uuid::Uuid;
+
+use crate::telemetry;
+
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest incoming id that is propagated as-is; longer or non-printable ids are replaced
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+tokio::task_local! {
+    static CURRENT: RequestId;
+}
+
+/// Id of the request being handled, for code without access to the `HttpRequest` (error responses)
+pub fn current() -> Option<RequestId> {
+    CURRENT.try_with(RequestId::clone).ok()
+}
+
+/// Correlation id of the current request, propagated from `X-Request-Id` or generated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(String);
+
+impl RequestId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    fn from_header(value: Option<&HeaderValue>) -> Self {
+        value
+            .and_then(|v| v.to_str().ok())
+            .map(str::trim)
+            .filter(|id| !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN)
+            .filter(|id| id.chars().all(|c| c.is_ascii_graphic()))
+            .map(|id| RequestId(id.to_string()))
+            .unwrap_or_else(|| RequestId(Uuid::new_v4().to_string()))
+    }
+}
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromRequest for RequestId {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let id = req
+            .extensions()
+            .get::<RequestId>()
+            .cloned()
+            .unwrap_or_else(|| RequestId::from_header(req.headers().get(REQUEST_ID_HEADER)));
+        ready(Ok(id))
+    }
+}
+
+/// Middleware that assigns the request id, runs the request inside an `http_request`
+/// span carrying it, logs the outcome and echoes the id in the response
+pub async fn assign_request_id(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let request_id = RequestId::from_header(req.headers().get(REQUEST_ID_HEADER));
+    req.extensions_mut().insert(request_id.clone());
+
+    let span = info_span!(
+        "http_request",
+        request_id = %request_id,
+        method = %req.method(),
+        path = %req.path(),
+        otel.kind = "server",
+    );
+    // Continuar la traza del cliente si envía `traceparent`
+    span.set_parent(telemetry::parent_context(req.headers()));
+    let started = Instant::now();
+
+    let outcome = CURRENT
+        .scope(request_id.clone(), async {
+            // El error de un servicio interno se convierte aquí, dentro del ámbito, para que su cuerpo incluya el id
+            next.call(req).await.map_err(|e| {
+                let res = e.error_response();
+                (e, res)
+            })
+        })
+        .instrument(span.clone())
+        .await;
+
+    let status = match &outcome {
+        Ok(res) => res.status(),
+        Err((_, res)) => res.status(),
+    };
+    let latency_ms = started.elapsed().as_millis() as u64;
+    span.in_scope(|| {
+        if status.is_server_error() {
+            error!(status = status.as_u16(), latency_ms, "request failed");
+        } else if status.is_client_error() {
+            warn!(status = status.as_u16(), latency_ms, "request rejected");
+        } else {
+            info!(status = status.as_u16(), latency_ms, "request completed");
+        }
+    });
+
+    let header = HeaderValue::from_str(request_id.as_str()).ok();
+    match outcome {
+        Ok(mut res) => {
+            if let Some(value) = header {
+                res.headers_mut().insert(REQUEST_ID_HEADER, value);
+            }
+            Ok(res)
+        }
+        // La petición ya no está disponible para crear un `ServiceResponse`: el error sigue hacia fuera
+        // con su respuesta, que es la que se envía
+        Err((e, mut res)) => {
+            if let Some(value) = header {
+                res.headers_mut().insert(REQUEST_ID_HEADER, value);
+            }
+            Err(InternalError::from_response(e, res).into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::dev::fn_service;
+    use actix_web::error::ErrorServiceUnavailable;
+    use actix_web::http::StatusCode;
+    use actix_web::middleware::from_fn;
+    use actix_web::{test, web, App, HttpResponse};
+
+    async fn echo(id: RequestId) -> HttpResponse {
+        HttpResponse::Ok().body(id.to_string())
+    }
+
+    #[actix_web::test]
+    async fn propagates_incoming_id() {
+        let app = test::init_service(
+            App::new().wrap(from_fn(assign_request_id)).route("/", web::get().to(echo)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/")
+            .insert_header((REQUEST_ID_HEADER, "abc-123"))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+
+        assert_eq!(res.headers().get(REQUEST_ID_HEADER).unwrap(), "abc-123");
+        assert_eq!(test::read_body(res).await, "abc-123");
+    }
+
+    #[actix_web::test]
+    async fn generates_id_when_missing_or_invalid() {
+        let app = test::init_service(
+            App::new().wrap(from_fn(assign_request_id)).route("/", web::get().to(echo)),
+        )
+        .await;
+
+        let too_long = "x".repeat(MAX_REQUEST_ID_LEN + 1);
+        for header in [None, Some(too_long.as_str())] {
+            let mut req = test::TestRequest::get().uri("/");
+            if let Some(value) = header {
+                req = req.insert_header((REQUEST_ID_HEADER, value));
+            }
+            let res = test::call_service(&app, req.to_request()).await;
+
+            let id = res.headers().get(REQUEST_ID_HEADER).unwrap().to_str().unwrap().to_string();
+            assert!(Uuid::parse_str(&id).is_ok());
+            assert_eq!(test::read_body(res).await, id);
+        }
+    }
+
+    #[actix_web::test]
+    async fn tags_failed_services() {
+        let app = test::init_service(App::new().wrap(from_fn(assign_request_id)).default_service(fn_service(
+            |_: ServiceRequest| async { Err::<ServiceResponse, _>(ErrorServiceUnavailable("sin servicio")) },
+        )))
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/")
+            .insert_header((REQUEST_ID_HEADER, "abc-123"))
+            .to_request();
+        // El servidor responde a un error con su `error_response`
+        let res = test::try_call_service(&app, req).await.err().unwrap().error_response();
+
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(res.headers().get(REQUEST_ID_HEADER).unwrap(), "abc-123");
+        assert_eq!(actix_web::body::to_bytes(res.into_body()).await.unwrap(), "sin servicio");
+    }
+}
@@ -0,0 +1,188 @@
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::forms::looks_like_email;
+use crate::models::email::ContactRequest;
+
+/// Rules choosing who receives each contact request, read from `routing_file`.
+/// The first matching rule wins; without a match the request goes to `default`,
+/// or to `admin_emails` when no default is set.
+/// Only `/api/v1/contact` is routed: forms have no service or message to match and name their own `recipients`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RoutingRules {
+    #[serde(default)]
+    pub default: Vec<String>,
+    #[serde(default)]
+    pub rules: Vec<RoutingRule>,
+}
+
+/// Recipients of the requests meeting every condition the rule sets.
+/// Each condition matches when any of its values does, ignoring case.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RoutingRule {
+    /// Values of `ContactRequest.service`
+    #[serde(default)]
+    pub service: Vec<String>,
+    /// Words or phrases looked for as whole words in the message and the company
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    /// Domains of the submitter's email, subdomains included
+    #[serde(default)]
+    pub email_domains: Vec<String>,
+    pub recipients: Vec<String>,
+}
+
+impl RoutingRules {
+    /// Recipients chosen for `request`, or `None` to fall back to `admin_emails`
+    pub fn recipients(&self, request: &ContactRequest) -> Option<&[String]> {
+        match self.rules.iter().find(|rule| rule.matches(request)) {
+            Some(rule) => Some(&rule.recipients),
+            None if !self.default.is_empty() => Some(&self.default),
+            None => None,
+        }
+    }
+}
+
+impl RoutingRule {
+    fn matches(&self, request: &ContactRequest) -> bool {
+        let service = request.service.trim();
+        let text = words(&format!("{} {}", request.message, request.company));
+        let domain = request
+            .email
+            .rsplit_once('@')
+            .map(|(_, domain)| domain.trim().to_ascii_lowercase())
+            .unwrap_or_default();
+
+        (self.service.is_empty() || self.service.iter().any(|s| s.eq_ignore_ascii_case(service)))
+            && (self.keywords.is_empty() || self.keywords.iter().any(|k| contains_phrase(&text, &words(k))))
+            && (self.email_domains.is_empty()
+                || self.email_domains.iter().any(|d| {
+                    let d = d.trim_start_matches('@').to_ascii_lowercase();
+                    domain == d || domain.ends_with(&format!(".{}", d))
+                }))
+    }
+
+    fn is_unconditional(&self) -> bool {
+        self.service.is_empty() && self.keywords.is_empty() && self.email_domains.is_empty()
+    }
+}
+
+/// Lowercase words of `text`, split on anything that is not a letter or a digit
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Whether `phrase` appears in `text` as consecutive whole words, so `web` does not match `website`
+fn contains_phrase(text: &[String], phrase: &[String]) -> bool {
+    !phrase.is_empty() && text.windows(phrase.len()).any(|window| window == phrase)
+}
+
+/// Reads the routing rules, a TOML or YAML document with `default` and a `rules` list
+pub fn load(path: &Path) -> Result<RoutingRules, String> {
+    let contents = fs::read_to_string(path).map_err(|e| format!("no se pudo leer '{}': {}", path.display(), e))?;
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default();
+    let routing: RoutingRules = match extension.to_ascii_lowercase().as_str() {
+        "toml" => toml::from_str(&contents).map_err(|e| format!("TOML inválido: {}", e))?,
+        "yaml" | "yml" => serde_yaml::from_str(&contents).map_err(|e| format!("YAML inválido: {}", e))?,
+        other => return Err(format!("formato no soportado '{}' (usa .toml, .yaml o .yml)", other)),
+    };
+
+    if let Some(address) = routing.default.iter().find(|a| !looks_like_email(a)) {
+        return Err(format!("default: destinatario inválido '{}'", address));
+    }
+    for (index, rule) in routing.rules.iter().enumerate() {
+        let invalid = |message: String| Err(format!("regla {}: {}", index + 1, message));
+        if rule.is_unconditional() {
+            return invalid("necesita service, keywords o email_domains; usa default para el resto".to_string());
+        }
+        if rule.recipients.is_empty() {
+            return invalid("no tiene destinatarios".to_string());
+        }
+        if let Some(address) = rule.recipients.iter().find(|a| !looks_like_email(a)) {
+            return invalid(format!("destinatario inválido '{}'", address));
+        }
+    }
+    Ok(routing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules() -> RoutingRules {
+        toml::from_str(
+            r#"
+default = ["ops@tusitio.com"]
+
+[[rules]]
+email_domains = ["bigcorp.com"]
+recipients = ["cuentas@tusitio.com"]
+
+[[rules]]
+service = ["design", "branding"]
+recipients = ["diseno@tusitio.com"]
+
+[[rules]]
+service = ["support"]
+keywords = ["urgente", "caído", "no funciona"]
+recipients = ["guardia@tusitio.com"]
+
+[[rules]]
+service = ["support"]
+recipients = ["helpdesk@tusitio.com"]
+"#,
+        )
+        .unwrap()
+    }
+
+    fn request(service: &str, email: &str, message: &str) -> ContactRequest {
+        ContactRequest {
+            name: "Ana".to_string(),
+            company: "Acme".to_string(),
+            email: email.to_string(),
+            service: service.to_string(),
+            message: message.to_string(),
+            locale: None,
+        }
+    }
+
+    fn route(service: &str, email: &str, message: &str) -> Option<Vec<String>> {
+        rules().recipients(&request(service, email, message)).map(<[String]>::to_vec)
+    }
+
+    #[test]
+    fn first_matching_rule_wins() {
+        assert_eq!(route("Design", "ana@acme.com", "logo"), Some(vec!["diseno@tusitio.com".to_string()]));
+        assert_eq!(route("support", "ana@acme.com", "La web está CAÍDO"), Some(vec!["guardia@tusitio.com".to_string()]));
+        assert_eq!(route("support", "ana@acme.com", "una duda"), Some(vec!["helpdesk@tusitio.com".to_string()]));
+        // El dominio va antes que el servicio y admite subdominios
+        assert_eq!(route("design", "ana@eu.bigcorp.com", ""), Some(vec!["cuentas@tusitio.com".to_string()]));
+        assert_eq!(route("design", "ana@notbigcorp.com", ""), Some(vec!["diseno@tusitio.com".to_string()]));
+    }
+
+    #[test]
+    fn keywords_match_whole_words() {
+        let guardia = Some(vec!["guardia@tusitio.com".to_string()]);
+        assert_eq!(route("support", "ana@acme.com", "¡Urgente!"), guardia);
+        assert_eq!(route("support", "ana@acme.com", "El login no   funciona."), guardia);
+        // Ni parte de otra palabra ni las palabras de una frase por separado
+        assert_eq!(route("support", "ana@acme.com", "nada urgentemente"), Some(vec!["helpdesk@tusitio.com".to_string()]));
+        assert_eq!(route("support", "ana@acme.com", "no sé si funciona"), Some(vec!["helpdesk@tusitio.com".to_string()]));
+    }
+
+    #[test]
+    fn falls_back_to_the_default_and_then_to_the_admins() {
+        assert_eq!(route("web", "ana@acme.com", "hola"), Some(vec!["ops@tusitio.com".to_string()]));
+
+        let without_default = RoutingRules { default: Vec::new(), ..rules() };
+        assert_eq!(without_default.recipients(&request("web", "ana@acme.com", "hola")), None);
+        assert_eq!(RoutingRules::default().recipients(&request("design", "ana@acme.com", "")), None);
+    }
+}
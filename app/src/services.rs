@@ -0,0 +1,151 @@
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use futures_util::FutureExt;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+
+use crate::shutdown::Shutdown;
+
+pub type ServiceFuture<'a> = Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>>;
+
+/// Long-running task owned by the application, e.g. the rate limiter cleanup.
+/// `run` should return `Ok(())` once `shutdown` is cancelled; an error or a panic makes the
+/// supervisor start it again after a backoff.
+pub trait BackgroundService: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn run(&self, shutdown: CancellationToken) -> ServiceFuture<'_>;
+}
+
+/// Services registered through [`crate::common::Application::services`], supervised once started
+pub struct Services {
+    services: Vec<Arc<dyn BackgroundService>>,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl Default for Services {
+    fn default() -> Self {
+        Self {
+            services: Vec::new(),
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+        }
+    }
+}
+
+impl Services {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, service: impl BackgroundService + 'static) -> &mut Self {
+        self.services.push(Arc::new(service));
+        self
+    }
+
+    /// Delay before the first restart, doubled on every consecutive failure up to `max`.
+    /// Only tests shorten it; the application keeps the defaults.
+    #[cfg(test)]
+    pub fn backoff(&mut self, initial: Duration, max: Duration) -> &mut Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max.max(initial);
+        self
+    }
+
+    /// Spawns every service on the shutdown tracker so the drain waits for them to stop
+    pub fn start(self, shutdown: &Shutdown) {
+        for service in self.services {
+            info!("Starting background service '{}'", service.name());
+            shutdown.spawn(supervise(service, shutdown.token(), self.initial_backoff, self.max_backoff));
+        }
+    }
+}
+
+async fn supervise(service: Arc<dyn BackgroundService>, shutdown: CancellationToken, initial: Duration, max: Duration) {
+    let mut backoff = initial;
+    loop {
+        let started = Instant::now();
+        let outcome = AssertUnwindSafe(service.run(shutdown.clone())).catch_unwind().await;
+        if shutdown.is_cancelled() {
+            break;
+        }
+
+        match outcome {
+            Ok(Ok(())) => {
+                info!("Background service '{}' finished", service.name());
+                break;
+            }
+            Ok(Err(e)) => error!(service = service.name(), error = %e, "El servicio de fondo falló"),
+            Err(_) => error!(service = service.name(), "El servicio de fondo entró en pánico"),
+        }
+
+        // Un servicio que llevaba tiempo funcionando vuelve a empezar con la espera mínima
+        if started.elapsed() > max {
+            backoff = initial;
+        }
+        warn!("Restarting background service '{}' in {:?}", service.name(), backoff);
+        if shutdown.run_until_cancelled(tokio::time::sleep(backoff)).await.is_none() {
+            break;
+        }
+        backoff = (backoff * 2).min(max);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct Flaky {
+        runs: Arc<AtomicUsize>,
+    }
+
+    impl BackgroundService for Flaky {
+        fn name(&self) -> &'static str {
+            "flaky"
+        }
+
+        fn run(&self, shutdown: CancellationToken) -> ServiceFuture<'_> {
+            Box::pin(async move {
+                match self.runs.fetch_add(1, Ordering::SeqCst) {
+                    0 => anyhow::bail!("primer intento"),
+                    1 => panic!("segundo intento"),
+                    _ => {
+                        shutdown.cancelled().await;
+                        Ok(())
+                    }
+                }
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn restarts_failed_services_until_shutdown() {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let shutdown = Shutdown::new();
+
+        let mut services = Services::new();
+        services
+            .backoff(Duration::from_millis(5), Duration::from_millis(20))
+            .register(Flaky { runs: runs.clone() });
+        services.start(&shutdown);
+
+        // El pánico se captura como un error más; el tercer intento queda esperando al apagado
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while runs.load(Ordering::SeqCst) < 3 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("el servicio no se reinició");
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+        assert!(shutdown.drain(Duration::from_secs(1)).await);
+    }
+}
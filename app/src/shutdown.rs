@@ -0,0 +1,191 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+use tracing::{error, info, warn};
+
+type HookFuture = Pin<Box<dyn Future<Output = Result<()>> + Send>>;
+type Hook = Box<dyn FnOnce() -> HookFuture + Send>;
+
+/// Coordinates the end of the process: background tasks watch the token,
+/// work that must not be lost (mail in flight) runs on the tracker so it can be drained
+/// and the `on_shutdown` hooks run last, e.g. to persist state
+#[derive(Clone, Default)]
+pub struct Shutdown {
+    token: CancellationToken,
+    tracker: TaskTracker,
+    hooks: Arc<Mutex<Vec<(&'static str, Hook)>>>,
+    /// When the whole shutdown must be over, fixed by the first [`Shutdown::begin`]
+    deadline: Arc<OnceLock<Instant>>,
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancelled when shutdown starts; long-running loops should `select!` on it
+    pub fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+
+    /// Runs `future` as a tracked task: it keeps going if the request that started it is dropped
+    /// and shutdown waits for it
+    pub fn spawn<F>(&self, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.tracker.spawn(future)
+    }
+
+    /// Registers `hook` to run after the drain, in registration order
+    pub fn on_shutdown<F, Fut>(&self, name: &'static str, hook: F)
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        let hook: Hook = Box::new(move || Box::pin(hook()));
+        self.hooks.lock().unwrap().push((name, hook));
+    }
+
+    /// Runs every registered hook once; a failing hook is logged and does not stop the rest
+    pub async fn run_hooks(&self) -> Result<()> {
+        let hooks = std::mem::take(&mut *self.hooks.lock().unwrap());
+        let mut failed = Vec::new();
+        for (name, hook) in hooks {
+            if let Err(e) = hook().await {
+                error!(hook = name, error = %e, "Falló una tarea de apagado");
+                failed.push(name);
+            }
+        }
+
+        if failed.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!("Fallaron las tareas de apagado: {}", failed.join(", ")))
+        }
+    }
+
+    /// Starts the shutdown clock when the stop signal arrives: draining the server and the tracked work
+    /// share a single `timeout`. Later calls keep the first deadline. Returns the time left until it.
+    pub fn begin(&self, timeout: Duration) -> Duration {
+        let deadline = *self.deadline.get_or_init(|| Instant::now() + timeout);
+        deadline.saturating_duration_since(Instant::now())
+    }
+
+    /// Tracked tasks still running
+    pub fn pending(&self) -> usize {
+        self.tracker.len()
+    }
+
+    /// Cancels background tasks and waits up to `timeout` for tracked work.
+    /// Returns `false` if some work was still running when the timeout expired.
+    pub async fn drain(&self, timeout: Duration) -> bool {
+        self.token.cancel();
+        self.tracker.close();
+
+        let pending = self.pending();
+        if pending > 0 {
+            info!("Esperando {} tareas en curso (máximo {:?})", pending, timeout);
+        }
+
+        tokio::time::timeout(timeout, self.tracker.wait()).await.is_ok()
+    }
+}
+
+/// Resolves on SIGTERM or Ctrl-C
+pub async fn wait_for_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            warn!("No se pudo escuchar Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                warn!("No se pudo escuchar SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => info!("Ctrl-C recibido, apagando"),
+        _ = terminate => info!("SIGTERM recibido, apagando"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn drains_tracked_work_and_cancels_background_tasks() {
+        let shutdown = Shutdown::new();
+
+        let token = shutdown.token();
+        let background = tokio::spawn(async move { token.cancelled().await });
+        let mail = shutdown.spawn(async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            "enviado"
+        });
+
+        assert!(shutdown.drain(Duration::from_secs(5)).await);
+        assert_eq!(mail.await.unwrap(), "enviado");
+        background.await.unwrap();
+
+        let stuck = Shutdown::new();
+        stuck.spawn(std::future::pending::<()>());
+        assert!(!stuck.drain(Duration::from_millis(20)).await);
+    }
+
+    #[test]
+    fn the_deadline_is_fixed_by_the_first_call() {
+        let shutdown = Shutdown::new();
+        assert!(shutdown.begin(Duration::from_millis(200)) > Duration::from_millis(150));
+
+        std::thread::sleep(Duration::from_millis(100));
+        let left = shutdown.clone().begin(Duration::from_secs(60));
+        assert!(left <= Duration::from_millis(100), "{:?}", left);
+
+        std::thread::sleep(Duration::from_millis(150));
+        assert_eq!(shutdown.begin(Duration::from_secs(60)), Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn runs_every_hook_once_and_reports_failures() {
+        let shutdown = Shutdown::new();
+        let calls = Arc::new(Mutex::new(Vec::new()));
+
+        for name in ["guardar", "falla", "cerrar"] {
+            let calls = calls.clone();
+            shutdown.on_shutdown(name, move || async move {
+                calls.lock().unwrap().push(name);
+                if name == "falla" {
+                    anyhow::bail!("disco lleno");
+                }
+                Ok(())
+            });
+        }
+
+        let error = shutdown.run_hooks().await.unwrap_err();
+        assert!(error.to_string().contains("falla"));
+        assert_eq!(*calls.lock().unwrap(), ["guardar", "falla", "cerrar"]);
+        assert!(shutdown.run_hooks().await.is_ok());
+    }
+}
@@ -0,0 +1,178 @@
+use std::time::Duration;
+
+use actix_web::http::header::HeaderMap;
+use anyhow::Result;
+use once_cell::sync::OnceCell;
+use opentelemetry::propagation::Extractor;
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry::Context;
+use opentelemetry_otlp::{Protocol, WithExportConfig};
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::Resource;
+use tracing::{warn, Subscriber};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+use crate::config::Config;
+
+/// Path appended to the collector base URL for the OTLP/HTTP traces signal
+const TRACES_PATH: &str = "/v1/traces";
+
+const EXPORT_TIMEOUT: Duration = Duration::from_secs(5);
+
+static TRACER_PROVIDER: OnceCell<SdkTracerProvider> = OnceCell::new();
+
+/// Builds a tracer provider exporting spans over OTLP/HTTP (protobuf) in batches
+pub fn build_provider(endpoint: &str, service_name: &str) -> Result<SdkTracerProvider> {
+    let endpoint = if endpoint.ends_with(TRACES_PATH) {
+        endpoint.to_string()
+    } else {
+        format!("{}{}", endpoint.trim_end_matches('/'), TRACES_PATH)
+    };
+
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .with_protocol(Protocol::HttpBinary)
+        .with_endpoint(endpoint)
+        .with_timeout(EXPORT_TIMEOUT)
+        .build()?;
+
+    Ok(SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(Resource::builder().with_service_name(service_name.to_string()).build())
+        .build())
+}
+
+/// OpenTelemetry layer for the tracing subscriber, when `otel_endpoint` is set.
+/// Also installs the W3C trace-context propagator used for incoming `traceparent` headers.
+pub fn layer<S>() -> Result<Option<impl Layer<S>>>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    let Some(endpoint) = Config::get_otel_endpoint() else {
+        return Ok(None);
+    };
+
+    opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+
+    let provider = build_provider(endpoint, Config::get_otel_service_name())?;
+    let tracer = provider.tracer("base-server");
+    let _ = TRACER_PROVIDER.set(provider);
+
+    Ok(Some(tracing_opentelemetry::layer().with_tracer(tracer)))
+}
+
+/// Remote parent context carried by the request's `traceparent`/`tracestate` headers
+pub fn parent_context(headers: &HeaderMap) -> Context {
+    opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.extract(&HeaderExtractor(headers))
+    })
+}
+
+/// Flushes pending spans and stops the exporter. Blocking.
+pub fn shutdown() {
+    if let Some(provider) = TRACER_PROVIDER.get() {
+        if let Err(e) = provider.shutdown() {
+            warn!("Error al cerrar el exportador OTLP: {}", e);
+        }
+    }
+}
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|v| v.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|k| k.as_str()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+
+    use actix_web::http::header::{HeaderName, HeaderValue};
+    use opentelemetry::trace::TraceContextExt;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    /// Minimal OTLP/HTTP collector: accepts one request and reports its request line and content type
+    fn spawn_collector_stub() -> (String, mpsc::Receiver<(String, String)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = format!("http://{}", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::channel();
+
+        std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+
+            let (mut content_type, mut content_length) = (String::new(), 0usize);
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                let (name, value) = line.split_once(':').unwrap();
+                match name.to_ascii_lowercase().as_str() {
+                    "content-type" => content_type = value.trim().to_string(),
+                    "content-length" => content_length = value.trim().parse().unwrap(),
+                    _ => {}
+                }
+            }
+            let mut body = vec![0u8; content_length];
+            reader.read_exact(&mut body).unwrap();
+
+            reader
+                .get_mut()
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+                .unwrap();
+            tx.send((request_line.trim().to_string(), content_type)).unwrap();
+        });
+
+        (addr, rx)
+    }
+
+    #[test]
+    fn exports_spans_to_collector() {
+        let (endpoint, received) = spawn_collector_stub();
+        let provider = build_provider(&endpoint, "test-service").unwrap();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info_span!("smtp.send").in_scope(|| {});
+        });
+        provider.force_flush().unwrap();
+
+        let (request_line, content_type) = received.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert_eq!(request_line, "POST /v1/traces HTTP/1.1");
+        assert_eq!(content_type, "application/x-protobuf");
+        provider.shutdown().unwrap();
+    }
+
+    #[test]
+    fn extracts_w3c_traceparent() {
+        opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            HeaderName::from_static("traceparent"),
+            HeaderValue::from_static("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"),
+        );
+
+        let context = parent_context(&headers);
+        let span_context = context.span().span_context().clone();
+        assert!(span_context.is_remote());
+        assert_eq!(span_context.trace_id().to_string(), "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(span_context.span_id().to_string(), "00f067aa0ba902b7");
+    }
+}
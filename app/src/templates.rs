@@ -0,0 +1,387 @@
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use tracing::instrument;
+
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+use crate::forms::FormDefinition;
+use crate::i18n::{self, Locale, Message};
+use crate::models::email::ContactRequest;
+
+/// `{{name}}` placeholders of the custom form templates
+static PLACEHOLDER: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{\{([^{}]+)\}\}").unwrap());
+
+/// Escapes user input before it is placed in the HTML
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Shared document and styles of every email; `body` is already escaped HTML
+fn layout(locale: Locale, title: &str, body: &str) -> String {
+    format!(
+        r#"
+        <!DOCTYPE html>
+        <html lang="{}">
+        <head>
+            <meta charset="UTF-8">
+            <meta name="viewport" content="width=device-width, initial-scale=1.0">
+            <title>{}</title>
+            <style>
+                body {{
+                    font-family: 'Segoe UI', Tahoma, Geneva, Verdana, sans-serif;
+                    line-height: 1.6;
+                    color: #333;
+                    max-width: 600px;
+                    margin: 0 auto;
+                    padding: 20px;
+                    background-color: #f4f4f4;
+                }}
+                .container {{
+                    background-color: #ffffff;
+                    border-radius: 10px;
+                    padding: 30px;
+                    box-shadow: 0 4px 6px rgba(0, 0, 0, 0.1);
+                }}
+                .header {{
+                    background: linear-gradient(135deg, #667eea 0%, #764ba2 100%);
+                    color: white;
+                    padding: 20px;
+                    border-radius: 8px;
+                    text-align: center;
+                    margin-bottom: 25px;
+                }}
+                .header h1 {{
+                    margin: 0;
+                    font-size: 24px;
+                    font-weight: 300;
+                }}
+                .info-section {{
+                    background-color: #f8f9fa;
+                    border-left: 4px solid #667eea;
+                    padding: 15px;
+                    margin: 15px 0;
+                    border-radius: 0 5px 5px 0;
+                }}
+                .info-section h3 {{
+                    margin: 0 0 10px 0;
+                    color: #667eea;
+                    font-size: 16px;
+                }}
+                .field {{
+                    margin: 10px 0;
+                }}
+                .field strong {{
+                    color: #555;
+                    display: inline-block;
+                    width: 80px;
+                }}
+                .message-box {{
+                    background-color: #fff3cd;
+                    border: 1px solid #ffeaa7;
+                    border-radius: 5px;
+                    padding: 15px;
+                    margin: 15px 0;
+                }}
+                .footer {{
+                    text-align: center;
+                    margin-top: 30px;
+                    padding-top: 20px;
+                    border-top: 1px solid #eee;
+                    color: #666;
+                    font-size: 14px;
+                }}
+                .ip-badge {{
+                    background-color: #e3f2fd;
+                    color: #1976d2;
+                    padding: 5px 10px;
+                    border-radius: 15px;
+                    font-size: 12px;
+                    font-family: monospace;
+                }}
+            </style>
+        </head>
+        <body>
+            <div class="container">
+{}
+            </div>
+        </body>
+        </html>
+        "#,
+        locale,
+        escape(title),
+        body
+    )
+}
+
+/// Renders the HTML notification sent to the admins for a new contact request
+#[instrument(name = "template.render", skip_all, fields(template = "contact_notification", locale = %locale))]
+pub fn contact_notification(locale: Locale, client_ip: &str, request: &ContactRequest, sent_at: DateTime<Utc>) -> String {
+    let t = |key| escape(&i18n::text(locale, key));
+    let body = format!(
+        r#"                <div class="header">
+                    <h1>📧 {}</h1>
+                </div>
+                
+                <div class="info-section">
+                    <h3>📍 {}</h3>
+                    <div class="field">
+                        <strong>IP:</strong> 
+                        <span class="ip-badge">{}</span>
+                    </div>
+                    <div class="field">
+                        <strong>{}:</strong> {}
+                    </div>
+                    <div class="field">
+                        <strong>{}:</strong> {}
+                    </div>
+                    <div class="field">
+                        <strong>{}:</strong> {}
+                    </div>
+                    <div class="field">
+                        <strong>{}:</strong> {}
+                    </div>
+                </div>
+                
+                <div class="message-box">
+                    <h3>💬 {}</h3>
+                    <p style="white-space: pre-wrap; margin: 0;">{}</p>
+                </div>
+                
+                <div class="footer">
+                    <p>{}</p>
+                    <p>{}: {}</p>
+                </div>"#,
+        t("notification.title"),
+        t("notification.client_info"),
+        escape(client_ip),
+        t("notification.name"),
+        escape(&request.name),
+        t("notification.company"),
+        escape(&request.company),
+        t("notification.email"),
+        escape(&request.email),
+        t("notification.service"),
+        escape(&request.service),
+        t("notification.message"),
+        escape(&request.message),
+        t("notification.footer"),
+        t("notification.sent_at"),
+        escape(&i18n::format_datetime(locale, sent_at))
+    );
+    layout(locale, &i18n::text(locale, "notification.title"), &body)
+}
+
+/// Renders the acknowledgement sent to the customer in their language
+#[instrument(name = "template.render", skip_all, fields(template = "contact_acknowledgement", locale = %locale))]
+pub fn contact_acknowledgement(locale: Locale, request: &ContactRequest, received_at: DateTime<Utc>) -> String {
+    let t = |key| escape(&i18n::text(locale, key));
+    let body = format!(
+        r#"                <div class="header">
+                    <h1>{}</h1>
+                </div>
+
+                <p>{}</p>
+                <p>{}</p>
+
+                <div class="message-box">
+                    <h3>{}</h3>
+                    <p style="white-space: pre-wrap; margin: 0;">{}</p>
+                </div>
+
+                <div class="footer">
+                    <p>{}: {}</p>
+                    <p>{}</p>
+                </div>"#,
+        t("acknowledgement.title"),
+        escape(&Message::new("acknowledgement.greeting").arg("name", &request.name).render(locale)),
+        escape(&Message::new("acknowledgement.body").arg("service", &request.service).render(locale)),
+        t("acknowledgement.summary"),
+        escape(&request.message),
+        t("acknowledgement.received_at"),
+        escape(&i18n::format_datetime(locale, received_at)),
+        t("acknowledgement.footer")
+    );
+    layout(locale, &i18n::text(locale, "acknowledgement.title"), &body)
+}
+
+/// Plain `(label, value)` pairs of a contact request, shown by the chat channels
+pub fn contact_fields(locale: Locale, client_ip: &str, request: &ContactRequest) -> Vec<(String, String)> {
+    [
+        ("notification.name", request.name.as_str()),
+        ("notification.company", request.company.as_str()),
+        ("notification.email", request.email.as_str()),
+        ("notification.service", request.service.as_str()),
+        ("notification.message", request.message.as_str()),
+        ("form.client_ip", client_ip),
+    ]
+    .into_iter()
+    .map(|(key, value)| (i18n::text(locale, key), value.to_string()))
+    .collect()
+}
+
+/// Plain `(label, value)` pairs of a form submission in the order of its definition
+pub fn form_fields(locale: Locale, form: &FormDefinition, client_ip: &str, values: &BTreeMap<String, Value>) -> Vec<(String, String)> {
+    form.fields
+        .iter()
+        .filter_map(|field| values.get(&field.name).map(|value| (field.label().to_string(), display_value(value))))
+        .chain([(i18n::text(locale, "form.client_ip"), client_ip.to_string())])
+        .collect()
+}
+
+/// Text of a submitted value as shown in the emails
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Renders the notification of a form submission: the form's own template when it has one,
+/// otherwise every field with its label
+#[instrument(name = "template.render", skip_all, fields(template = "form_notification", form = form_id, locale = %locale))]
+pub fn form_notification(
+    locale: Locale,
+    form_id: &str,
+    form: &FormDefinition,
+    client_ip: &str,
+    values: &BTreeMap<String, Value>,
+    sent_at: DateTime<Utc>,
+) -> String {
+    let title = form.title(form_id);
+    let sent_at = i18n::format_datetime(locale, sent_at);
+
+    if let Some(template) = &form.template {
+        // Una sola pasada sobre la plantilla: un valor enviado que contenga `{{otro_campo}}` no se vuelve a sustituir
+        let rendered = PLACEHOLDER.replace_all(template, |caps: &Captures| {
+            let value = match &caps[1] {
+                "form" => title.to_string(),
+                "ip" => client_ip.to_string(),
+                "date" => sent_at.clone(),
+                // Los campos opcionales que no se enviaron quedan vacíos
+                name if form.field(name).is_some() => values.get(name).map(display_value).unwrap_or_default(),
+                _ => return caps[0].to_string(),
+            };
+            escape(&value)
+        });
+        return rendered.into_owned();
+    }
+
+    let t = |key| escape(&i18n::text(locale, key));
+    let fields: String = form
+        .fields
+        .iter()
+        .filter_map(|field| values.get(&field.name).map(|value| (field, value)))
+        .map(|(field, value)| {
+            format!(
+                r#"
+                    <div class="field">
+                        <strong>{}:</strong>
+                        <span style="white-space: pre-wrap;">{}</span>
+                    </div>"#,
+                escape(field.label()),
+                escape(&display_value(value))
+            )
+        })
+        .collect();
+    let heading = Message::new("form.title").arg("form", title).render(locale);
+
+    let body = format!(
+        r#"                <div class="header">
+                    <h1>📧 {}</h1>
+                </div>
+
+                <div class="info-section">
+                    <div class="field">
+                        <strong>{}:</strong>
+                        <span class="ip-badge">{}</span>
+                    </div>{}
+                </div>
+
+                <div class="footer">
+                    <p>{}</p>
+                    <p>{}: {}</p>
+                </div>"#,
+        escape(&heading),
+        t("form.client_ip"),
+        escape(client_ip),
+        fields,
+        t("notification.footer"),
+        t("notification.sent_at"),
+        escape(&sent_at)
+    );
+    layout(locale, &heading, &body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn request() -> ContactRequest {
+        ContactRequest {
+            name: "Ana <script>".to_string(),
+            company: "ACME".to_string(),
+            email: "ana@example.com".to_string(),
+            service: "web".to_string(),
+            message: "Hola".to_string(),
+            locale: None,
+        }
+    }
+
+    #[test]
+    fn renders_localised_and_escaped() {
+        let at = Utc.with_ymd_and_hms(2026, 10, 19, 14, 5, 0).unwrap();
+
+        let ack = contact_acknowledgement(Locale::En, &request(), at);
+        assert!(ack.contains(r#"<html lang="en">"#));
+        assert!(ack.contains("Hi Ana &lt;script&gt;,"));
+        assert!(ack.contains("October 19, 2026, 2:05 PM UTC"));
+
+        let notification = contact_notification(Locale::Es, "1.2.3.4", &request(), at);
+        assert!(notification.contains("Nueva Solicitud de Contacto"));
+        assert!(notification.contains("19 de octubre de 2026, 14:05 UTC"));
+        assert!(!notification.contains("<script>"));
+    }
+
+    #[test]
+    fn renders_form_templates_escaped() {
+        let at = Utc.with_ymd_and_hms(2026, 10, 19, 14, 5, 0).unwrap();
+        let mut form: FormDefinition = toml::from_str(
+            "title = \"Presupuesto\"\n[[fields]]\nname = \"name\"\nlabel = \"Nombre\"\n[[fields]]\nname = \"budget\"\ntype = \"number\"\n",
+        )
+        .unwrap();
+        let values = BTreeMap::from([("name".to_string(), Value::from("Ana <b>"))]);
+
+        let generic = form_notification(Locale::Es, "quote", &form, "1.2.3.4", &values, at);
+        assert!(generic.contains("Nuevo envío del formulario «Presupuesto»"));
+        assert!(generic.contains("Nombre:"));
+        assert!(generic.contains("Ana &lt;b&gt;"));
+
+        form.template = Some("<h1>{{form}}</h1><p>{{name}} ({{budget}}) {{ip}}</p>".to_string());
+        let custom = form_notification(Locale::Es, "quote", &form, "1.2.3.4", &values, at);
+        assert_eq!(custom, "<h1>Presupuesto</h1><p>Ana &lt;b&gt; () 1.2.3.4</p>");
+
+        // Los valores enviados no pueden inyectar otros marcadores, y los desconocidos se dejan como están
+        let values = BTreeMap::from([
+            ("name".to_string(), Value::from("{{budget}} {{ip}}")),
+            ("budget".to_string(), Value::from(100)),
+        ]);
+        form.template = Some("{{name}} {{budget}} {{otro}}".to_string());
+        let custom = form_notification(Locale::Es, "quote", &form, "1.2.3.4", &values, at);
+        assert_eq!(custom, "{{budget}} {{ip}} 100 {{otro}}");
+    }
+}
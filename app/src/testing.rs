@@ -0,0 +1,275 @@
+use std::net::{SocketAddr, TcpListener};
+
+use actix_web::dev::{ServerHandle, Service, ServiceResponse};
+use actix_web::http::StatusCode;
+use actix_web::{test, web};
+use anyhow::{anyhow, Result};
+use email_sender::EmailSender;
+use once_cell::sync::OnceCell;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::task::JoinHandle;
+use tracing_subscriber::layer::{Layered, SubscriberExt};
+use tracing_subscriber::Registry;
+
+use crate::app_state::AppState;
+use crate::auth::api_key::{hash_api_key, ApiKeyEntry};
+use crate::auth::jwt::JwtCodec;
+use crate::auth::{AuthService, API_KEY_HEADER};
+use crate::common::Application;
+use crate::config::{Config, MailTransport};
+use crate::forms;
+use crate::logging::{self, FilterLayer};
+use crate::shutdown::Shutdown;
+
+/// API keys accepted by [`app_state`], one per role
+pub const ADMIN_KEY: &str = "bsk_test_admin";
+pub const VIEWER_KEY: &str = "bsk_test_viewer";
+/// Secret of the bearer tokens accepted by [`app_state`]
+pub const JWT_SECRET: &[u8] = b"secreto-de-las-pruebas";
+
+/// Held by the tests that swap the live configuration and by those that check its reloadable keys
+pub static LIVE_CONFIG: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// Forms available to the unit tests, as `forms_file` would define them
+const FORMS: &str = r#"
+[quote]
+title = "Presupuesto"
+rate_limit = { per_minute = 2, per_12h = 10 }
+
+[[quote.fields]]
+name = "name"
+label = "Nombre"
+required = true
+
+[[quote.fields]]
+name = "email"
+type = "email"
+required = true
+"#;
+
+/// Configuration of the unit tests: the defaults plus the forms in [`FORMS`], with mail kept in memory
+pub fn config() -> Config {
+    let path = std::env::temp_dir().join(format!("forms-{}.toml", std::process::id()));
+    std::fs::write(&path, FORMS).expect("formularios de prueba");
+    let forms = forms::load(&path).expect("formularios de prueba");
+    let _ = std::fs::remove_file(path);
+    Config {
+        forms,
+        mail_transport: Some(MailTransport::Capture),
+        ..Config::default()
+    }
+}
+
+/// State for handler tests: mail captured in memory, an admin and a viewer API key and tokens signed with [`JWT_SECRET`]
+pub async fn app_state() -> web::Data<AppState> {
+    let mut state = AppState::new(EmailSender::capture("no-reply@tusitio.com"), Shutdown::new())
+        .await
+        .expect("estado de prueba");
+    let keys = [("ops", "admin", ADMIN_KEY), ("soporte", "viewer", VIEWER_KEY)]
+        .iter()
+        .map(|(name, role, key)| ApiKeyEntry::parse(&format!("{}:{}:{}", name, role, hash_api_key(key))).unwrap())
+        .collect();
+    state.auth = AuthService::new(keys, JwtCodec::new(JWT_SECRET, 60));
+    web::Data::new(state)
+}
+
+/// Authenticates `req` with an API key
+pub fn with_key(req: test::TestRequest, key: &str) -> test::TestRequest {
+    req.insert_header((API_KEY_HEADER, key))
+}
+
+/// Calls the test service and returns the status and the JSON body, `null` if the body is not JSON
+pub async fn call<S, R>(app: &S, req: R) -> (StatusCode, serde_json::Value)
+where
+    S: Service<R, Response = ServiceResponse, Error = actix_web::Error>,
+{
+    let res = test::call_service(app, req).await;
+    let status = res.status();
+    let body = test::read_body(res).await;
+    (status, serde_json::from_slice(&body).unwrap_or_default())
+}
+
+/// Installs the reloadable log filter once, on a subscriber kept alive for the whole test run
+/// but never set as the global one, so tests can read and change it through [`logging`]
+pub fn init_logging() {
+    static SUBSCRIBER: OnceCell<Layered<FilterLayer, Registry>> = OnceCell::new();
+    SUBSCRIBER.get_or_init(|| Registry::default().with(logging::filter_layer("info").unwrap()));
+}
+
+/// Boots an [`Application`] on an ephemeral local port for integration tests.
+/// Configuration and logging are left alone, so the app runs on the default configuration.
+pub struct TestServer<A: Application> {
+    app: A,
+    addr: SocketAddr,
+    handle: ServerHandle,
+    server: JoinHandle<std::io::Result<()>>,
+}
+
+impl<A: Application> TestServer<A> {
+    /// Runs setup, starts the background services and serves the registered routes on `127.0.0.1:0`
+    pub async fn start(app: A) -> Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        let server = app.launch(listener).await?;
+        let handle = server.handle();
+
+        Ok(Self {
+            app,
+            addr,
+            handle,
+            server: tokio::spawn(server),
+        })
+    }
+
+    pub fn app(&self) -> &A {
+        &self.app
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Sends a bare HTTP/1.1 `GET` and returns the status code and the body
+    pub async fn get(&self, path: &str) -> Result<(u16, String)> {
+        self.request("GET", path).await
+    }
+
+    /// Sends a bare HTTP/1.1 request without body and returns the status code and the body
+    pub async fn request(&self, method: &str, path: &str) -> Result<(u16, String)> {
+        let mut stream = TcpStream::connect(self.addr).await?;
+        let request = format!(
+            "{} {} HTTP/1.1\r\nHost: {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            method, path, self.addr
+        );
+        stream.write_all(request.as_bytes()).await?;
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await?;
+        let (head, body) = response
+            .split_once("\r\n\r\n")
+            .ok_or_else(|| anyhow!("Respuesta HTTP incompleta"))?;
+        let status = head
+            .split_whitespace()
+            .nth(1)
+            .and_then(|code| code.parse().ok())
+            .ok_or_else(|| anyhow!("Línea de estado inválida: {}", head))?;
+        Ok((status, body.to_string()))
+    }
+
+    /// Stops the server gracefully and runs the application's shutdown, like a SIGTERM would
+    pub async fn stop(self) -> Result<A> {
+        self.app.lifecycle().begin(Config::get_shutdown_timeout());
+        self.handle.stop(true).await;
+        self.server.await??;
+        self.app.shutdown().await?;
+        Ok(self.app)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{Controller, Routes};
+    use crate::services::{BackgroundService, ServiceFuture, Services};
+    use actix_web::{dev::Server, get, App, HttpResponse, HttpServer, Responder};
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio_util::sync::CancellationToken;
+
+    #[get("/ping")]
+    async fn ping() -> impl Responder {
+        HttpResponse::Ok().body("pong")
+    }
+
+    fn demo() -> Controller {
+        Controller {
+            prefix: "/demo",
+            scope: || web::scope("/demo").service(ping),
+            openapi: Default::default,
+        }
+    }
+
+    struct Ticker {
+        ticks: Arc<AtomicUsize>,
+    }
+
+    impl BackgroundService for Ticker {
+        fn name(&self) -> &'static str {
+            "ticker"
+        }
+
+        fn run(&self, shutdown: CancellationToken) -> ServiceFuture<'_> {
+            Box::pin(async move {
+                if self.ticks.fetch_add(1, Ordering::SeqCst) == 0 {
+                    anyhow::bail!("falla al arrancar");
+                }
+                shutdown.cancelled().await;
+                Ok(())
+            })
+        }
+    }
+
+    #[derive(Default)]
+    struct Demo {
+        lifecycle: Shutdown,
+        ticks: Arc<AtomicUsize>,
+        saved: Arc<AtomicBool>,
+    }
+
+    impl Application for Demo {
+        fn lifecycle(&self) -> &Shutdown {
+            &self.lifecycle
+        }
+
+        async fn setup(&self) -> Result<()> {
+            let saved = self.saved.clone();
+            self.lifecycle.on_shutdown("demo", move || async move {
+                saved.store(true, Ordering::SeqCst);
+                Ok(())
+            });
+            Ok(())
+        }
+
+        fn routes(&self, routes: &mut Routes) {
+            routes.api(demo());
+        }
+
+        fn services(&self, services: &mut Services) {
+            services
+                .backoff(Duration::from_millis(5), Duration::from_millis(5))
+                .register(Ticker { ticks: self.ticks.clone() });
+        }
+
+        fn create_server(&self, listener: TcpListener, routes: Routes) -> Result<Server> {
+            Ok(HttpServer::new(move || App::new().configure(|cfg| routes.configure(cfg)))
+                .workers(1)
+                .disable_signals()
+                .listen(listener)?
+                .run())
+        }
+    }
+
+    #[actix_web::test]
+    async fn boots_serves_and_shuts_down_an_application() {
+        let server = TestServer::start(Demo::default()).await.unwrap();
+        assert_ne!(server.addr().port(), 0);
+
+        assert_eq!(server.get("/api/v1/demo/ping").await.unwrap(), (200, "pong".to_string()));
+        assert_eq!(server.get("/nope").await.unwrap().0, 404);
+
+        // El servicio falla al arrancar y el supervisor lo reinicia
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while server.app().ticks.load(Ordering::SeqCst) < 2 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("el servicio no se reinició");
+
+        let app = server.stop().await.unwrap();
+        assert!(app.saved.load(Ordering::SeqCst));
+        assert!(app.lifecycle.token().is_cancelled());
+    }
+}
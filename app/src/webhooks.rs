@@ -0,0 +1,357 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use ring::hmac;
+use serde::Serialize;
+use tracing::{debug, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::config::{Config, Secret};
+use crate::shutdown::Shutdown;
+
+/// `sha256=<hex>` HMAC-SHA256 of the raw request body, keyed with `webhook_secret`
+pub const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+pub const EVENT_HEADER: &str = "X-Webhook-Event";
+/// Same value on every attempt of a delivery, so receivers can drop duplicates
+pub const DELIVERY_HEADER: &str = "X-Webhook-Delivery";
+
+/// Deliveries kept in the log; the oldest are dropped first
+const LOG_CAPACITY: usize = 500;
+
+/// Events that can be sent to webhook targets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+pub enum WebhookEvent {
+    /// A request arrived at `/api/v1/contact`
+    #[serde(rename = "contact.received")]
+    ContactReceived,
+    /// A configured form was submitted at `/api/v1/forms/{form_id}`
+    #[serde(rename = "form.submitted")]
+    FormSubmitted,
+}
+
+impl WebhookEvent {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            WebhookEvent::ContactReceived => "contact.received",
+            WebhookEvent::FormSubmitted => "form.submitted",
+        }
+    }
+}
+
+/// Body of every webhook request
+#[derive(Serialize)]
+struct Payload<'a, T> {
+    id: &'a str,
+    event: WebhookEvent,
+    created_at: DateTime<Utc>,
+    data: &'a T,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryStatus {
+    /// Being sent or waiting for the next attempt
+    Pending,
+    Delivered,
+    /// Rejected by the target, out of attempts or interrupted by shutdown
+    Failed,
+}
+
+/// One event sent to one target, as kept in the delivery log
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Delivery {
+    pub id: String,
+    pub event: WebhookEvent,
+    pub url: String,
+    pub status: DeliveryStatus,
+    pub attempts: u32,
+    /// HTTP status of the last response, if the target answered
+    pub response_status: Option<u16>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Sends signed events to the configured targets in the background, retrying with exponential backoff
+#[derive(Clone)]
+pub struct Webhooks {
+    client: reqwest::Client,
+    targets: Arc<Vec<(WebhookEvent, String)>>,
+    key: Option<hmac::Key>,
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    log: Arc<Mutex<VecDeque<Delivery>>>,
+}
+
+impl Webhooks {
+    pub fn new(targets: Vec<(WebhookEvent, String)>, secret: Option<&Secret>, timeout: Duration) -> Result<Self> {
+        Ok(Self {
+            client: reqwest::Client::builder().timeout(timeout).build()?,
+            targets: Arc::new(targets),
+            key: secret.map(|s| hmac::Key::new(hmac::HMAC_SHA256, s.expose().as_bytes())),
+            max_attempts: 5,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(300),
+            log: Arc::new(Mutex::new(VecDeque::new())),
+        })
+    }
+
+    pub fn from_config() -> Result<Self> {
+        let targets = [
+            (WebhookEvent::ContactReceived, Config::get_webhook_contact_received()),
+            (WebhookEvent::FormSubmitted, Config::get_webhook_form_submitted()),
+        ]
+        .into_iter()
+        .flat_map(|(event, urls)| urls.iter().map(move |url| (event, url.clone())))
+        .collect();
+
+        Ok(Self::new(targets, Config::get_webhook_secret(), Config::get_webhook_timeout())?
+            .retries(Config::get_webhook_max_attempts(), Duration::from_secs(1), Duration::from_secs(300)))
+    }
+
+    /// Attempts per delivery and the wait between them, doubled after every failure
+    pub fn retries(mut self, max_attempts: u32, initial_backoff: Duration, max_backoff: Duration) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self.initial_backoff = initial_backoff;
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Queues `data` for every target of `event`. Deliveries run as shutdown-tracked tasks:
+    /// an attempt in flight is awaited on shutdown, pending retries are abandoned.
+    pub fn dispatch<T: Serialize>(&self, shutdown: &Shutdown, event: WebhookEvent, data: &T) {
+        for (_, url) in self.targets.iter().filter(|(e, _)| *e == event) {
+            let id = Uuid::new_v4().to_string();
+            let payload = Payload {
+                id: &id,
+                event,
+                created_at: Utc::now(),
+                data,
+            };
+            let body = match serde_json::to_vec(&payload) {
+                Ok(body) => body,
+                Err(e) => {
+                    warn!(event = event.as_str(), error = %e, "No se pudo serializar el webhook");
+                    return;
+                }
+            };
+
+            self.record(Delivery {
+                id: id.clone(),
+                event,
+                url: url.clone(),
+                status: DeliveryStatus::Pending,
+                attempts: 0,
+                response_status: None,
+                error: None,
+                created_at: payload.created_at,
+                updated_at: payload.created_at,
+            });
+
+            let webhooks = self.clone();
+            let url = url.clone();
+            let cancelled = shutdown.token();
+            shutdown.spawn(async move {
+                let mut backoff = webhooks.initial_backoff;
+                for attempt in 1..=webhooks.max_attempts {
+                    let outcome = webhooks.attempt(&id, event, &url, &body).await;
+                    let done = match outcome {
+                        Ok(_) => true,
+                        Err(Some(status)) if !retryable(status) => true,
+                        Err(_) => attempt == webhooks.max_attempts,
+                    };
+
+                    webhooks.update(&id, |d| {
+                        d.attempts = attempt;
+                        match &outcome {
+                            Ok(status) => {
+                                d.status = DeliveryStatus::Delivered;
+                                d.response_status = Some(status.as_u16());
+                                d.error = None;
+                            }
+                            Err(status) => {
+                                d.response_status = status.map(|s| s.as_u16());
+                                if done {
+                                    d.status = DeliveryStatus::Failed;
+                                }
+                            }
+                        }
+                    });
+                    if done {
+                        if outcome.is_err() {
+                            warn!(event = event.as_str(), url = %url, attempts = attempt, "Webhook no entregado");
+                        }
+                        return;
+                    }
+
+                    if cancelled.run_until_cancelled(tokio::time::sleep(backoff)).await.is_none() {
+                        webhooks.update(&id, |d| {
+                            d.status = DeliveryStatus::Failed;
+                            d.error = Some("reintentos cancelados por el apagado".to_string());
+                        });
+                        return;
+                    }
+                    backoff = (backoff * 2).min(webhooks.max_backoff);
+                }
+            });
+        }
+    }
+
+    /// Sends one attempt. `Err(None)` is a network error or timeout, always retried.
+    async fn attempt(&self, id: &str, event: WebhookEvent, url: &str, body: &[u8]) -> Result<StatusCode, Option<StatusCode>> {
+        let mut request = self
+            .client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, event.as_str())
+            .header(DELIVERY_HEADER, id)
+            .body(body.to_vec());
+        if let Some(key) = &self.key {
+            request = request.header(SIGNATURE_HEADER, sign(key, body));
+        }
+
+        match request.send().await {
+            Ok(response) if response.status().is_success() => Ok(response.status()),
+            Ok(response) => {
+                let status = response.status();
+                debug!(url, status = status.as_u16(), "El destino del webhook respondió con error");
+                self.update(id, |d| d.error = Some(format!("HTTP {}", status)));
+                Err(Some(status))
+            }
+            Err(e) => {
+                debug!(url, error = %e, "Fallo de red al enviar el webhook");
+                self.update(id, |d| d.error = Some(e.to_string()));
+                Err(None)
+            }
+        }
+    }
+
+    /// Delivery log, newest first
+    pub fn deliveries(&self) -> Vec<Delivery> {
+        self.log.lock().unwrap().iter().rev().cloned().collect()
+    }
+
+    fn record(&self, delivery: Delivery) {
+        let mut log = self.log.lock().unwrap();
+        if log.len() == LOG_CAPACITY {
+            log.pop_front();
+        }
+        log.push_back(delivery);
+    }
+
+    fn update(&self, id: &str, apply: impl FnOnce(&mut Delivery)) {
+        let mut log = self.log.lock().unwrap();
+        if let Some(delivery) = log.iter_mut().rev().find(|d| d.id == id) {
+            apply(delivery);
+            delivery.updated_at = Utc::now();
+        }
+    }
+}
+
+/// Server errors, timeouts and rate limiting may succeed later; any other status will not
+fn retryable(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::REQUEST_TIMEOUT || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+fn sign(key: &hmac::Key, body: &[u8]) -> String {
+    format!("sha256={}", hex::encode(hmac::sign(key, body).as_ref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct Stub {
+        hits: AtomicUsize,
+        received: Mutex<Vec<(String, String, Vec<u8>)>>,
+    }
+
+    /// Receptor local: `/flaky` falla la primera vez, `/reject` rechaza siempre con 400
+    async fn receive(req: HttpRequest, body: web::Bytes, stub: web::Data<Stub>) -> HttpResponse {
+        let header = |name| req.headers().get(name).and_then(|v| v.to_str().ok()).unwrap_or_default().to_string();
+        stub.received
+            .lock()
+            .unwrap()
+            .push((header(EVENT_HEADER), header(SIGNATURE_HEADER), body.to_vec()));
+
+        match req.path() {
+            "/reject" => HttpResponse::BadRequest().finish(),
+            _ if stub.hits.fetch_add(1, Ordering::SeqCst) == 0 => HttpResponse::InternalServerError().finish(),
+            _ => HttpResponse::NoContent().finish(),
+        }
+    }
+
+    #[actix_web::test]
+    async fn signs_retries_and_logs_deliveries() {
+        let stub = web::Data::new(Stub::default());
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let data = stub.clone();
+        let server = HttpServer::new(move || App::new().app_data(data.clone()).default_service(web::to(receive)))
+            .workers(1)
+            .disable_signals()
+            .listen(listener)
+            .unwrap()
+            .run();
+        let handle = server.handle();
+        actix_web::rt::spawn(server);
+
+        let secret = Secret::from("s3creto");
+        let webhooks = Webhooks::new(
+            vec![
+                (WebhookEvent::ContactReceived, format!("{}/flaky", base)),
+                (WebhookEvent::ContactReceived, format!("{}/reject", base)),
+                (WebhookEvent::FormSubmitted, format!("{}/other", base)),
+            ],
+            Some(&secret),
+            Duration::from_secs(5),
+        )
+        .unwrap()
+        .retries(3, Duration::from_millis(10), Duration::from_millis(10));
+
+        let shutdown = Shutdown::new();
+        webhooks.dispatch(&shutdown, WebhookEvent::ContactReceived, &serde_json::json!({ "name": "Ana" }));
+
+        let finished = tokio::time::timeout(Duration::from_secs(5), async {
+            while webhooks.deliveries().iter().any(|d| d.status == DeliveryStatus::Pending) {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await;
+        assert!(finished.is_ok(), "{:?}", webhooks.deliveries());
+
+        let deliveries = webhooks.deliveries();
+        assert_eq!(deliveries.len(), 2);
+        let by_url = |suffix: &str| deliveries.iter().find(|d| d.url.ends_with(suffix)).unwrap();
+        let flaky = by_url("/flaky");
+        assert_eq!((flaky.status, flaky.attempts, flaky.response_status), (DeliveryStatus::Delivered, 2, Some(204)));
+        // Un 400 no se reintenta
+        let rejected = by_url("/reject");
+        assert_eq!((rejected.status, rejected.attempts, rejected.response_status), (DeliveryStatus::Failed, 1, Some(400)));
+
+        let received = stub.received.lock().unwrap().clone();
+        assert_eq!(received.len(), 3);
+        let key = hmac::Key::new(hmac::HMAC_SHA256, b"s3creto");
+        for (event, signature, body) in received {
+            assert_eq!(event, "contact.received");
+            let signature = hex::decode(signature.strip_prefix("sha256=").unwrap()).unwrap();
+            hmac::verify(&key, &body, &signature).unwrap();
+
+            let payload: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(payload["event"], "contact.received");
+            assert_eq!(payload["data"]["name"], "Ana");
+        }
+
+        handle.stop(true).await;
+    }
+}
@@ -12,7 +12,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("   - Nombre: Tu Nombre\n");
 
     // Lista de destinatarios de ejemplo
-    let recipients = vec![
+    let recipients = [
         "usuario1@example.com".to_string(),
         "usuario2@example.com".to_string(),
         "usuario3@example.com".to_string(),
@@ -0,0 +1,319 @@
+use crate::suppression::SuppressionReason;
+
+/// Tipo de rebote de un destinatario
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BounceKind {
+    /// Fallo permanente: la dirección no existe, el dominio no acepta correo...
+    Hard,
+    /// Fallo temporal o entrega retrasada; se puede volver a intentar
+    Soft,
+    /// El destinatario marcó el correo como spam
+    Complaint,
+}
+
+/// Destinatario de un informe de rebote (RFC 3464) o de queja (RFC 5965)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bounce {
+    pub address: String,
+    pub kind: BounceKind,
+    /// Código de estado DSN (`5.1.1`) o tipo de queja (`abuse`)
+    pub status: Option<String>,
+    /// Respuesta del servidor remoto
+    pub diagnostic: Option<String>,
+}
+
+impl Bounce {
+    /// Motivo de supresión; los rebotes temporales no suprimen la dirección
+    pub fn suppression_reason(&self) -> Option<SuppressionReason> {
+        match self.kind {
+            BounceKind::Hard => Some(SuppressionReason::HardBounce),
+            BounceKind::Complaint => Some(SuppressionReason::Complaint),
+            BounceKind::Soft => None,
+        }
+    }
+
+    /// Texto guardado en la lista de supresión
+    pub fn detail(&self) -> Option<String> {
+        match (&self.status, &self.diagnostic) {
+            (Some(status), Some(diagnostic)) => Some(format!("{}: {}", status, diagnostic)),
+            (status, diagnostic) => status.clone().or_else(|| diagnostic.clone()),
+        }
+    }
+}
+
+/// Informe de rebote o de queja
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub bounces: Vec<Bounce>,
+    /// `From` del mensaje original (`text/rfc822-headers` o `message/rfc822`), para comprobar que lo enviamos nosotros
+    pub original_from: Option<String>,
+}
+
+/// Extrae los destinatarios de un mensaje `multipart/report`: `delivery-status` para los rebotes
+/// y `feedback-report` para las quejas. Devuelve `None` si el mensaje no es un informe.
+pub fn parse_report(raw: &[u8]) -> Option<Report> {
+    let text = String::from_utf8_lossy(raw).replace("\r\n", "\n");
+    let (headers, body) = split_entity(&text);
+    let content_type = header(&headers, "content-type")?;
+    if !media_type(content_type).eq_ignore_ascii_case("multipart/report") {
+        return None;
+    }
+    let boundary = param(content_type, "boundary")?;
+
+    let mut bounces = Vec::new();
+    let mut complaint: Option<(String, Vec<String>)> = None;
+    let mut original_to = Vec::new();
+    let mut original_from = None;
+
+    for part in split_parts(body, &boundary) {
+        let (part_headers, part_body) = split_entity(part);
+        let media = header(&part_headers, "content-type").map(media_type).unwrap_or("text/plain").to_ascii_lowercase();
+        match media.as_str() {
+            "message/delivery-status" | "message/global-delivery-status" => bounces.extend(delivery_status(part_body)),
+            "message/feedback-report" => {
+                let fields = fields(part_body);
+                let feedback_type = header(&fields, "feedback-type").unwrap_or("abuse").to_string();
+                let recipients = fields
+                    .iter()
+                    .filter(|(name, _)| name == "original-rcpt-to")
+                    .filter_map(|(_, value)| address(value))
+                    .collect();
+                complaint = Some((feedback_type, recipients));
+            }
+            // Cabeceras del mensaje original: el destinatario de la queja si el informe no lo indica
+            "message/rfc822" | "text/rfc822-headers" => {
+                let (original, _) = split_entity(part_body);
+                original_from = header(&original, "from").and_then(address);
+                original_to = header(&original, "to")
+                    .map(|to| to.split(',').filter_map(address).collect())
+                    .unwrap_or_default();
+            }
+            _ => {}
+        }
+    }
+
+    if let Some((feedback_type, recipients)) = complaint {
+        let recipients = if recipients.is_empty() { original_to } else { recipients };
+        bounces.extend(recipients.into_iter().map(|address| Bounce {
+            address,
+            kind: BounceKind::Complaint,
+            status: Some(feedback_type.clone()),
+            diagnostic: None,
+        }));
+    }
+    Some(Report { bounces, original_from })
+}
+
+/// Campos por destinatario de un `message/delivery-status`: bloques separados por líneas vacías,
+/// el primero con los campos del mensaje y uno por destinatario
+fn delivery_status(body: &str) -> Vec<Bounce> {
+    body.split("\n\n")
+        .map(fields)
+        .filter_map(|fields| {
+            let recipient = header(&fields, "final-recipient").or_else(|| header(&fields, "original-recipient"))?;
+            let address = address(recipient.split_once(';').map_or(recipient, |(_, a)| a))?;
+            let action = header(&fields, "action").unwrap_or_default().to_ascii_lowercase();
+            let status = header(&fields, "status").map(|s| s.split_whitespace().next().unwrap_or_default().to_string());
+
+            let kind = match action.as_str() {
+                "failed" if status.as_deref().is_some_and(|s| s.starts_with('5')) => BounceKind::Hard,
+                "failed" | "delayed" => BounceKind::Soft,
+                // delivered, relayed, expanded
+                _ => return None,
+            };
+            Some(Bounce {
+                address,
+                kind,
+                status,
+                diagnostic: header(&fields, "diagnostic-code").map(|d| d.split_once(';').map_or(d, |(_, d)| d).trim().to_string()),
+            })
+        })
+        .collect()
+}
+
+/// Separa cabeceras y cuerpo en la primera línea vacía
+fn split_entity(text: &str) -> (Vec<(String, String)>, &str) {
+    let text = text.trim_start_matches('\n');
+    match text.split_once("\n\n") {
+        Some((headers, body)) => (fields(headers), body),
+        None => (fields(text), ""),
+    }
+}
+
+/// Campos `Nombre: valor` con los pliegues deshechos y el nombre en minúsculas
+fn fields(block: &str) -> Vec<(String, String)> {
+    let mut fields: Vec<(String, String)> = Vec::new();
+    for line in block.lines() {
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = fields.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            fields.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+    fields
+}
+
+fn header<'a>(fields: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    fields.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+}
+
+fn media_type(content_type: &str) -> &str {
+    content_type.split(';').next().unwrap_or_default().trim()
+}
+
+fn param(content_type: &str, name: &str) -> Option<String> {
+    content_type.split(';').skip(1).find_map(|p| {
+        let (key, value) = p.split_once('=')?;
+        key.trim().eq_ignore_ascii_case(name).then(|| value.trim().trim_matches('"').to_string())
+    })
+}
+
+/// Partes de un multipart, sin el preámbulo ni el epílogo
+fn split_parts<'a>(body: &'a str, boundary: &str) -> Vec<&'a str> {
+    let delimiter = format!("--{}", boundary);
+    let mut parts = Vec::new();
+    let mut current: Option<usize> = None;
+    let mut offset = 0;
+    for line in body.split_inclusive('\n') {
+        let trimmed = line.trim_end();
+        if trimmed == delimiter || trimmed == format!("{}--", delimiter) {
+            if let Some(start) = current {
+                parts.push(&body[start..offset]);
+            }
+            if trimmed != delimiter {
+                return parts;
+            }
+            current = Some(offset + line.len());
+        }
+        offset += line.len();
+    }
+    parts
+}
+
+/// Dirección de `Ana <ana@example.com>` o `ana@example.com`
+fn address(value: &str) -> Option<String> {
+    let value = match (value.find('<'), value.rfind('>')) {
+        (Some(start), Some(end)) if start < end => &value[start + 1..end],
+        _ => value,
+    };
+    let value = value.trim();
+    value.contains('@').then(|| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DSN: &str = "From: MAILER-DAEMON@mx.tusitio.com\r\n\
+To: no-reply@tusitio.com\r\n\
+Subject: Undelivered Mail Returned to Sender\r\n\
+MIME-Version: 1.0\r\n\
+Content-Type: multipart/report; report-type=delivery-status;\r\n\tboundary=\"B0UND\"\r\n\
+\r\n\
+This is a MIME-encapsulated message.\r\n\
+\r\n\
+--B0UND\r\n\
+Content-Type: text/plain\r\n\
+\r\n\
+I'm sorry to have to inform you that your message could not be delivered.\r\n\
+\r\n\
+--B0UND\r\n\
+Content-Type: message/delivery-status\r\n\
+\r\n\
+Reporting-MTA: dns; mx.tusitio.com\r\n\
+Arrival-Date: Mon, 19 Oct 2026 14:05:00 +0000\r\n\
+\r\n\
+Final-Recipient: rfc822; nadie@example.com\r\n\
+Original-Recipient: rfc822;nadie@example.com\r\n\
+Action: failed\r\n\
+Status: 5.1.1\r\n\
+Diagnostic-Code: smtp; 550 5.1.1 <nadie@example.com>: Recipient address\r\n\
+\trejected: User unknown\r\n\
+\r\n\
+Final-Recipient: rfc822; lleno@example.com\r\n\
+Action: delayed\r\n\
+Status: 4.2.2\r\n\
+\r\n\
+Final-Recipient: rfc822; ok@example.com\r\n\
+Action: delivered\r\n\
+Status: 2.0.0\r\n\
+\r\n\
+--B0UND\r\n\
+Content-Type: text/rfc822-headers\r\n\
+\r\n\
+From: Tu Sitio <no-reply@tusitio.com>\r\n\
+To: nadie@example.com\r\n\
+Subject: Hemos recibido tu solicitud\r\n\
+\r\n\
+--B0UND--\r\n";
+
+    const ARF: &str = "From: feedback@mail.example.net\r\n\
+Content-Type: multipart/report; report-type=feedback-report; boundary=\"part\"\r\n\
+\r\n\
+--part\r\n\
+Content-Type: text/plain\r\n\
+\r\n\
+This is an email abuse report.\r\n\
+--part\r\n\
+Content-Type: message/feedback-report\r\n\
+\r\n\
+Feedback-Type: abuse\r\n\
+User-Agent: ExampleFBL/1.0\r\n\
+Version: 1\r\n\
+--part\r\n\
+Content-Type: message/rfc822\r\n\
+\r\n\
+From: no-reply@tusitio.com\r\n\
+To: Luis <Luis@Example.net>\r\n\
+Subject: Hemos recibido tu solicitud\r\n\
+\r\n\
+Hola\r\n\
+--part--\r\n";
+
+    #[test]
+    fn parses_delivery_status_notifications() {
+        let report = parse_report(DSN.as_bytes()).unwrap();
+        assert_eq!(report.original_from.as_deref(), Some("no-reply@tusitio.com"));
+        let bounces = report.bounces;
+        assert_eq!(bounces.len(), 2);
+
+        assert_eq!(bounces[0].address, "nadie@example.com");
+        assert_eq!(bounces[0].kind, BounceKind::Hard);
+        assert_eq!(bounces[0].suppression_reason(), Some(SuppressionReason::HardBounce));
+        assert_eq!(
+            bounces[0].detail().as_deref(),
+            Some("5.1.1: 550 5.1.1 <nadie@example.com>: Recipient address rejected: User unknown")
+        );
+
+        assert_eq!(bounces[1].address, "lleno@example.com");
+        assert_eq!(bounces[1].kind, BounceKind::Soft);
+        assert_eq!(bounces[1].suppression_reason(), None);
+    }
+
+    #[test]
+    fn parses_complaints_from_the_original_message() {
+        let report = parse_report(ARF.as_bytes()).unwrap();
+        assert_eq!(report.original_from.as_deref(), Some("no-reply@tusitio.com"));
+        assert_eq!(
+            report.bounces,
+            [Bounce {
+                address: "Luis@Example.net".to_string(),
+                kind: BounceKind::Complaint,
+                status: Some("abuse".to_string()),
+                diagnostic: None,
+            }]
+        );
+
+        let with_recipient = ARF.replace("Version: 1\r\n", "Version: 1\r\nOriginal-Rcpt-To: <luis@example.net>\r\n");
+        assert_eq!(parse_report(with_recipient.as_bytes()).unwrap().bounces[0].address, "luis@example.net");
+    }
+
+    #[test]
+    fn ignores_messages_that_are_not_reports() {
+        assert_eq!(parse_report(b"From: ana@example.com\r\nSubject: Hola\r\n\r\nHola"), None);
+        assert_eq!(parse_report(b"Content-Type: multipart/mixed; boundary=x\r\n\r\n--x\r\n\r\nhola\r\n--x--\r\n"), None);
+    }
+}
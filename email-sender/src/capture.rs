@@ -0,0 +1,36 @@
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+
+/// Correo retenido por el transporte de captura en lugar de enviarse
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CapturedEmail {
+    pub from: String,
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+    pub is_html: bool,
+}
+
+/// Buzón en memoria compartido por todos los clones de un [`crate::EmailSender`] de captura
+#[derive(Debug, Clone, Default)]
+pub struct MailCapture(Arc<Mutex<Vec<CapturedEmail>>>);
+
+impl MailCapture {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Correos capturados hasta ahora, en orden de envío
+    pub fn emails(&self) -> Vec<CapturedEmail> {
+        self.0.lock().unwrap().clone()
+    }
+
+    pub fn clear(&self) {
+        self.0.lock().unwrap().clear();
+    }
+
+    pub(crate) fn push(&self, email: CapturedEmail) {
+        self.0.lock().unwrap().push(email);
+    }
+}
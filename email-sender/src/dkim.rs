@@ -0,0 +1,416 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ring::digest::{digest, SHA256};
+use ring::rand::SystemRandom;
+use ring::signature::{Ed25519KeyPair, KeyPair, RsaKeyPair, RSA_PKCS1_SHA256};
+
+/// Cabeceras firmadas por defecto; las que no aparezcan en el mensaje se omiten
+pub const DEFAULT_SIGNED_HEADERS: &[&str] = &[
+    "From", "To", "Cc", "Reply-To", "Subject", "Date", "Message-ID", "MIME-Version", "Content-Type",
+];
+
+/// Clave privada de firma
+enum SigningKey {
+    Rsa(RsaKeyPair),
+    Ed25519(Ed25519KeyPair),
+}
+
+/// Firma DKIM (RFC 6376) con canonicalización relaxed/relaxed.
+/// Admite RSA-SHA256 y Ed25519-SHA256 (RFC 8463).
+pub struct DkimSigner {
+    domain: String,
+    selector: String,
+    headers: Vec<String>,
+    key: SigningKey,
+}
+
+impl fmt::Debug for DkimSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DkimSigner")
+            .field("domain", &self.domain)
+            .field("selector", &self.selector)
+            .field("algorithm", &self.algorithm())
+            .field("headers", &self.headers)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DkimSigner {
+    /// Carga la clave privada de un archivo PEM: PKCS#8 (RSA o Ed25519) o PKCS#1 (`RSA PRIVATE KEY`).
+    /// `headers` vacío firma [`DEFAULT_SIGNED_HEADERS`]; `From` se firma siempre.
+    pub fn from_file(domain: &str, selector: &str, key_path: &Path, headers: &[String]) -> Result<Self> {
+        let pem = std::fs::read_to_string(key_path)
+            .with_context(|| format!("No se pudo leer la clave DKIM {}", key_path.display()))?;
+        let key = parse_key(&pem).with_context(|| format!("Clave DKIM inválida en {}", key_path.display()))?;
+        Self::new(domain, selector, key, headers)
+    }
+
+    fn new(domain: &str, selector: &str, key: SigningKey, headers: &[String]) -> Result<Self> {
+        if domain.trim().is_empty() || selector.trim().is_empty() {
+            return Err(anyhow!("El dominio y el selector DKIM no pueden estar vacíos"));
+        }
+
+        let names: Vec<String> = if headers.is_empty() {
+            DEFAULT_SIGNED_HEADERS.iter().map(|h| h.to_ascii_lowercase()).collect()
+        } else {
+            headers.iter().map(|h| h.trim().to_ascii_lowercase()).filter(|h| !h.is_empty()).collect()
+        };
+        // Sin repetidos, en el orden de su primera aparición
+        let mut seen = HashSet::new();
+        let mut signed: Vec<String> = names.into_iter().filter(|h| seen.insert(h.clone())).collect();
+        if !seen.contains("from") {
+            signed.insert(0, "from".to_string());
+        }
+
+        Ok(Self {
+            domain: domain.trim().to_string(),
+            selector: selector.trim().to_string(),
+            headers: signed,
+            key,
+        })
+    }
+
+    /// Valor de la etiqueta `a=`
+    pub fn algorithm(&self) -> &'static str {
+        match self.key {
+            SigningKey::Rsa(_) => "rsa-sha256",
+            SigningKey::Ed25519(_) => "ed25519-sha256",
+        }
+    }
+
+    /// Clave pública en el formato que usa la etiqueta `p=` del registro DNS:
+    /// `RSAPublicKey` DER para RSA, los 32 bytes de la clave para Ed25519
+    pub fn public_key(&self) -> &[u8] {
+        match &self.key {
+            SigningKey::Rsa(key) => key.public().as_ref(),
+            SigningKey::Ed25519(key) => key.public_key().as_ref(),
+        }
+    }
+
+    /// Devuelve el mensaje con la cabecera `DKIM-Signature` delante
+    pub fn sign(&self, message: &[u8]) -> Result<Vec<u8>> {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        self.sign_at(message, timestamp)
+    }
+
+    fn sign_at(&self, message: &[u8], timestamp: u64) -> Result<Vec<u8>> {
+        let (header_block, body) = split_message(message)?;
+        let headers = parse_headers(header_block);
+        let body_hash = BASE64.encode(digest(&SHA256, &canonicalize_body(body)));
+
+        // Solo se listan en h= las cabeceras presentes; de cada una se firma la última aparición
+        let present: Vec<&str> = self
+            .headers
+            .iter()
+            .map(String::as_str)
+            .filter(|name| headers.iter().any(|(h, _)| h.eq_ignore_ascii_case(name)))
+            .collect();
+        let mut data = String::new();
+        for name in &present {
+            if let Some((_, raw)) = headers.iter().rev().find(|(h, _)| h.eq_ignore_ascii_case(name)) {
+                data.push_str(&canonicalize_header(raw));
+                data.push_str("\r\n");
+            }
+        }
+
+        let unsigned = format!(
+            "v=1; a={}; c=relaxed/relaxed; d={}; s={}; t={}; h={}; bh={}; b=",
+            self.algorithm(),
+            self.domain,
+            self.selector,
+            timestamp,
+            present.join(":"),
+            body_hash
+        );
+        // La propia cabecera se firma con b= vacío y sin el CRLF final
+        data.push_str(&canonicalize_header(&format!("DKIM-Signature: {}", unsigned)));
+
+        let signature = match &self.key {
+            SigningKey::Rsa(key) => {
+                let mut signature = vec![0; key.public().modulus_len()];
+                key.sign(&RSA_PKCS1_SHA256, &SystemRandom::new(), data.as_bytes(), &mut signature)
+                    .map_err(|_| anyhow!("No se pudo firmar el mensaje con la clave RSA"))?;
+                signature
+            }
+            // Ed25519-SHA256 firma el hash SHA-256 de los datos (RFC 8463)
+            SigningKey::Ed25519(key) => key.sign(digest(&SHA256, data.as_bytes()).as_ref()).as_ref().to_vec(),
+        };
+
+        let mut signed = format!("DKIM-Signature: {}{}\r\n", unsigned, BASE64.encode(signature)).into_bytes();
+        signed.extend_from_slice(message);
+        Ok(signed)
+    }
+}
+
+fn parse_key(pem: &str) -> Result<SigningKey> {
+    let (label, der) = decode_pem(pem)?;
+    match label.as_str() {
+        "RSA PRIVATE KEY" => RsaKeyPair::from_der(&der)
+            .map(SigningKey::Rsa)
+            .map_err(|e| anyhow!("clave RSA PKCS#1 no válida: {}", e)),
+        "PRIVATE KEY" => match RsaKeyPair::from_pkcs8(&der) {
+            Ok(key) => Ok(SigningKey::Rsa(key)),
+            Err(_) => Ed25519KeyPair::from_pkcs8_maybe_unchecked(&der)
+                .map(SigningKey::Ed25519)
+                .map_err(|e| anyhow!("la clave PKCS#8 no es RSA ni Ed25519: {}", e)),
+        },
+        other => Err(anyhow!("tipo de PEM no soportado '{}'", other)),
+    }
+}
+
+/// Primer bloque PEM: su etiqueta y el DER que contiene
+fn decode_pem(pem: &str) -> Result<(String, Vec<u8>)> {
+    let mut lines = pem.lines().map(str::trim).skip_while(|l| !l.starts_with("-----BEGIN "));
+    let label = lines
+        .next()
+        .and_then(|l| l.strip_prefix("-----BEGIN "))
+        .and_then(|l| l.strip_suffix("-----"))
+        .ok_or_else(|| anyhow!("no contiene ningún bloque PEM"))?
+        .to_string();
+    let encoded: String = lines.take_while(|l| !l.starts_with("-----END ")).collect();
+    let der = BASE64.decode(encoded).map_err(|e| anyhow!("base64 inválido: {}", e))?;
+    Ok((label, der))
+}
+
+/// Separa cabeceras y cuerpo en la primera línea vacía
+fn split_message(message: &[u8]) -> Result<(&str, &[u8])> {
+    let end = message.windows(4).position(|w| w == b"\r\n\r\n");
+    let (headers, body) = match end {
+        Some(i) => (&message[..i + 2], &message[i + 4..]),
+        None => (message, &[][..]),
+    };
+    // Las cabeceras que genera lettre son ASCII (RFC 2047 para el resto); firmar otras cabeceras
+    // que las del mensaje daría una firma que ningún receptor puede verificar
+    let headers = std::str::from_utf8(headers).map_err(|_| anyhow!("Las cabeceras del mensaje no son UTF-8 válido"))?;
+    Ok((headers, body))
+}
+
+/// Cabeceras en orden, como `(nombre, texto completo con los pliegues)`
+fn parse_headers(block: &str) -> Vec<(&str, &str)> {
+    let mut headers = Vec::new();
+    let mut start = None;
+    let mut offset = 0;
+    for line in block.split_inclusive("\r\n") {
+        if !line.starts_with([' ', '\t']) {
+            if let Some(begin) = start {
+                headers.push(&block[begin..offset]);
+            }
+            start = Some(offset);
+        }
+        offset += line.len();
+    }
+    if let Some(begin) = start {
+        headers.push(&block[begin..offset]);
+    }
+
+    headers
+        .into_iter()
+        .map(|raw| raw.trim_end_matches("\r\n"))
+        .filter_map(|raw| raw.split_once(':').map(|(name, _)| (name.trim(), raw)))
+        .collect()
+}
+
+/// Canonicalización relaxed de una cabecera, sin CRLF final (RFC 6376 §3.4.2)
+fn canonicalize_header(raw: &str) -> String {
+    let (name, value) = raw.split_once(':').unwrap_or((raw, ""));
+    let unfolded = value.replace("\r\n", "");
+    let value = unfolded.split([' ', '\t']).filter(|s| !s.is_empty()).collect::<Vec<_>>().join(" ");
+    format!("{}:{}", name.trim().to_ascii_lowercase(), value)
+}
+
+/// Canonicalización relaxed del cuerpo (RFC 6376 §3.4.4)
+fn canonicalize_body(body: &[u8]) -> Vec<u8> {
+    let mut lines: Vec<Vec<u8>> = body
+        .split(|&b| b == b'\n')
+        .map(|line| {
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            let mut out = Vec::with_capacity(line.len());
+            let mut in_space = false;
+            for &b in line {
+                if b == b' ' || b == b'\t' {
+                    in_space = true;
+                } else {
+                    if in_space {
+                        out.push(b' ');
+                        in_space = false;
+                    }
+                    out.push(b);
+                }
+            }
+            out
+        })
+        .collect();
+    while lines.last().is_some_and(|l| l.is_empty()) {
+        lines.pop();
+    }
+
+    let mut canonical = Vec::with_capacity(body.len());
+    for line in lines {
+        canonical.extend_from_slice(&line);
+        canonical.extend_from_slice(b"\r\n");
+    }
+    canonical
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::signature::{UnparsedPublicKey, ED25519, RSA_PKCS1_2048_8192_SHA256};
+
+    const MESSAGE: &[u8] = b"From: no-reply@tusitio.com\r\nTo: ops@tusitio.com\r\nSubject: Nueva  solicitud\r\n\tde contacto\r\nDate: Mon, 19 Oct 2026 14:05:00 +0000\r\nContent-Type: text/plain\r\n\r\nHola   equipo \r\n\r\n\r\n";
+
+    fn signer(key_file: &str, headers: &[&str]) -> DkimSigner {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata").join(key_file);
+        let headers: Vec<String> = headers.iter().map(|h| h.to_string()).collect();
+        DkimSigner::from_file("tusitio.com", "mail", &path, &headers).unwrap()
+    }
+
+    fn tags(value: &str) -> Vec<(String, String)> {
+        value
+            .split(';')
+            .filter_map(|tag| tag.split_once('='))
+            .map(|(k, v)| (k.trim().to_string(), v.split_whitespace().collect()))
+            .collect()
+    }
+
+    /// Verificación independiente de la firma: rehace los hashes a partir del mensaje firmado
+    fn verify(signed: &[u8], public_key: &[u8]) -> Result<(), String> {
+        let (header_block, body) = split_message(signed).map_err(|e| e.to_string())?;
+        let headers = parse_headers(header_block);
+        let (_, dkim) = headers.first().filter(|(name, _)| *name == "DKIM-Signature").ok_or("falta la firma")?;
+        let value = dkim.split_once(':').unwrap().1;
+        let tags = tags(value);
+        let tag = |name: &str| tags.iter().find(|(k, _)| k == name).map(|(_, v)| v.clone()).unwrap_or_default();
+
+        if tag("bh") != BASE64.encode(digest(&SHA256, &canonicalize_body(body))) {
+            return Err("el hash del cuerpo no coincide".to_string());
+        }
+
+        let mut data = String::new();
+        for name in tag("h").split(':') {
+            let (_, raw) = headers[1..].iter().rev().find(|(h, _)| h.eq_ignore_ascii_case(name)).unwrap();
+            data.push_str(&canonicalize_header(raw));
+            data.push_str("\r\n");
+        }
+        let b = tag("b");
+        let without_b = dkim.replace(&b, "");
+        data.push_str(&canonicalize_header(&without_b));
+
+        let signature = BASE64.decode(b).map_err(|e| e.to_string())?;
+        match tag("a").as_str() {
+            "rsa-sha256" => UnparsedPublicKey::new(&RSA_PKCS1_2048_8192_SHA256, public_key).verify(data.as_bytes(), &signature),
+            "ed25519-sha256" => {
+                UnparsedPublicKey::new(&ED25519, public_key).verify(digest(&SHA256, data.as_bytes()).as_ref(), &signature)
+            }
+            other => return Err(format!("algoritmo desconocido {}", other)),
+        }
+        .map_err(|_| "firma inválida".to_string())
+    }
+
+    #[test]
+    fn canonicalizes_like_the_rfc_example() {
+        // RFC 6376 §3.4.5
+        assert_eq!(canonicalize_header("A: X"), "a:X");
+        assert_eq!(canonicalize_header("B : Y\t\r\n\tZ  "), "b:Y Z");
+        assert_eq!(canonicalize_body(b" C \r\nD \t E\r\n\r\n\r\n"), b" C\r\nD E\r\n");
+        assert_eq!(canonicalize_body(b""), b"");
+    }
+
+    #[test]
+    fn matches_an_independent_implementation() {
+        // Bytes canónicos escritos a mano según RFC 6376 §3.4.2 y §3.4.4; bh= y b= calculados aparte
+        // con hashlib y la Ed25519 de `cryptography` en Python, que es determinista
+        assert_eq!(canonicalize_body(split_message(MESSAGE).unwrap().1), b"Hola equipo\r\n");
+        let signed = signer("dkim-ed25519.pem", &[]).sign_at(MESSAGE, 1_792_418_700).unwrap();
+        let text = String::from_utf8(signed).unwrap();
+        let header = text.lines().next().unwrap();
+        assert_eq!(
+            header,
+            "DKIM-Signature: v=1; a=ed25519-sha256; c=relaxed/relaxed; d=tusitio.com; s=mail; t=1792418700; \
+             h=from:to:subject:date:content-type; bh=de/TSYpvIBV1G+RsJHbjbHMXHrOoChu6CuuAqC00WqM=; \
+             b=0KybMG6CejiID7Hb8tRzgEGAafw6iZD4Zrgp6xMEkfPcttRm96hSGCiZUbsK6ZR2oL2MfeXJnXtbpg+gVX3BBw=="
+        );
+    }
+
+    #[test]
+    fn signatures_verify_against_the_public_key() {
+        for key_file in ["dkim-rsa.pem", "dkim-ed25519.pem"] {
+            let signer = signer(key_file, &[]);
+            let signed = signer.sign_at(MESSAGE, 1_792_418_700).unwrap();
+            let text = String::from_utf8(signed.clone()).unwrap();
+
+            assert!(text.starts_with("DKIM-Signature: v=1; a="), "{}", key_file);
+            assert!(text.contains("d=tusitio.com; s=mail; t=1792418700; h=from:to:subject:date:content-type;"));
+            assert!(text.ends_with(std::str::from_utf8(MESSAGE).unwrap()));
+            verify(&signed, signer.public_key()).unwrap();
+
+            // Cualquier cambio en una cabecera firmada o en el cuerpo invalida la firma
+            let tampered = text.replace("Nueva  solicitud", "Otra solicitud");
+            assert!(verify(tampered.as_bytes(), signer.public_key()).is_err());
+            let tampered = text.replace("Hola", "Adiós");
+            assert!(verify(tampered.as_bytes(), signer.public_key()).is_err());
+        }
+    }
+
+    #[test]
+    fn signs_only_the_configured_headers() {
+        let signer = signer("dkim-ed25519.pem", &["Subject", "X-Missing"]);
+        assert_eq!(signer.algorithm(), "ed25519-sha256");
+
+        let signed = signer.sign(MESSAGE).unwrap();
+        let text = String::from_utf8(signed.clone()).unwrap();
+        assert!(text.contains("; h=from:subject;"));
+        // Las cabeceras sin firmar pueden cambiar en tránsito
+        let relayed = text.replace("To: ops@tusitio.com", "To: otro@tusitio.com");
+        verify(relayed.as_bytes(), signer.public_key()).unwrap();
+    }
+
+    #[test]
+    fn deduplicates_signed_headers_keeping_their_order() {
+        let listed = signer("dkim-ed25519.pem", &["Subject", "To", "subject", "From", "To"]);
+        assert_eq!(listed.headers, ["subject", "to", "from"]);
+        // From se firma siempre, aunque no esté en la lista
+        let without_from = signer("dkim-ed25519.pem", &["To", "Subject", "to"]);
+        assert_eq!(without_from.headers, ["from", "to", "subject"]);
+
+        let error = without_from.sign(b"From: no-reply@tusitio.com\r\nSubject: \xff\r\n\r\nHola").unwrap_err();
+        assert!(error.to_string().contains("UTF-8"));
+    }
+
+    #[tokio::test]
+    async fn signs_the_mail_written_by_the_file_transport() {
+        let dir = std::env::temp_dir().join(format!("dkim-file-{}", std::process::id()));
+        let signer = signer("dkim-ed25519.pem", &[]);
+        let public_key = signer.public_key().to_vec();
+        let sender = crate::EmailSender::file(&dir, "no-reply@tusitio.com").unwrap().with_dkim(signer);
+        sender
+            .send_simple_email(&["ops@tusitio.com".to_string()], "Nueva solicitud", "Hola equipo")
+            .await
+            .unwrap();
+
+        let files: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|e| e == "eml"))
+            .collect();
+        assert_eq!(files.len(), 1);
+        let raw = std::fs::read(&files[0]).unwrap();
+        assert!(raw.starts_with(b"DKIM-Signature: v=1; a=ed25519-sha256;"));
+        verify(&raw, &public_key).unwrap();
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_unusable_keys() {
+        let error = parse_key("-----BEGIN CERTIFICATE-----\nAAAA\n-----END CERTIFICATE-----\n").err().unwrap();
+        assert!(error.to_string().contains("CERTIFICATE"));
+        assert!(parse_key("no es un PEM").is_err());
+    }
+}
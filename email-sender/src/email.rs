@@ -1,8 +1,21 @@
 use anyhow::{anyhow, Result};
-use lettre::{Message, SmtpTransport, Transport};
+use lettre::{FileTransport, Message, SmtpTransport, Transport};
 use lettre::transport::smtp::authentication::Credentials;
-use lettre::message::{header::ContentType, MultiPart, SinglePart};
+use lettre::message::{header::ContentType, Mailbox, MultiPart, SinglePart};
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, error, instrument, warn};
+
+use crate::capture::{CapturedEmail, MailCapture};
+use crate::dkim::DkimSigner;
+use crate::error::EmailError;
+use crate::probe::{run_probe, SmtpProbe};
+use crate::suppression::SuppressionList;
+
+/// Tiempo máximo de cada operación de la sonda SMTP
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// Estructura para configurar el contenido del email
 #[derive(Debug, Serialize, Deserialize)]
@@ -12,10 +25,27 @@ pub struct EmailContent {
     pub is_html: bool,
 }
 
+/// Destino de los correos
+#[derive(Clone)]
+enum Mailer {
+    Smtp {
+        transport: SmtpTransport,
+        server: String,
+        credentials: Credentials,
+    },
+    /// Escribe cada correo como `.eml` en un directorio
+    File { dir: PathBuf },
+    /// Guarda los correos en memoria
+    Capture(MailCapture),
+}
+
 /// Struct que encapsula configuración y lógica de envío de correos
+#[derive(Clone)]
 pub struct EmailSender {
-    mailer: SmtpTransport,
+    mailer: Mailer,
     from_email: String,
+    dkim: Option<Arc<DkimSigner>>,
+    suppressions: SuppressionList,
 }
 
 impl EmailSender {
@@ -27,39 +57,142 @@ impl EmailSender {
         from_email: &str,
     ) -> Result<Self> {
         let creds = Credentials::new(smtp_user.to_string(), smtp_password.to_string());
-        let mailer = SmtpTransport::relay(smtp_server)?
-            .credentials(creds)
+        let transport = SmtpTransport::relay(smtp_server)?
+            .credentials(creds.clone())
             .build();
 
         Ok(Self {
-            mailer,
+            mailer: Mailer::Smtp {
+                transport,
+                server: smtp_server.to_string(),
+                credentials: creds,
+            },
+            from_email: from_email.to_string(),
+            dkim: None,
+            suppressions: SuppressionList::new(),
+        })
+    }
+
+    /// Crea un enviador que guarda cada correo como archivo `.eml` en `dir` en lugar de enviarlo
+    pub fn file(dir: impl AsRef<Path>, from_email: &str) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| anyhow!("No se pudo crear el directorio de correo {}: {}", dir.display(), e))?;
+
+        Ok(Self {
+            mailer: Mailer::File { dir },
             from_email: from_email.to_string(),
+            dkim: None,
+            suppressions: SuppressionList::new(),
         })
     }
 
-    /// Envía un email a múltiples destinatarios
+    /// Crea un enviador que retiene los correos en memoria; se consultan con [`EmailSender::captured`]
+    pub fn capture(from_email: &str) -> Self {
+        Self {
+            mailer: Mailer::Capture(MailCapture::new()),
+            from_email: from_email.to_string(),
+            dkim: None,
+            suppressions: SuppressionList::new(),
+        }
+    }
+
+    /// Firma con DKIM los correos que salen por SMTP o a archivo; el transporte de captura no firma
+    pub fn with_dkim(mut self, signer: DkimSigner) -> Self {
+        self.dkim = Some(Arc::new(signer));
+        self
+    }
+
+    /// Lista de direcciones a las que no se envía; por defecto una vacía en memoria
+    pub fn with_suppressions(mut self, suppressions: SuppressionList) -> Self {
+        self.suppressions = suppressions;
+        self
+    }
+
+    pub fn suppressions(&self) -> &SuppressionList {
+        &self.suppressions
+    }
+
+    /// Firmante DKIM configurado, si lo hay
+    pub fn dkim(&self) -> Option<&DkimSigner> {
+        self.dkim.as_deref()
+    }
+
+    /// Buzón de un enviador creado con [`EmailSender::capture`]
+    pub fn captured(&self) -> Option<&MailCapture> {
+        match &self.mailer {
+            Mailer::Capture(capture) => Some(capture),
+            _ => None,
+        }
+    }
+
+    /// Nombre del transporte usado, para logs y métricas
+    pub fn transport_name(&self) -> &'static str {
+        match self.mailer {
+            Mailer::Smtp { .. } => "smtp",
+            Mailer::File { .. } => "file",
+            Mailer::Capture(_) => "capture",
+        }
+    }
+
+    /// Descripción del destino: servidor SMTP o directorio de correo
+    pub fn destination(&self) -> String {
+        match &self.mailer {
+            Mailer::Smtp { server, .. } => server.clone(),
+            Mailer::File { dir } => dir.display().to_string(),
+            Mailer::Capture(_) => "memoria".to_string(),
+        }
+    }
+
+    /// Envía un email a múltiples destinatarios.
+    /// Los suprimidos se saltan; solo es un error si lo están todos.
     pub async fn send_email_to_multiple(
         &self,
         recipients: &[String],
         content: &EmailContent,
-    ) -> Result<()> {
+    ) -> Result<(), EmailError> {
+        let mut refused = None;
+        let mut sent = 0;
         for recipient in recipients {
+            if let Err(e) = self.check_suppressed(recipient) {
+                refused.get_or_insert(e);
+                continue;
+            }
             self.send_single_email(recipient, content).await?;
+            sent += 1;
+        }
+
+        match refused {
+            Some(e) if sent == 0 => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_suppressed(&self, recipient: &str) -> Result<(), EmailError> {
+        match self.suppressions.get(recipient) {
+            Some(entry) => {
+                warn!(recipient, reason = %entry.reason, "Destinatario suprimido, no se le envía el correo");
+                Err(EmailError::Suppressed {
+                    address: recipient.to_string(),
+                    reason: entry.reason,
+                })
+            }
+            None => Ok(()),
         }
-        Ok(())
     }
 
     /// Envía un email a un solo destinatario
+    #[instrument(name = "smtp.send", skip(self, content), fields(subject = %content.subject, transport = self.transport_name()))]
     pub async fn send_single_email(
         &self,
         recipient: &str,
         content: &EmailContent,
-    ) -> Result<()> {
-        let from_address = self.from_email.clone();
+    ) -> Result<(), EmailError> {
+        self.check_suppressed(recipient)?;
 
         let email_builder = Message::builder()
-            .from(from_address.parse().map_err(|_| anyhow!("Email 'from' inválido"))?)
-            .to(recipient.parse().map_err(|_| anyhow!("Email 'to' inválido"))?)
+            .from(parse_mailbox(&self.from_email)?)
+            .to(parse_mailbox(recipient)?)
             .subject(&content.subject);
 
         let email = if content.is_html {
@@ -77,10 +210,43 @@ impl EmailSender {
             email_builder.body(content.body.clone())?
         };
 
-        self.mailer
-            .send(&email)
-            .map_err(|e| anyhow!("Fallo al enviar el correo a {}: {}", recipient, e))?;
+        // Con DKIM se envía el mensaje ya serializado con la firma delante
+        let signed = match &self.dkim {
+            Some(signer) if !matches!(self.mailer, Mailer::Capture(_)) => Some(
+                signer
+                    .sign(&email.formatted())
+                    .map_err(|e| EmailError::Build(format!("firma DKIM: {}", e)))?,
+            ),
+            _ => None,
+        };
 
+        let result = match (&self.mailer, &signed) {
+            (Mailer::Smtp { transport, .. }, Some(raw)) => {
+                transport.send_raw(email.envelope(), raw).map(drop).map_err(EmailError::from)
+            }
+            (Mailer::Smtp { transport, .. }, None) => transport.send(&email).map(drop).map_err(EmailError::from),
+            (Mailer::File { dir }, Some(raw)) => {
+                FileTransport::new(dir).send_raw(email.envelope(), raw).map(drop).map_err(EmailError::from)
+            }
+            (Mailer::File { dir }, None) => FileTransport::new(dir).send(&email).map(drop).map_err(EmailError::from),
+            (Mailer::Capture(capture), _) => {
+                capture.push(CapturedEmail {
+                    from: self.from_email.clone(),
+                    to: recipient.to_string(),
+                    subject: content.subject.clone(),
+                    body: content.body.clone(),
+                    is_html: content.is_html,
+                });
+                Ok(())
+            }
+        };
+
+        if let Err(e) = &result {
+            error!(recipient, error = %e, retryable = e.is_retryable(), "Fallo al enviar el correo");
+        }
+        result?;
+
+        debug!(recipient, destination = %self.destination(), "Correo entregado al transporte");
         Ok(())
     }
 
@@ -90,7 +256,7 @@ impl EmailSender {
         recipients: &[String],
         subject: &str,
         body: &str,
-    ) -> Result<()> {
+    ) -> Result<(), EmailError> {
         let content = EmailContent {
             subject: subject.to_string(),
             body: body.to_string(),
@@ -106,7 +272,7 @@ impl EmailSender {
         recipients: &[String],
         subject: &str,
         html_body: &str,
-    ) -> Result<()> {
+    ) -> Result<(), EmailError> {
         let content = EmailContent {
             subject: subject.to_string(),
             body: html_body.to_string(),
@@ -116,19 +282,61 @@ impl EmailSender {
         self.send_email_to_multiple(recipients, &content).await
     }
 
-    /// Verifica la conexión SMTP
-    pub fn test_connection(&self) -> Result<()> {
+    /// Comprueba el relay SMTP sin enviar ningún correo: conecta, negocia TLS,
+    /// autentica y cierra con NOOP/QUIT. Es bloqueante.
+    /// Los transportes locales no tienen relay: la sonda vuelve sin conectar y con error.
+    pub fn probe(&self) -> SmtpProbe {
+        match &self.mailer {
+            Mailer::Smtp { server, credentials, .. } => run_probe(server, credentials, PROBE_TIMEOUT),
+            _ => SmtpProbe {
+                reachable: false,
+                encrypted: false,
+                tls_version: None,
+                auth_ok: false,
+                banner: None,
+                latency_ms: 0,
+                error: Some(format!("El transporte '{}' no usa SMTP", self.transport_name())),
+            },
+        }
+    }
+
+    /// Verifica el transporte: por SMTP envía un correo real a `from_email`, con el transporte de archivo
+    /// comprueba que se puede escribir en el directorio, y el de captura siempre está disponible.
+    /// Para comprobaciones periódicas del relay usa [`EmailSender::probe`], que no envía nada.
+    pub fn test_connection(&self) -> Result<(), EmailError> {
+        let transport = match &self.mailer {
+            Mailer::Smtp { transport, .. } => transport,
+            Mailer::File { dir } => return check_writable(dir),
+            Mailer::Capture(_) => return Ok(()),
+        };
+
         // Crear un email de prueba simple
         let test_email = Message::builder()
-            .from(self.from_email.parse().map_err(|_| anyhow!("Email 'from' inválido"))?)
-            .to(self.from_email.parse().map_err(|_| anyhow!("Email 'to' inválido"))?)
+            .from(parse_mailbox(&self.from_email)?)
+            .to(parse_mailbox(&self.from_email)?)
             .subject("Test de conexión")
             .body("Este es un email de prueba para verificar la conexión SMTP.".to_string())?;
-
-        self.mailer
-            .send(&test_email)
-            .map_err(|e| anyhow!("Fallo en la prueba de conexión SMTP: {}", e))?;
+        transport.send(&test_email)?;
 
         Ok(())
     }
 }
+
+/// Crea y borra un archivo en `dir`, como haría el transporte de archivo al guardar un correo
+fn check_writable(dir: &Path) -> Result<(), EmailError> {
+    let probe = dir.join(format!(".test-connection-{}", std::process::id()));
+    std::fs::write(&probe, b"")
+        .and_then(|()| std::fs::remove_file(&probe))
+        .map_err(|e| EmailError::Connection(format!("No se puede escribir en {}: {}", dir.display(), e)))
+}
+
+/// Indica si `address` es una única dirección `usuario@dominio` válida, sin nombre visible ni lista
+pub fn is_valid_address(address: &str) -> bool {
+    address.parse::<lettre::Address>().is_ok()
+}
+
+fn parse_mailbox(address: &str) -> Result<Mailbox, EmailError> {
+    address
+        .parse()
+        .map_err(|e| EmailError::invalid_address(address, e))
+}
@@ -0,0 +1,264 @@
+use std::fmt;
+
+use lettre::address::AddressError;
+use lettre::transport::smtp;
+use lettre::transport::smtp::response::Code;
+
+use crate::suppression::SuppressionReason;
+
+/// Error al enviar un correo, clasificado para que quien llama decida si reintentar
+#[derive(Debug)]
+pub enum EmailError {
+    /// Dirección de remitente o destinatario mal formada
+    InvalidAddress { address: String, source: AddressError },
+    /// No se pudo construir el mensaje
+    Build(String),
+    /// El servidor rechazó las credenciales o no hay un mecanismo de autenticación compatible
+    Auth(String),
+    /// No se pudo llegar al transporte: relay SMTP inaccesible o directorio de correo no escribible
+    Connection(String),
+    /// Fallo al negociar TLS
+    Tls(String),
+    /// El servidor no ofrece algo que el envío necesita: STARTTLS, un mecanismo de autenticación
+    /// compatible, SMTPUTF8 u 8BITMIME. Hay que cambiar la configuración de uno de los dos lados
+    Unsupported(String),
+    /// Respuesta SMTP 5xx: reintentar no servirá
+    PermanentRejection { code: u16, message: String },
+    /// Respuesta SMTP 4xx: el servidor pide reintentar más tarde
+    TransientRejection { code: u16, message: String },
+    /// El servidor no respondió a tiempo
+    Timeout,
+    /// El destinatario está en la lista de supresión y no se le envía nada
+    Suppressed { address: String, reason: SuppressionReason },
+}
+
+/// Códigos 5xx que indican credenciales rechazadas (RFC 4954)
+const AUTH_CODES: &[u16] = &[530, 534, 535, 538];
+
+impl EmailError {
+    /// `true` si el mismo envío puede funcionar más tarde sin cambiar nada
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            EmailError::Connection(_) | EmailError::TransientRejection { .. } | EmailError::Timeout
+        )
+    }
+
+    /// Código de respuesta SMTP, si el servidor llegó a responder con un error
+    pub fn smtp_code(&self) -> Option<u16> {
+        match self {
+            EmailError::PermanentRejection { code, .. } | EmailError::TransientRejection { code, .. } => Some(*code),
+            _ => None,
+        }
+    }
+
+    /// Clasifica una respuesta negativa del servidor SMTP por su código
+    fn from_reply(code: u16, message: String) -> Self {
+        match code {
+            400..=499 => EmailError::TransientRejection { code, message },
+            code if AUTH_CODES.contains(&code) => EmailError::Auth(message),
+            _ => EmailError::PermanentRejection { code, message },
+        }
+    }
+
+    pub(crate) fn invalid_address(address: &str, source: AddressError) -> Self {
+        EmailError::InvalidAddress {
+            address: address.to_string(),
+            source,
+        }
+    }
+}
+
+impl fmt::Display for EmailError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EmailError::InvalidAddress { address, source } => write!(f, "Dirección '{}' inválida: {}", address, source),
+            EmailError::Build(e) => write!(f, "No se pudo construir el correo: {}", e),
+            EmailError::Auth(e) => write!(f, "Autenticación SMTP rechazada: {}", e),
+            EmailError::Connection(e) => write!(f, "No se pudo conectar con el transporte de correo: {}", e),
+            EmailError::Tls(e) => write!(f, "Error de TLS: {}", e),
+            EmailError::Unsupported(e) => write!(f, "El servidor SMTP no admite lo que requiere el envío: {}", e),
+            EmailError::PermanentRejection { code, message } => write!(f, "El servidor rechazó el correo ({}): {}", code, message),
+            EmailError::TransientRejection { code, message } => {
+                write!(f, "El servidor rechazó el correo temporalmente ({}): {}", code, message)
+            }
+            EmailError::Timeout => f.write_str("Tiempo de espera agotado con el servidor SMTP"),
+            EmailError::Suppressed { address, reason } => write!(f, "La dirección '{}' está suprimida ({})", address, reason),
+        }
+    }
+}
+
+impl std::error::Error for EmailError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EmailError::InvalidAddress { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<lettre::error::Error> for EmailError {
+    fn from(e: lettre::error::Error) -> Self {
+        EmailError::Build(e.to_string())
+    }
+}
+
+impl From<lettre::transport::file::Error> for EmailError {
+    fn from(e: lettre::transport::file::Error) -> Self {
+        EmailError::Connection(e.to_string())
+    }
+}
+
+impl From<smtp::Error> for EmailError {
+    fn from(e: smtp::Error) -> Self {
+        let message = e.to_string();
+
+        if timed_out(&e) {
+            return EmailError::Timeout;
+        }
+        if e.is_tls() {
+            return EmailError::Tls(message);
+        }
+        if let Some(code) = e.status() {
+            return EmailError::from_reply(reply_code(code), message);
+        }
+        // lettre marca como error del cliente lo que el servidor no anuncia en EHLO
+        if e.is_client() {
+            return EmailError::Unsupported(message);
+        }
+        EmailError::Connection(message)
+    }
+}
+
+/// `is_timeout` solo reconoce `TimedOut`, pero en Unix un socket con tiempo de espera
+/// devuelve `WouldBlock` al agotarlo
+fn timed_out(e: &smtp::Error) -> bool {
+    let mut source = std::error::Error::source(e);
+    while let Some(err) = source {
+        if let Some(io) = err.downcast_ref::<std::io::Error>() {
+            return matches!(io.kind(), std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock);
+        }
+        source = err.source();
+    }
+    e.is_timeout()
+}
+
+/// Código de respuesta como número, p. ej. 550
+fn reply_code(code: Code) -> u16 {
+    code.severity as u16 * 100 + code.category as u16 * 10 + code.detail as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{accept_all, StubSmtp};
+    use lettre::transport::smtp::authentication::Credentials;
+    use lettre::transport::smtp::client::{Tls, TlsParameters};
+    use lettre::{AsyncSmtpTransport, AsyncTransport, Message, SmtpTransport, Tokio1Executor, Transport};
+    use std::net::TcpListener;
+    use std::time::Duration;
+
+    #[test]
+    fn classifies_smtp_replies() {
+        let transient = EmailError::from_reply(452, "buzón lleno".to_string());
+        assert!(matches!(transient, EmailError::TransientRejection { code: 452, .. }));
+        assert!(transient.is_retryable());
+
+        let permanent = EmailError::from_reply(550, "usuario desconocido".to_string());
+        assert_eq!(permanent.smtp_code(), Some(550));
+        assert!(!permanent.is_retryable());
+
+        let auth = EmailError::from_reply(535, "credenciales inválidas".to_string());
+        assert!(matches!(auth, EmailError::Auth(_)));
+        assert!(!auth.is_retryable());
+
+        let invalid = "sin-arroba".parse::<lettre::Address>().unwrap_err();
+        assert!(!EmailError::invalid_address("sin-arroba", invalid).is_retryable());
+    }
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn message() -> Message {
+        Message::builder()
+            .from("no-reply@tusitio.com".parse().unwrap())
+            .to("ops@tusitio.com".parse().unwrap())
+            .subject("Hola")
+            .body("Hola".to_string())
+            .unwrap()
+    }
+
+    /// Envía un correo al servidor de pruebas y clasifica el error de lettre
+    fn send_to(stub: &StubSmtp, credentials: bool, timeout: Duration) -> EmailError {
+        let mut builder = SmtpTransport::builder_dangerous("127.0.0.1").port(stub.port()).timeout(Some(timeout));
+        if credentials {
+            builder = builder.credentials(Credentials::new("usuario".to_string(), "clave".to_string()));
+        }
+        builder.build().send(&message()).unwrap_err().into()
+    }
+
+    #[test]
+    fn classifies_smtp_errors_from_a_server() {
+        let rcpt = |reply: &'static str| {
+            StubSmtp::start(move |command| {
+                if command.starts_with("RCPT") {
+                    Some(reply.to_string())
+                } else {
+                    accept_all(command, &[])
+                }
+            })
+        };
+        let transient = send_to(&rcpt("452 4.2.2 Buzón lleno"), false, TIMEOUT);
+        assert!(matches!(transient, EmailError::TransientRejection { code: 452, .. }), "{:?}", transient);
+        let permanent = send_to(&rcpt("550 5.1.1 Usuario desconocido"), false, TIMEOUT);
+        assert!(matches!(permanent, EmailError::PermanentRejection { code: 550, .. }), "{:?}", permanent);
+
+        let auth = StubSmtp::start(|command| {
+            if command.starts_with("AUTH") {
+                Some("535 5.7.8 Credenciales inválidas".to_string())
+            } else {
+                accept_all(command, &["AUTH PLAIN LOGIN"])
+            }
+        });
+        let rejected = send_to(&auth, true, TIMEOUT);
+        assert!(matches!(rejected, EmailError::Auth(_)), "{:?}", rejected);
+
+        // Con credenciales y un servidor que no anuncia AUTH
+        let without_auth = StubSmtp::start(|command| accept_all(command, &[]));
+        let unsupported = send_to(&without_auth, true, TIMEOUT);
+        assert!(matches!(unsupported, EmailError::Unsupported(_)), "{:?}", unsupported);
+        assert!(!unsupported.is_retryable());
+
+        let silent = StubSmtp::with_greeting(None, |_| None);
+        let timeout = send_to(&silent, false, Duration::from_millis(200));
+        assert!(matches!(timeout, EmailError::Timeout), "{:?}", timeout);
+        assert!(timeout.is_retryable());
+
+        let closed = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let refused: EmailError = SmtpTransport::builder_dangerous("127.0.0.1")
+            .port(closed)
+            .build()
+            .send(&message())
+            .unwrap_err()
+            .into();
+        assert!(matches!(refused, EmailError::Connection(_)), "{:?}", refused);
+        assert!(refused.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn classifies_failed_tls_handshakes() {
+        // El servidor acepta STARTTLS pero sigue hablando en claro. lettre 0.10 informa del handshake
+        // fallido como error de conexión; `is_tls` solo lo marcan los fallos al leer el certificado
+        let stub = StubSmtp::start(|command| match command {
+            "STARTTLS" => Some("220 Adelante".to_string()),
+            command => accept_all(command, &["STARTTLS"]),
+        });
+        let tls = TlsParameters::new("localhost".to_string()).unwrap();
+        let transport = AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous("127.0.0.1")
+            .port(stub.port())
+            .tls(Tls::Required(tls))
+            .timeout(Some(Duration::from_secs(5)))
+            .build();
+        let error: EmailError = transport.send(message()).await.unwrap_err().into();
+        assert!(matches!(error, EmailError::Connection(_)), "{:?}", error);
+    }
+}
@@ -1,7 +1,21 @@
+pub mod bounce;
+pub mod capture;
+pub mod dkim;
 pub mod email;
+pub mod error;
+pub mod probe;
+pub mod suppression;
+#[cfg(test)]
+mod testing;
 
 // Re-export main types for easy access
-pub use email::{EmailSender, EmailContent};
+pub use bounce::{Bounce, BounceKind, Report};
+pub use capture::{CapturedEmail, MailCapture};
+pub use dkim::DkimSigner;
+pub use email::{is_valid_address, EmailSender, EmailContent};
+pub use error::EmailError;
+pub use probe::SmtpProbe;
+pub use suppression::{Suppression, SuppressionList, SuppressionReason};
 
 #[cfg(test)]
 mod tests {
@@ -12,4 +26,55 @@ mod tests {
         let result = 2 + 2;
         assert_eq!(result, 4);
     }
+
+    #[tokio::test]
+    async fn capture_transport_keeps_emails_in_memory() {
+        let sender = EmailSender::capture("no-reply@tusitio.com");
+        sender
+            .send_html_email(&["ops@tusitio.com".to_string()], "Hola", "<p>Hola</p>")
+            .await
+            .unwrap();
+
+        let emails = sender.captured().unwrap().emails();
+        assert_eq!(sender.transport_name(), "capture");
+        assert_eq!(emails.len(), 1);
+        assert_eq!(emails[0].to, "ops@tusitio.com");
+        assert!(emails[0].is_html);
+        assert!(!sender.probe().is_usable());
+    }
+
+    #[test]
+    fn tests_the_local_transports() {
+        let dir = std::env::temp_dir().join(format!("mail-test-connection-{}", std::process::id()));
+        let sender = EmailSender::file(&dir, "no-reply@tusitio.com").unwrap();
+        sender.test_connection().unwrap();
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+
+        // Un directorio borrado después de crear el enviador ya no sirve
+        std::fs::remove_dir_all(&dir).unwrap();
+        let error = sender.test_connection().unwrap_err();
+        assert!(matches!(error, EmailError::Connection(_)), "{:?}", error);
+
+        EmailSender::capture("no-reply@tusitio.com").test_connection().unwrap();
+    }
+
+    #[tokio::test]
+    async fn refuses_suppressed_recipients() {
+        let suppressions = SuppressionList::new();
+        suppressions.add("rebota@tusitio.com", SuppressionReason::HardBounce, None).unwrap();
+        let sender = EmailSender::capture("no-reply@tusitio.com").with_suppressions(suppressions);
+
+        let error = sender
+            .send_html_email(&["Rebota@tusitio.com".to_string()], "Hola", "<p>Hola</p>")
+            .await
+            .unwrap_err();
+        assert!(matches!(error, EmailError::Suppressed { reason: SuppressionReason::HardBounce, .. }));
+        assert!(!error.is_retryable());
+
+        // Con más destinatarios solo se salta el suprimido
+        let recipients = ["rebota@tusitio.com".to_string(), "ops@tusitio.com".to_string()];
+        sender.send_html_email(&recipients, "Hola", "<p>Hola</p>").await.unwrap();
+        let emails = sender.captured().unwrap().emails();
+        assert_eq!(emails.iter().map(|e| e.to.as_str()).collect::<Vec<_>>(), ["ops@tusitio.com"]);
+    }
 }
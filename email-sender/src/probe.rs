@@ -0,0 +1,152 @@
+use std::time::{Duration, Instant};
+
+use lettre::transport::smtp::authentication::{Credentials, Mechanism};
+use lettre::transport::smtp::client::{SmtpConnection, TlsParameters};
+use lettre::transport::smtp::commands::Noop;
+use lettre::transport::smtp::extension::ClientId;
+use lettre::transport::smtp::SUBMISSIONS_PORT;
+use serde::Serialize;
+
+/// Resultado de una sonda de conectividad SMTP que no envía correo
+#[derive(Debug, Clone, Serialize)]
+pub struct SmtpProbe {
+    /// Se pudo abrir la conexión y el servidor respondió al saludo y a EHLO
+    pub reachable: bool,
+    /// La conexión está cifrada con TLS
+    pub encrypted: bool,
+    /// Versión de TLS negociada, p. ej. `TLSv1.3`. Siempre `None` con el backend native-tls
+    /// que usa lettre, porque no expone la versión; se rellenará si se cambia a rustls
+    pub tls_version: Option<String>,
+    /// Las credenciales configuradas fueron aceptadas
+    pub auth_ok: bool,
+    /// Nombre anunciado por el servidor en su saludo
+    pub banner: Option<String>,
+    /// Tiempo total de la sonda en milisegundos
+    pub latency_ms: u64,
+    /// Primer error encontrado, si lo hubo
+    pub error: Option<String>,
+}
+
+impl SmtpProbe {
+    /// El relay es utilizable para enviar: alcanzable, cifrado y autenticado
+    pub fn is_usable(&self) -> bool {
+        self.reachable && self.encrypted && self.auth_ok
+    }
+}
+
+/// Conecta con TLS, autentica y termina con NOOP/QUIT, igual que la
+/// comprobación de conexión del transporte, pero registrando cada paso
+pub(crate) fn run_probe(server: &str, credentials: &Credentials, timeout: Duration) -> SmtpProbe {
+    match TlsParameters::new(server.to_string()) {
+        Ok(tls) => probe_at(server, SUBMISSIONS_PORT, Some(&tls), credentials, timeout),
+        Err(e) => SmtpProbe {
+            reachable: false,
+            encrypted: false,
+            tls_version: None,
+            auth_ok: false,
+            banner: None,
+            latency_ms: 0,
+            error: Some(format!("Parámetros TLS inválidos: {}", e)),
+        },
+    }
+}
+
+/// Sonda contra `server:port`, con TLS implícito si se dan parámetros
+fn probe_at(
+    server: &str,
+    port: u16,
+    tls: Option<&TlsParameters>,
+    credentials: &Credentials,
+    timeout: Duration,
+) -> SmtpProbe {
+    let started = Instant::now();
+    let mut probe = SmtpProbe {
+        reachable: false,
+        encrypted: false,
+        tls_version: None,
+        auth_ok: false,
+        banner: None,
+        latency_ms: 0,
+        error: None,
+    };
+
+    if let Err(e) = probe_steps((server, port), tls, credentials, timeout, &mut probe) {
+        probe.error = Some(e);
+    }
+
+    probe.latency_ms = started.elapsed().as_millis() as u64;
+    probe
+}
+
+fn probe_steps(
+    addr: (&str, u16),
+    tls: Option<&TlsParameters>,
+    credentials: &Credentials,
+    timeout: Duration,
+    probe: &mut SmtpProbe,
+) -> Result<(), String> {
+    let mut conn = SmtpConnection::connect(addr, Some(timeout), &ClientId::default(), tls, None)
+        .map_err(|e| format!("No se pudo conectar con el servidor SMTP: {}", e))?;
+
+    probe.reachable = true;
+    probe.encrypted = conn.is_encrypted();
+    probe.banner = Some(conn.server_info().name().to_string());
+
+    let auth = conn.auth(&[Mechanism::Plain, Mechanism::Login], credentials);
+    probe.auth_ok = auth.is_ok();
+
+    let noop = conn.command(Noop);
+    let _ = conn.quit();
+
+    auth.map_err(|e| format!("Autenticación SMTP rechazada: {}", e))?;
+    noop.map_err(|e| format!("El servidor SMTP no respondió a NOOP: {}", e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{accept_all, StubSmtp};
+
+    fn credentials() -> Credentials {
+        Credentials::new("usuario".to_string(), "clave".to_string())
+    }
+
+    #[test]
+    fn probes_a_server_without_sending_mail() {
+        let stub = StubSmtp::start(|command| match command.split_whitespace().next() {
+            Some("MAIL" | "RCPT" | "DATA") => panic!("la sonda no debe enviar correo: {}", command),
+            _ => accept_all(command, &["AUTH PLAIN LOGIN"]),
+        });
+        let probe = probe_at("127.0.0.1", stub.port(), None, &credentials(), Duration::from_secs(5));
+
+        assert!(probe.reachable && probe.auth_ok, "{:?}", probe);
+        assert_eq!(probe.banner.as_deref(), Some("stub.local"));
+        assert_eq!(probe.error, None);
+        // Sin TLS el relay no se da por utilizable
+        assert!(!probe.encrypted);
+        assert_eq!(probe.tls_version, None);
+        assert!(!probe.is_usable());
+    }
+
+    #[test]
+    fn reports_rejected_credentials_and_unreachable_servers() {
+        let stub = StubSmtp::start(|command| {
+            if command.starts_with("AUTH") {
+                Some("535 5.7.8 Credenciales inválidas".to_string())
+            } else {
+                accept_all(command, &["AUTH PLAIN LOGIN"])
+            }
+        });
+        let probe = probe_at("127.0.0.1", stub.port(), None, &credentials(), Duration::from_secs(5));
+        assert!(probe.reachable && !probe.auth_ok, "{:?}", probe);
+        assert!(probe.error.unwrap().starts_with("Autenticación SMTP rechazada"));
+
+        // Un servidor que habla en claro no supera el handshake del TLS implícito
+        let plain = StubSmtp::start(|command| accept_all(command, &[]));
+        let tls = TlsParameters::new("localhost".to_string()).unwrap();
+        let probe = probe_at("127.0.0.1", plain.port(), Some(&tls), &credentials(), Duration::from_secs(5));
+        assert!(!probe.reachable);
+        assert!(probe.error.unwrap().starts_with("No se pudo conectar"));
+    }
+}
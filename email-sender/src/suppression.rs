@@ -0,0 +1,251 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Motivo por el que no se vuelve a escribir a una dirección
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SuppressionReason {
+    /// El servidor del destinatario rechazó el correo de forma permanente (DSN 5.x.x)
+    HardBounce,
+    /// El destinatario marcó un correo como spam (informe ARF)
+    Complaint,
+}
+
+impl SuppressionReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SuppressionReason::HardBounce => "hard_bounce",
+            SuppressionReason::Complaint => "complaint",
+        }
+    }
+}
+
+impl fmt::Display for SuppressionReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Dirección suprimida
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Suppression {
+    pub address: String,
+    pub reason: SuppressionReason,
+    /// Estado DSN y diagnóstico del servidor remoto, o tipo de queja
+    pub detail: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Direcciones a las que [`crate::EmailSender`] se niega a enviar.
+/// Los clones comparten la lista; si se abrió con [`SuppressionList::open`] cada cambio se guarda en el archivo.
+/// El JSON se genera con el bloqueo tomado pero el archivo se escribe después, para que las consultas
+/// de cada envío no esperen al disco. Los cambios hacen E/S bloqueante: desde async, con `spawn_blocking`.
+#[derive(Debug, Clone, Default)]
+pub struct SuppressionList {
+    entries: Arc<RwLock<BTreeMap<String, Suppression>>>,
+    /// Versión de la lista, incrementada con el bloqueo de escritura en cada cambio
+    version: Arc<AtomicU64>,
+    store: Option<Arc<Store>>,
+}
+
+#[derive(Debug)]
+struct Store {
+    path: PathBuf,
+    /// Versión guardada en el archivo, para no pisar una copia más reciente con una anterior
+    saved: Mutex<u64>,
+}
+
+/// Copia de la lista tomada con el bloqueo, pendiente de escribir
+struct Snapshot {
+    version: u64,
+    json: Vec<u8>,
+}
+
+impl SuppressionList {
+    /// Lista solo en memoria
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Carga la lista de `path` (vacía si no existe) y la guarda ahí en cada cambio
+    pub fn open(path: &Path) -> Result<Self> {
+        let entries: Vec<Suppression> = match std::fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(Self {
+            entries: Arc::new(RwLock::new(entries.into_iter().map(|s| (normalize(&s.address), s)).collect())),
+            version: Arc::default(),
+            store: Some(Arc::new(Store {
+                path: path.to_path_buf(),
+                saved: Mutex::new(0),
+            })),
+        })
+    }
+
+    pub fn get(&self, address: &str) -> Option<Suppression> {
+        self.entries.read().unwrap().get(&normalize(address)).cloned()
+    }
+
+    pub fn contains(&self, address: &str) -> bool {
+        self.entries.read().unwrap().contains_key(&normalize(address))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Todas las entradas, de la más reciente a la más antigua
+    pub fn list(&self) -> Vec<Suppression> {
+        let mut entries: Vec<Suppression> = self.entries.read().unwrap().values().cloned().collect();
+        entries.sort_by_key(|s| Reverse(s.created_at));
+        entries
+    }
+
+    /// Suprime `address`; devuelve `false` si ya lo estaba, en cuyo caso se conserva la entrada original
+    pub fn add(&self, address: &str, reason: SuppressionReason, detail: Option<String>) -> Result<bool> {
+        let snapshot = {
+            let mut entries = self.entries.write().unwrap();
+            let key = normalize(address);
+            if entries.contains_key(&key) {
+                return Ok(false);
+            }
+            entries.insert(
+                key,
+                Suppression {
+                    address: address.trim().to_string(),
+                    reason,
+                    detail,
+                    created_at: Utc::now(),
+                },
+            );
+            self.snapshot(&entries)?
+        };
+        self.save(snapshot)?;
+        Ok(true)
+    }
+
+    /// Quita `address` de la lista; `false` si no estaba
+    pub fn remove(&self, address: &str) -> Result<bool> {
+        let snapshot = {
+            let mut entries = self.entries.write().unwrap();
+            if entries.remove(&normalize(address)).is_none() {
+                return Ok(false);
+            }
+            self.snapshot(&entries)?
+        };
+        self.save(snapshot)?;
+        Ok(true)
+    }
+
+    /// Vacía la lista y devuelve cuántas entradas tenía
+    pub fn clear(&self) -> Result<usize> {
+        let (removed, snapshot) = {
+            let mut entries = self.entries.write().unwrap();
+            let removed = entries.len();
+            entries.clear();
+            (removed, self.snapshot(&entries)?)
+        };
+        self.save(snapshot)?;
+        Ok(removed)
+    }
+
+    /// Serializa la lista tras un cambio; se llama con el bloqueo de escritura tomado
+    fn snapshot(&self, entries: &BTreeMap<String, Suppression>) -> Result<Option<Snapshot>> {
+        let version = self.version.fetch_add(1, Ordering::SeqCst) + 1;
+        if self.store.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(Snapshot {
+            version,
+            json: serde_json::to_vec_pretty(&entries.values().collect::<Vec<_>>())?,
+        }))
+    }
+
+    /// Escribe a un archivo temporal y lo renombra para no dejar el archivo a medias.
+    /// Si otro cambio ya guardó una versión posterior, esta copia se descarta.
+    fn save(&self, snapshot: Option<Snapshot>) -> Result<()> {
+        let (Some(store), Some(snapshot)) = (&self.store, snapshot) else {
+            return Ok(());
+        };
+        let mut saved = store.saved.lock().unwrap();
+        if *saved >= snapshot.version {
+            return Ok(());
+        }
+        if let Some(dir) = store.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let tmp = store.path.with_extension("tmp");
+        std::fs::write(&tmp, snapshot.json)?;
+        std::fs::rename(&tmp, &store.path)?;
+        *saved = snapshot.version;
+        Ok(())
+    }
+}
+
+/// Las direcciones se comparan sin distinguir mayúsculas
+fn normalize(address: &str) -> String {
+    address.trim().to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn persists_every_change() {
+        let path = std::env::temp_dir().join(format!("suppressions-{}.json", std::process::id()));
+        let list = SuppressionList::open(&path).unwrap();
+        assert!(list.add("Ana@Example.com", SuppressionReason::HardBounce, Some("5.1.1".to_string())).unwrap());
+        assert!(!list.add("ana@example.com", SuppressionReason::Complaint, None).unwrap());
+        assert!(list.add("luis@example.com", SuppressionReason::Complaint, None).unwrap());
+
+        let reopened = SuppressionList::open(&path).unwrap();
+        assert_eq!(reopened.len(), 2);
+        assert_eq!(reopened.get("ANA@example.com").unwrap().reason, SuppressionReason::HardBounce);
+
+        assert!(reopened.remove("luis@example.com").unwrap());
+        assert!(!reopened.remove("luis@example.com").unwrap());
+        assert_eq!(SuppressionList::open(&path).unwrap().len(), 1);
+        assert_eq!(reopened.clear().unwrap(), 1);
+        assert!(SuppressionList::open(&path).unwrap().is_empty());
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn concurrent_changes_keep_the_latest_list_on_disk() {
+        let path = std::env::temp_dir().join(format!("suppressions-concurrent-{}.json", std::process::id()));
+        let list = SuppressionList::open(&path).unwrap();
+        let threads: Vec<_> = (0..8)
+            .map(|i| {
+                let list = list.clone();
+                std::thread::spawn(move || {
+                    for j in 0..10 {
+                        list.add(&format!("{}-{}@example.com", i, j), SuppressionReason::HardBounce, None).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        assert_eq!(SuppressionList::open(&path).unwrap().len(), 80);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,88 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread;
+
+/// Servidor SMTP de pruebas en `127.0.0.1` que atiende cada conexión en su propio hilo.
+/// Saluda con `220`, y cada línea recibida se responde con lo que devuelva `reply` para ella
+/// (sin el CRLF final); `None` deja al cliente esperando. Tras un `354` se lee el mensaje hasta
+/// la línea `.`, que también se pasa a `reply`.
+pub(crate) struct StubSmtp {
+    addr: SocketAddr,
+}
+
+impl StubSmtp {
+    pub(crate) fn start(reply: impl Fn(&str) -> Option<String> + Send + Sync + 'static) -> Self {
+        Self::with_greeting(Some("220 stub.local ESMTP"), reply)
+    }
+
+    /// Como [`StubSmtp::start`], con otro saludo o ninguno
+    pub(crate) fn with_greeting(
+        greeting: Option<&str>,
+        reply: impl Fn(&str) -> Option<String> + Send + Sync + 'static,
+    ) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("puerto local");
+        let addr = listener.local_addr().unwrap();
+        let greeting: Option<Arc<str>> = greeting.map(|g| format!("{}\r\n", g).into());
+        let reply = Arc::new(reply);
+        thread::spawn(move || {
+            // El pool de lettre abre una conexión de reserva al crearse, que queda abierta
+            // mientras el envío usa otra: se atienden a la vez
+            for stream in listener.incoming().flatten() {
+                let (greeting, reply) = (greeting.clone(), reply.clone());
+                thread::spawn(move || serve(stream, greeting.as_deref(), reply.as_ref()));
+            }
+        });
+        Self { addr }
+    }
+
+    pub(crate) fn port(&self) -> u16 {
+        self.addr.port()
+    }
+}
+
+fn serve(mut stream: TcpStream, greeting: Option<&str>, reply: &impl Fn(&str) -> Option<String>) -> std::io::Result<()> {
+    let Some(greeting) = greeting else {
+        // Sin saludo el cliente espera hasta agotar su tiempo; se mantiene la conexión abierta
+        let mut sink = String::new();
+        return BufReader::new(stream).read_line(&mut sink).map(drop);
+    };
+    stream.write_all(greeting.as_bytes())?;
+
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut in_data = false;
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 0 {
+        let command = line.trim_end_matches(['\r', '\n']).to_string();
+        line.clear();
+        if in_data && command != "." {
+            continue;
+        }
+        in_data = false;
+
+        let Some(response) = reply(&command) else { continue };
+        in_data = response.starts_with("354");
+        stream.write_all(format!("{}\r\n", response).as_bytes())?;
+        if command.eq_ignore_ascii_case("QUIT") {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Respuestas de un servidor que acepta todo, anunciando `extensions` en EHLO
+pub(crate) fn accept_all(command: &str, extensions: &[&str]) -> Option<String> {
+    let verb = command.split_whitespace().next().unwrap_or_default().to_ascii_uppercase();
+    Some(match verb.as_str() {
+        "EHLO" => std::iter::once("stub.local")
+            .chain(extensions.iter().copied())
+            .enumerate()
+            .map(|(i, line)| format!("250{}{}", if i == extensions.len() { ' ' } else { '-' }, line))
+            .collect::<Vec<_>>()
+            .join("\r\n"),
+        "DATA" => "354 Termina con .".to_string(),
+        "QUIT" => "221 Adiós".to_string(),
+        "AUTH" => "235 Autenticado".to_string(),
+        _ => "250 OK".to_string(),
+    })
+}